@@ -0,0 +1,51 @@
+// Time the end of an era, with its election, for a large set of stakers. Run it with
+// `cargo bench --bench era_end`.
+use code_challenge_1::staking::StakingPallet;
+use code_challenge_1::Runtime;
+use std::time::Instant;
+
+const VALIDATORS: u64 = 200;
+const NOMINATORS: u64 = 10_000;
+const TARGETS: u64 = 16;
+const ERAS: u32 = 5;
+
+fn main() {
+    let mut staking = StakingPallet::<Runtime>::new();
+    for validator in 1..=VALIDATORS {
+        staking.set_balance(validator, 10_000);
+        staking.stake(validator, 1_000 + validator).unwrap();
+        staking.validate(validator).unwrap();
+        staking.set_keys(validator, validator.to_le_bytes().to_vec()).unwrap();
+    }
+    staking.parameters.max_validators = 100;
+    for nominator in VALIDATORS + 1..=VALIDATORS + NOMINATORS {
+        staking.set_balance(nominator, 1_000);
+        staking.stake(nominator, 50 + nominator % 500).unwrap();
+        // Spread each nominator's targets over the validator set
+        let targets = (0..TARGETS).map(|offset| (nominator * 7 + offset * 13) % VALIDATORS + 1);
+        let mut targets: Vec<u64> = targets.collect();
+        targets.sort();
+        targets.dedup();
+        staking.nominate(nominator, targets).unwrap();
+    }
+
+    let mut block = 0;
+    let mut total = 0u128;
+    for era_index in 1..=ERAS {
+        // Only the last block of the era does the era-end work
+        block += 9;
+        let start = Instant::now();
+        staking.on_finalize(block);
+        let elapsed = start.elapsed();
+        total += elapsed.as_micros();
+        block += 1;
+        let active = staking.active_validators().len();
+        println!("era {}: {} validators elected in {:?}", era_index, active, elapsed);
+    }
+    println!(
+        "{} nominators, {} validators: {} us per era end on average",
+        NOMINATORS,
+        VALIDATORS,
+        total / ERAS as u128
+    );
+}
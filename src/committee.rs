@@ -0,0 +1,376 @@
+use crate::governance::{GovernanceConfig, GovernanceError, GovernancePallet};
+use crate::system::{Origin, SystemConfig, SystemPallet};
+use std::collections::HashMap;
+use std::fmt;
+
+pub trait CommitteeConfig: GovernanceConfig {
+    // The runtime event type, able to carry committee events into the system pallet
+    type RuntimeEvent: From<CommitteeEvent<Self>> + Into<<Self as SystemConfig>::RuntimeEvent>;
+}
+
+// Privileged governance operations the committee can decide on
+pub enum CommitteeAction<T: SystemConfig> {
+    Veto(u32),          // Veto an approved proposal
+    FlagMalicious(u32), // Flag an open proposal so its deposit is slashed if rejected
+    FastTrack {
+        proposal_id: u32,
+        new_deadline: T::BlockNumber,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecisionStatus {
+    Open,
+    Executed(Result<(), GovernanceError>), // Reached the threshold and was dispatched
+}
+
+pub struct Decision<T: CommitteeConfig> {
+    proposer: T::AccountId,
+    action: CommitteeAction<T>,
+    approvals: Vec<T::AccountId>, // Members who approved, including the proposer
+    status: DecisionStatus,
+}
+
+impl<T: CommitteeConfig> Decision<T> {
+    // Get the member who proposed the decision
+    pub fn proposer(&self) -> &T::AccountId {
+        &self.proposer
+    }
+
+    // Get the action the decision dispatches once approved
+    pub fn action(&self) -> &CommitteeAction<T> {
+        &self.action
+    }
+
+    // Get the current status of the decision
+    pub fn status(&self) -> &DecisionStatus {
+        &self.status
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitteeError {
+    BadOrigin,
+    NotMember,
+    ZeroWeight,
+    DuplicateMember,
+    InvalidThreshold,
+    DecisionNotFound,
+    DecisionClosed,
+    AlreadyApproved,
+}
+
+impl fmt::Display for CommitteeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            CommitteeError::BadOrigin => "Origin is not allowed to perform this operation",
+            CommitteeError::NotMember => "Account is not a committee member",
+            CommitteeError::ZeroWeight => "Committee members must have a non-zero weight",
+            CommitteeError::DuplicateMember => "Account is listed more than once",
+            CommitteeError::InvalidThreshold => {
+                "Threshold must be a majority of the total weight and not exceed it"
+            },
+            CommitteeError::DecisionNotFound => "No decision found with the given ID",
+            CommitteeError::DecisionClosed => "Decision has already been executed",
+            CommitteeError::AlreadyApproved => "Member has already approved this decision",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for CommitteeError {}
+
+pub enum CommitteeEvent<T: CommitteeConfig> {
+    MembersChanged {
+        members: Vec<(T::AccountId, u32)>,
+        threshold: u32,
+    },
+    DecisionProposed {
+        decision_id: u32,
+        proposer: T::AccountId,
+    },
+    DecisionApproved {
+        decision_id: u32,
+        member: T::AccountId,
+        approved_weight: u32,
+    },
+    DecisionExecuted {
+        decision_id: u32,
+        result: Result<(), GovernanceError>,
+    },
+}
+
+pub struct CommitteePallet<T: CommitteeConfig> {
+    members: Vec<(T::AccountId, u32)>, // Members and their voting weights
+    threshold: u32, // Approved weight a decision needs before it is dispatched
+    decisions: HashMap<u32, Decision<T>>,
+    next_decision_id: u32,
+}
+
+impl<T: CommitteeConfig> CommitteePallet<T> {
+    pub fn new() -> Self {
+        Self {
+            members: Vec::new(),
+            threshold: 0,
+            decisions: HashMap::new(),
+            next_decision_id: 0,
+        }
+    }
+
+    // Replace the committee with weighted `members` and the weight decisions need. Requires
+    // root. The threshold must be a majority of the total weight. Approvals on open decisions
+    // by accounts leaving the committee are dropped.
+    pub fn set_members(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        members: Vec<(T::AccountId, u32)>,
+        threshold: u32,
+    ) -> Result<(), CommitteeError> {
+        if !matches!(origin, Origin::Root) {
+            return Err(CommitteeError::BadOrigin);
+        }
+
+        let mut total_weight = 0u32;
+        for (index, (who, weight)) in members.iter().enumerate() {
+            if *weight == 0 {
+                return Err(CommitteeError::ZeroWeight);
+            }
+            if members[..index].iter().any(|(other, _)| other == who) {
+                return Err(CommitteeError::DuplicateMember);
+            }
+            total_weight = total_weight.saturating_add(*weight);
+        }
+        if threshold <= total_weight / 2 || threshold > total_weight {
+            return Err(CommitteeError::InvalidThreshold);
+        }
+
+        self.members = members.clone();
+        self.threshold = threshold;
+        let is_member = |who: &T::AccountId| members.iter().any(|(member, _)| member == who);
+        for decision in self.decisions.values_mut() {
+            if decision.status == DecisionStatus::Open {
+                decision.approvals.retain(|who| is_member(who));
+            }
+        }
+
+        Self::deposit_event(system, CommitteeEvent::MembersChanged { members, threshold });
+        Ok(())
+    }
+
+    // Get the voting weight of a member, or None if the account is not on the committee
+    pub fn member_weight(&self, who: &T::AccountId) -> Option<u32> {
+        self.members.iter().find(|(member, _)| member == who).map(|(_, weight)| *weight)
+    }
+
+    // Get the approved weight a decision needs before it is dispatched
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    // Propose a decision, counting the proposer's approval. It is dispatched straight away if
+    // the proposer's weight alone reaches the threshold.
+    pub fn propose(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        governance: &mut GovernancePallet<T>,
+        proposer: T::AccountId,
+        action: CommitteeAction<T>,
+    ) -> Result<u32, CommitteeError> {
+        if self.member_weight(&proposer).is_none() {
+            return Err(CommitteeError::NotMember);
+        }
+
+        let decision_id = self.next_decision_id;
+        let decision = Decision {
+            proposer: proposer.clone(),
+            action,
+            approvals: Vec::new(),
+            status: DecisionStatus::Open,
+        };
+        self.decisions.insert(decision_id, decision);
+        self.next_decision_id += 1;
+
+        let event = CommitteeEvent::DecisionProposed { decision_id, proposer: proposer.clone() };
+        Self::deposit_event(system, event);
+
+        self.approve(system, governance, proposer, decision_id)?;
+        Ok(decision_id)
+    }
+
+    // Approve an open decision with the member's weight, dispatching it as the committee
+    // origin once the approved weight reaches the threshold
+    pub fn approve(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        governance: &mut GovernancePallet<T>,
+        member: T::AccountId,
+        decision_id: u32,
+    ) -> Result<(), CommitteeError> {
+        if self.member_weight(&member).is_none() {
+            return Err(CommitteeError::NotMember);
+        }
+
+        match self.decisions.get_mut(&decision_id) {
+            Some(decision) => {
+                if decision.status != DecisionStatus::Open {
+                    return Err(CommitteeError::DecisionClosed);
+                }
+                if decision.approvals.contains(&member) {
+                    return Err(CommitteeError::AlreadyApproved);
+                }
+                decision.approvals.push(member.clone());
+            },
+            None => return Err(CommitteeError::DecisionNotFound),
+        }
+
+        let approved_weight = self.approved_weight(decision_id);
+        let event = CommitteeEvent::DecisionApproved { decision_id, member, approved_weight };
+        Self::deposit_event(system, event);
+
+        if approved_weight >= self.threshold {
+            self.execute(system, governance, decision_id);
+        }
+        Ok(())
+    }
+
+    // Total current weight of the members who approved a decision
+    pub fn approved_weight(&self, decision_id: u32) -> u32 {
+        match self.decisions.get(&decision_id) {
+            Some(decision) => decision
+                .approvals
+                .iter()
+                .filter_map(|who| self.member_weight(who))
+                .fold(0u32, |total, weight| total.saturating_add(weight)),
+            None => 0,
+        }
+    }
+
+    // Dispatch an approved decision to governance as the committee origin, recording the
+    // outcome. A failed dispatch still closes the decision.
+    fn execute(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        governance: &mut GovernancePallet<T>,
+        decision_id: u32,
+    ) {
+        let decision = match self.decisions.get_mut(&decision_id) {
+            Some(decision) => decision,
+            None => return,
+        };
+
+        let origin = Origin::Committee;
+        let result = match &decision.action {
+            CommitteeAction::Veto(proposal_id) => {
+                governance.veto_proposal(system, origin, *proposal_id)
+            },
+            CommitteeAction::FlagMalicious(proposal_id) => {
+                governance.flag_malicious(system, origin, *proposal_id)
+            },
+            CommitteeAction::FastTrack { proposal_id, new_deadline } => {
+                governance.fast_track(system, origin, *proposal_id, *new_deadline)
+            },
+        };
+        decision.status = DecisionStatus::Executed(result);
+
+        Self::deposit_event(system, CommitteeEvent::DecisionExecuted { decision_id, result });
+    }
+
+    // Get decision details
+    pub fn get_decision(&self, decision_id: u32) -> Option<&Decision<T>> {
+        self.decisions.get(&decision_id)
+    }
+
+    // Convert a committee event into the runtime event type and store it in the system pallet
+    fn deposit_event(system: &mut SystemPallet<T>, event: CommitteeEvent<T>) {
+        let event: <T as CommitteeConfig>::RuntimeEvent = event.into();
+        system.deposit_event(event.into());
+    }
+}
+
+impl<T: CommitteeConfig> Default for CommitteePallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance::{ProposalStatus, Track};
+    use crate::staking::StakingPallet;
+    use crate::{Runtime, RuntimeEvent};
+
+    #[test]
+    fn test_weighted_committee() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+        let mut committee = CommitteePallet::<Runtime>::new();
+
+        // Only root sets the committee, and the threshold must be a weighted majority
+        let members = vec![(alice, 3), (bob, 2), (charlie, 1)];
+        let result = committee.set_members(&mut system, Origin::Signed(alice), members.clone(), 4);
+        assert_eq!(result, Err(CommitteeError::BadOrigin));
+        let result = committee.set_members(&mut system, Origin::Root, members.clone(), 3);
+        assert_eq!(result, Err(CommitteeError::InvalidThreshold));
+        committee.set_members(&mut system, Origin::Root, members, 4).unwrap();
+        assert_eq!(committee.member_weight(&alice), Some(3));
+        assert_eq!(committee.member_weight(&dave), None);
+
+        staking.set_balance(dave, 1000);
+        staking.stake(dave, 500).unwrap();
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                dave,
+                Track::Text,
+                "Suspicious".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Alice's weight alone is not enough, and non-members cannot take part
+        let action = CommitteeAction::FlagMalicious(proposal_id);
+        let result = committee.propose(&mut system, &mut governance, dave, action);
+        assert_eq!(result, Err(CommitteeError::NotMember));
+        let action = CommitteeAction::FlagMalicious(proposal_id);
+        let decision_id = committee.propose(&mut system, &mut governance, alice, action).unwrap();
+        assert_eq!(committee.approved_weight(decision_id), 3);
+        assert!(!governance.get_proposal(proposal_id).unwrap().is_flagged());
+
+        // Charlie's approval brings the weight to the threshold and dispatches the decision
+        committee.approve(&mut system, &mut governance, charlie, decision_id).unwrap();
+        let decision = committee.get_decision(decision_id).unwrap();
+        assert_eq!(decision.status(), &DecisionStatus::Executed(Ok(())));
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert!(proposal.is_flagged());
+        assert_eq!(proposal.flagged_by(), None);
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Committee(CommitteeEvent::DecisionExecuted {
+                decision_id: 0,
+                result: Ok(()),
+            }))
+        ));
+
+        let result = committee.approve(&mut system, &mut governance, bob, decision_id);
+        assert_eq!(result, Err(CommitteeError::DecisionClosed));
+
+        // A dispatch governance rejects still closes the decision with its error
+        let action = CommitteeAction::Veto(proposal_id);
+        let decision_id = committee.propose(&mut system, &mut governance, bob, action).unwrap();
+        committee.approve(&mut system, &mut governance, alice, decision_id).unwrap();
+        let decision = committee.get_decision(decision_id).unwrap();
+        let expected = DecisionStatus::Executed(Err(GovernanceError::ProposalNotApproved));
+        assert_eq!(decision.status(), &expected);
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.status(), &ProposalStatus::Active);
+    }
+}
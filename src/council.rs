@@ -0,0 +1,600 @@
+use crate::governance::{ApprovalCurve, GovernanceConfig, GovernanceError, GovernancePallet, Track};
+use crate::staking::StakingPallet;
+use crate::system::{Origin, SystemConfig, SystemPallet};
+use num::traits::CheckedAdd;
+use std::collections::HashMap;
+use std::fmt;
+
+pub trait CouncilConfig: GovernanceConfig {
+    // Number of blocks council members have to vote on a motion
+    const MOTION_DURATION: Self::BlockNumber;
+    // Approval curve of referendums submitted by approved motions, lower than the tracks'
+    // own curves that publicly proposed referendums are decided by
+    const REFERENDUM_APPROVAL: ApprovalCurve;
+    // The runtime event type, able to carry council events into the system pallet
+    type RuntimeEvent: From<CouncilEvent<Self>> + Into<<Self as SystemConfig>::RuntimeEvent>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MotionStatus {
+    Voting,
+    Approved,    // Reached its threshold and was submitted as a public referendum
+    Disapproved, // Can no longer reach its threshold
+    Expired,     // Motion duration ended before reaching its threshold
+}
+
+pub struct Motion<T: CouncilConfig> {
+    proposer: T::AccountId,
+    track: Track, // Track the referendum is submitted on
+    description: String,
+    call: Option<T::RuntimeCall>,
+    threshold: u32, // Number of aye votes needed to approve
+    ayes: Vec<T::AccountId>,
+    nays: Vec<T::AccountId>,
+    end_block: T::BlockNumber, // Last block at which members can vote
+    status: MotionStatus,
+    referendum_id: Option<u32>, // Governance proposal created once approved
+}
+
+impl<T: CouncilConfig> Motion<T> {
+    // Get the current status of the motion
+    pub fn status(&self) -> &MotionStatus {
+        &self.status
+    }
+
+    // Get the governance proposal created for an approved motion
+    pub fn referendum_id(&self) -> Option<u32> {
+        self.referendum_id
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CouncilError {
+    NotMember,
+    AlreadyMember,
+    MotionNotFound,
+    MotionClosed,
+    InvalidThreshold,
+    AlreadyVoted,
+    VotingPeriodEnded,
+    MotionStillOpen,
+    BadOrigin,
+    Overflow,
+    Governance(GovernanceError), // Submitting the referendum failed
+}
+
+impl fmt::Display for CouncilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            CouncilError::NotMember => "Account is not a council member",
+            CouncilError::AlreadyMember => "Account is already a council member",
+            CouncilError::MotionNotFound => "No motion found with the given ID",
+            CouncilError::MotionClosed => "Motion is no longer open for voting",
+            CouncilError::InvalidThreshold => {
+                "Threshold must be a majority of the council and not exceed its size"
+            },
+            CouncilError::AlreadyVoted => "Member has already voted on this motion",
+            CouncilError::VotingPeriodEnded => "Voting period for this motion has ended",
+            CouncilError::MotionStillOpen => "Motion can still reach its threshold",
+            CouncilError::BadOrigin => "Origin is not allowed to perform this operation",
+            CouncilError::Overflow => "Arithmetic overflow",
+            CouncilError::Governance(error) => return write!(f, "Governance error: {}", error),
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for CouncilError {}
+
+pub enum CouncilEvent<T: CouncilConfig> {
+    MemberAdded {
+        who: T::AccountId,
+    },
+    MemberRemoved {
+        who: T::AccountId,
+    },
+    MotionProposed {
+        motion_id: u32,
+        proposer: T::AccountId,
+        threshold: u32,
+    },
+    MotionVoted {
+        motion_id: u32,
+        member: T::AccountId,
+        approve: bool,
+    },
+    MotionClosed {
+        motion_id: u32,
+        status: MotionStatus,
+    },
+    ReferendumSubmitted {
+        motion_id: u32,
+        proposal_id: u32,
+    },
+}
+
+pub struct CouncilPallet<T: CouncilConfig> {
+    pub members: Vec<T::AccountId>,
+    pub motions: HashMap<u32, Motion<T>>,
+    next_motion_id: u32,
+}
+
+impl<T: CouncilConfig> CouncilPallet<T> {
+    pub fn new() -> Self {
+        Self {
+            members: Vec::new(),
+            motions: HashMap::new(),
+            next_motion_id: 0,
+        }
+    }
+
+    // Add an account to the council. Requires root.
+    pub fn add_member(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        who: T::AccountId,
+    ) -> Result<(), CouncilError> {
+        Self::ensure_root(&origin)?;
+        if self.is_member(&who) {
+            return Err(CouncilError::AlreadyMember);
+        }
+
+        self.members.push(who.clone());
+        Self::deposit_event(system, CouncilEvent::MemberAdded { who });
+        Ok(())
+    }
+
+    // Remove an account from the council, dropping its votes on open motions. Requires root.
+    pub fn remove_member(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        who: T::AccountId,
+    ) -> Result<(), CouncilError> {
+        Self::ensure_root(&origin)?;
+        if !self.is_member(&who) {
+            return Err(CouncilError::NotMember);
+        }
+
+        self.members.retain(|member| *member != who);
+        for motion in self.motions.values_mut() {
+            if motion.status == MotionStatus::Voting {
+                motion.ayes.retain(|member| *member != who);
+                motion.nays.retain(|member| *member != who);
+            }
+        }
+
+        Self::deposit_event(system, CouncilEvent::MemberRemoved { who });
+        Ok(())
+    }
+
+    // Membership changes are made by root only
+    fn ensure_root(origin: &Origin<T>) -> Result<(), CouncilError> {
+        match origin {
+            Origin::Root => Ok(()),
+            _ => Err(CouncilError::BadOrigin),
+        }
+    }
+
+    // Check whether an account sits on the council
+    pub fn is_member(&self, who: &T::AccountId) -> bool {
+        self.members.contains(who)
+    }
+
+    // Propose a motion that needs `threshold` member approvals before it becomes a referendum.
+    // The threshold must be at least a simple majority of the current council.
+    pub fn propose_motion(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        proposer: T::AccountId,
+        track: Track,
+        description: String,
+        call: Option<T::RuntimeCall>,
+        threshold: u32,
+    ) -> Result<u32, CouncilError> {
+        if !self.is_member(&proposer) {
+            return Err(CouncilError::NotMember);
+        }
+
+        let member_count = self.members.len() as u32;
+        if threshold <= member_count / 2 || threshold > member_count {
+            return Err(CouncilError::InvalidThreshold);
+        }
+
+        let end_block = system
+            .block_number()
+            .checked_add(&T::MOTION_DURATION)
+            .ok_or(CouncilError::Overflow)?;
+
+        let motion_id = self.next_motion_id;
+        let motion = Motion {
+            proposer: proposer.clone(),
+            track,
+            description,
+            call,
+            threshold,
+            ayes: Vec::new(),
+            nays: Vec::new(),
+            end_block,
+            status: MotionStatus::Voting,
+            referendum_id: None,
+        };
+
+        self.motions.insert(motion_id, motion);
+        self.next_motion_id += 1;
+
+        Self::deposit_event(
+            system,
+            CouncilEvent::MotionProposed { motion_id, proposer, threshold },
+        );
+
+        Ok(motion_id)
+    }
+
+    // Vote on an open motion
+    pub fn vote_motion(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        member: T::AccountId,
+        motion_id: u32,
+        approve: bool,
+    ) -> Result<(), CouncilError> {
+        if !self.is_member(&member) {
+            return Err(CouncilError::NotMember);
+        }
+
+        match self.motions.get_mut(&motion_id) {
+            Some(motion) => {
+                if motion.status != MotionStatus::Voting {
+                    return Err(CouncilError::MotionClosed);
+                }
+
+                if system.block_number() > motion.end_block {
+                    return Err(CouncilError::VotingPeriodEnded);
+                }
+
+                if motion.ayes.contains(&member) || motion.nays.contains(&member) {
+                    return Err(CouncilError::AlreadyVoted);
+                }
+
+                match approve {
+                    true => motion.ayes.push(member.clone()),
+                    false => motion.nays.push(member.clone()),
+                }
+            },
+            None => return Err(CouncilError::MotionNotFound),
+        }
+
+        Self::deposit_event(system, CouncilEvent::MotionVoted { motion_id, member, approve });
+        Ok(())
+    }
+
+    // Close a motion. Approved motions are submitted to governance as a public referendum
+    // created on behalf of the proposer, who pays the usual proposal deposit. The referendum
+    // is decided by the lowered `REFERENDUM_APPROVAL` curve.
+    pub fn close_motion(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        governance: &mut GovernancePallet<T>,
+        motion_id: u32,
+    ) -> Result<MotionStatus, CouncilError> {
+        let member_count = self.members.len() as u32;
+
+        let motion = match self.motions.get_mut(&motion_id) {
+            Some(motion) => motion,
+            None => return Err(CouncilError::MotionNotFound),
+        };
+
+        if motion.status != MotionStatus::Voting {
+            return Err(CouncilError::MotionClosed);
+        }
+
+        let ayes = motion.ayes.len() as u32;
+        let nays = motion.nays.len() as u32;
+
+        let new_status = if ayes >= motion.threshold {
+            MotionStatus::Approved
+        } else if nays > member_count.saturating_sub(motion.threshold) {
+            MotionStatus::Disapproved
+        } else if system.block_number() > motion.end_block {
+            MotionStatus::Expired
+        } else {
+            return Err(CouncilError::MotionStillOpen);
+        };
+
+        if new_status == MotionStatus::Approved {
+            // Check the deposit up front so a failed submission does not lose the call
+            if staking.get_free_balance(motion.proposer.clone()) < T::PROPOSAL_DEPOSIT {
+                return Err(CouncilError::Governance(GovernanceError::InsufficientDeposit));
+            }
+
+            let proposal_id = governance
+                .create_referendum_with_approval(
+                    system,
+                    staking,
+                    motion.proposer.clone(),
+                    motion.track,
+                    motion.description.clone(),
+                    motion.call.take(),
+                    T::REFERENDUM_APPROVAL,
+                )
+                .map_err(CouncilError::Governance)?;
+            motion.referendum_id = Some(proposal_id);
+
+            Self::deposit_event(
+                system,
+                CouncilEvent::ReferendumSubmitted { motion_id, proposal_id },
+            );
+        }
+
+        motion.status = new_status.clone();
+        Self::deposit_event(
+            system,
+            CouncilEvent::MotionClosed { motion_id, status: new_status.clone() },
+        );
+
+        Ok(new_status)
+    }
+
+    // Get motion details
+    pub fn get_motion(&self, motion_id: u32) -> Option<&Motion<T>> {
+        self.motions.get(&motion_id)
+    }
+
+    // Convert a council event into the runtime event type and store it in the system pallet
+    fn deposit_event(system: &mut SystemPallet<T>, event: CouncilEvent<T>) {
+        let event: <T as CouncilConfig>::RuntimeEvent = event.into();
+        system.deposit_event(event.into());
+    }
+}
+
+impl<T: CouncilConfig> Default for CouncilPallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance::{AccountVote, Conviction, ProposalStatus, Vote};
+    use crate::Runtime;
+
+    #[test]
+    fn test_council_motion_should_work() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        for member in [alice, bob, charlie] {
+            runtime.council.add_member(&mut runtime.system, Origin::Root, member).unwrap();
+        }
+
+        // Thresholds below a majority or above the council size are rejected
+        let result = runtime.council.propose_motion(
+            &mut runtime.system,
+            alice,
+            Track::Text,
+            "Low".to_string(),
+            None,
+            1,
+        );
+        assert_eq!(result, Err(CouncilError::InvalidThreshold));
+        let result = runtime.council.propose_motion(
+            &mut runtime.system,
+            alice,
+            Track::Text,
+            "High".to_string(),
+            None,
+            4,
+        );
+        assert_eq!(result, Err(CouncilError::InvalidThreshold));
+
+        let motion_id = runtime
+            .council
+            .propose_motion(&mut runtime.system, alice, Track::Text, "Upgrade".to_string(), None, 2)
+            .unwrap();
+
+        // Only members vote, and only once
+        let result = runtime.council.vote_motion(&mut runtime.system, 4u64, motion_id, true);
+        assert_eq!(result, Err(CouncilError::NotMember));
+        runtime.council.vote_motion(&mut runtime.system, alice, motion_id, true).unwrap();
+        let result = runtime.council.vote_motion(&mut runtime.system, alice, motion_id, false);
+        assert_eq!(result, Err(CouncilError::AlreadyVoted));
+
+        // One aye out of two needed: the motion is still open
+        let result = runtime.council.close_motion(
+            &mut runtime.system,
+            &mut runtime.staking,
+            &mut runtime.governance,
+            motion_id,
+        );
+        assert_eq!(result, Err(CouncilError::MotionStillOpen));
+
+        // Reaching the threshold submits a public referendum
+        runtime.council.vote_motion(&mut runtime.system, bob, motion_id, true).unwrap();
+        let status = runtime
+            .council
+            .close_motion(
+                &mut runtime.system,
+                &mut runtime.staking,
+                &mut runtime.governance,
+                motion_id,
+            )
+            .unwrap();
+        assert_eq!(status, MotionStatus::Approved);
+
+        let referendum_id = runtime.council.get_motion(motion_id).unwrap().referendum_id().unwrap();
+        let referendum = runtime.governance.get_proposal(referendum_id).unwrap();
+        assert_eq!(referendum.status(), &ProposalStatus::Active);
+        assert_eq!(
+            runtime.governance.get_proposal_details(referendum_id),
+            Ok(("Upgrade".to_string(), alice))
+        );
+    }
+
+    #[test]
+    fn test_council_membership_and_expiry() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut runtime = Runtime::new();
+        for member in [alice, bob, charlie] {
+            runtime.council.add_member(&mut runtime.system, Origin::Root, member).unwrap();
+        }
+        let result = runtime.council.add_member(&mut runtime.system, Origin::Root, alice);
+        assert_eq!(result, Err(CouncilError::AlreadyMember));
+
+        // Only root can change the membership
+        let dave = 4u64;
+        let result = runtime.council.add_member(&mut runtime.system, Origin::Signed(dave), dave);
+        assert_eq!(result, Err(CouncilError::BadOrigin));
+        let result = runtime.council.remove_member(&mut runtime.system, Origin::Signed(bob), alice);
+        assert_eq!(result, Err(CouncilError::BadOrigin));
+        assert!(!runtime.council.is_member(&dave));
+        assert!(runtime.council.is_member(&alice));
+
+        // Two nays out of three make a 2-of-3 motion unreachable
+        let rejected_id = runtime
+            .council
+            .propose_motion(
+                &mut runtime.system,
+                alice,
+                Track::Text,
+                "Unpopular".to_string(),
+                None,
+                2,
+            )
+            .unwrap();
+        runtime.council.vote_motion(&mut runtime.system, bob, rejected_id, false).unwrap();
+        runtime.council.vote_motion(&mut runtime.system, charlie, rejected_id, false).unwrap();
+        let status = runtime
+            .council
+            .close_motion(
+                &mut runtime.system,
+                &mut runtime.staking,
+                &mut runtime.governance,
+                rejected_id,
+            )
+            .unwrap();
+        assert_eq!(status, MotionStatus::Disapproved);
+
+        // Removed members lose their votes on open motions
+        let expiring_id = runtime
+            .council
+            .propose_motion(
+                &mut runtime.system,
+                alice,
+                Track::Text,
+                "Forgotten".to_string(),
+                None,
+                2,
+            )
+            .unwrap();
+        runtime.council.vote_motion(&mut runtime.system, charlie, expiring_id, true).unwrap();
+        runtime.council.remove_member(&mut runtime.system, Origin::Root, charlie).unwrap();
+        assert!(!runtime.council.is_member(&charlie));
+        assert!(runtime.council.get_motion(expiring_id).unwrap().ayes.is_empty());
+
+        // Motions that run out of time expire
+        let duration = <Runtime as CouncilConfig>::MOTION_DURATION;
+        runtime.system.set_block_number(duration + 1);
+        let result = runtime.council.vote_motion(&mut runtime.system, alice, expiring_id, true);
+        assert_eq!(result, Err(CouncilError::VotingPeriodEnded));
+        let status = runtime
+            .council
+            .close_motion(
+                &mut runtime.system,
+                &mut runtime.staking,
+                &mut runtime.governance,
+                expiring_id,
+            )
+            .unwrap();
+        assert_eq!(status, MotionStatus::Expired);
+    }
+
+    #[test]
+    fn test_council_referendums_use_lowered_threshold() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        for (who, stake) in [(bob, 120), (charlie, 150), (dave, 2000)] {
+            runtime.staking.set_balance(who, stake);
+            runtime.staking.stake(who, stake).unwrap();
+        }
+        for member in [alice, bob] {
+            runtime.council.add_member(&mut runtime.system, Origin::Root, member).unwrap();
+        }
+
+        let motion_id = runtime
+            .council
+            .propose_motion(&mut runtime.system, alice, Track::Text, "Council".to_string(), None, 2)
+            .unwrap();
+        for member in [alice, bob] {
+            runtime.council.vote_motion(&mut runtime.system, member, motion_id, true).unwrap();
+        }
+        runtime
+            .council
+            .close_motion(
+                &mut runtime.system,
+                &mut runtime.staking,
+                &mut runtime.governance,
+                motion_id,
+            )
+            .unwrap();
+        let council_id = runtime.council.get_motion(motion_id).unwrap().referendum_id().unwrap();
+        let public_id = runtime
+            .governance
+            .create_proposal(
+                &mut runtime.system,
+                &mut runtime.staking,
+                alice,
+                Track::Text,
+                "Public".to_string(),
+                None,
+            )
+            .unwrap();
+
+        let referendum = runtime.governance.get_proposal(council_id).unwrap();
+        assert_eq!(referendum.approval(), ApprovalCurve::NegativeTurnoutBias);
+        let referendum = runtime.governance.get_proposal(public_id).unwrap();
+        assert_eq!(referendum.approval(), ApprovalCurve::Fixed(50));
+
+        // The same low-turnout minority of ayes passes the council referendum only
+        let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+        let nay = AccountVote { vote: Vote::Nay, conviction: Conviction::Locked1x };
+        for proposal_id in [council_id, public_id] {
+            for (voter, vote) in [(bob, aye), (charlie, nay)] {
+                runtime
+                    .governance
+                    .vote(&mut runtime.system, &mut runtime.staking, voter, proposal_id, vote)
+                    .unwrap();
+            }
+        }
+        let end_block = [council_id, public_id]
+            .iter()
+            .map(|id| runtime.governance.get_proposal(*id).unwrap().end_block())
+            .max()
+            .unwrap();
+        runtime.system.set_block_number(end_block);
+        let status = runtime
+            .governance
+            .finalize_proposal(&mut runtime.system, &mut runtime.staking, council_id)
+            .unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        let status = runtime
+            .governance
+            .finalize_proposal(&mut runtime.system, &mut runtime.staking, public_id)
+            .unwrap();
+        assert_eq!(status, ProposalStatus::Rejected);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::staking::{Exposure, IndividualExposure, StakingConfig, StakingPallet};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+// Method used to choose the active validators at the start of each era
+pub trait ElectionProvider<T: StakingConfig> {
+    // Choose up to `max_validators` of the staking candidates, with the stake backing each,
+    // by decreasing total stake
+    fn elect(staking: &StakingPallet<T>) -> Vec<Exposure<T>>;
+}
+
+// Elect the candidates with the most backing stake, each nominator's stake being split evenly
+// between the candidates it nominates. Ties keep the order candidates joined in.
+pub struct SimpleElection;
+
+impl<T: StakingConfig> ElectionProvider<T> for SimpleElection {
+    fn elect(staking: &StakingPallet<T>) -> Vec<Exposure<T>> {
+        let mut stakers: Vec<Exposure<T>> = staking
+            .candidates()
+            .iter()
+            .map(|validator| staking.exposure_of(validator))
+            .filter(|exposure| exposure.total() > 0)
+            .collect();
+        stakers.sort_by_key(|exposure| Reverse(exposure.total()));
+        stakers.truncate(staking.parameters.max_validators as usize);
+        stakers
+    }
+}
+
+// Scale of the Phragmén loads, so fractions of stake keep their precision
+const LOAD_SCALE: u128 = 1_000_000_000_000;
+// Number of passes spreading each nominator's stake evenly over the validators it elected
+const BALANCING_ITERATIONS: usize = 10;
+
+// Sequential Phragmén, like `sp_npos_elections::seq_phragmen`: candidates are elected one at a
+// time, each time picking the one whose backers carry the least load, then every nominator's
+// stake is rebalanced between its elected targets so backing is spread as evenly as possible.
+// Validators vote for themselves with their own stake.
+pub struct PhragmenElection;
+
+// A voter with its stake and the indices of the candidates it approves
+struct Voter<AccountId> {
+    who: AccountId,
+    stake: u128,
+    targets: Vec<usize>,
+}
+
+impl<T: StakingConfig> ElectionProvider<T> for PhragmenElection {
+    fn elect(staking: &StakingPallet<T>) -> Vec<Exposure<T>> {
+        let candidates = staking.candidates();
+        let index: BTreeMap<&T::AccountId, usize> =
+            candidates.iter().enumerate().map(|(index, candidate)| (candidate, index)).collect();
+        let mut voters: Vec<Voter<T::AccountId>> = Vec::new();
+        for (index, candidate) in candidates.iter().enumerate() {
+            let stake = staking.get_staked_balance(candidate.clone()).into();
+            voters.push(Voter { who: candidate.clone(), stake, targets: vec![index] });
+        }
+        for (nominator, nominated) in &staking.nominations {
+            let mut targets: Vec<usize> =
+                nominated.iter().filter_map(|target| index.get(target).copied()).collect();
+            targets.sort_unstable();
+            let stake = staking.get_staked_balance(nominator.clone()).into();
+            if !targets.is_empty() {
+                voters.push(Voter { who: nominator.clone(), stake, targets });
+            }
+        }
+
+        // candidate -> (voter, edge) of each voter backing it, in voter order
+        let mut backers: Vec<Vec<(usize, usize)>> = vec![Vec::new(); candidates.len()];
+        for (voter, Voter { targets, .. }) in voters.iter().enumerate() {
+            for (edge, target) in targets.iter().enumerate() {
+                backers[*target].push((voter, edge));
+            }
+        }
+
+        let mut approval = vec![0u128; candidates.len()];
+        for voter in &voters {
+            for target in &voter.targets {
+                approval[*target] = approval[*target].saturating_add(voter.stake);
+            }
+        }
+
+        // Elect the candidate with the lowest score, updating the load of its backers
+        let rounds = (staking.parameters.max_validators as usize).min(candidates.len());
+        let mut elected: Vec<usize> = Vec::new();
+        let mut is_elected = vec![false; candidates.len()];
+        let mut voter_loads = vec![0u128; voters.len()];
+        let mut edge_loads: Vec<Vec<u128>> =
+            voters.iter().map(|voter| vec![0; voter.targets.len()]).collect();
+        for _ in 0..rounds {
+            let mut best: Option<(usize, u128)> = None;
+            for (candidate, backed) in approval.iter().enumerate() {
+                if is_elected[candidate] || *backed == 0 {
+                    continue;
+                }
+                let mut load = LOAD_SCALE;
+                for (voter, _) in &backers[candidate] {
+                    let backer_load = voters[*voter].stake.saturating_mul(voter_loads[*voter]);
+                    load = load.saturating_add(backer_load);
+                }
+                let score = load / backed;
+                if best.is_none_or(|(_, best_score)| score < best_score) {
+                    best = Some((candidate, score));
+                }
+            }
+            let (winner, score) = match best {
+                Some(best) => best,
+                None => break,
+            };
+            for (voter, edge) in &backers[winner] {
+                edge_loads[*voter][*edge] = score.saturating_sub(voter_loads[*voter]);
+                voter_loads[*voter] = score;
+            }
+            elected.push(winner);
+            is_elected[winner] = true;
+        }
+
+        // Split each voter's stake between its elected targets in proportion to the edge loads
+        let mut assignments: Vec<Vec<u128>> = Vec::new();
+        for (index, voter) in voters.iter().enumerate() {
+            let shares = edge_loads[index]
+                .iter()
+                .map(|edge_load| match voter_loads[index] {
+                    0 => 0,
+                    voter_load => voter.stake.saturating_mul(*edge_load) / voter_load,
+                })
+                .collect();
+            assignments.push(shares);
+        }
+        let mut backing = vec![0u128; candidates.len()];
+        for (voter, shares) in voters.iter().zip(&assignments) {
+            for (target, share) in voter.targets.iter().zip(shares) {
+                backing[*target] = backing[*target].saturating_add(*share);
+            }
+        }
+        for _ in 0..BALANCING_ITERATIONS {
+            for (voter, shares) in voters.iter().zip(assignments.iter_mut()) {
+                balance_voter(voter, shares, &mut backing, &is_elected);
+            }
+        }
+
+        let mut stakers: Vec<Exposure<T>> = Vec::new();
+        for winner in elected {
+            let validator = candidates[winner].clone();
+            let own = staking.get_staked_balance(validator.clone());
+            let commission = staking.commissions.get(&validator).copied().unwrap_or_default();
+            let mut exposure = Exposure { validator, own, others: Vec::new(), commission };
+            // The nominators come after the self-votes of the candidates
+            for (voter, edge) in &backers[winner] {
+                let share = assignments[*voter][*edge];
+                if *voter < candidates.len() || share == 0 {
+                    continue;
+                }
+                // A share never exceeds the nominator's stake, so it always fits in a balance
+                if let Ok(value) = T::Balance::try_from(share) {
+                    let who = voters[*voter].who.clone();
+                    exposure.others.push(IndividualExposure { who, value });
+                }
+            }
+            staking.clip_nominators(&mut exposure.others);
+            stakers.push(exposure);
+        }
+        stakers.sort_by_key(|exposure| Reverse(exposure.total()));
+        stakers
+    }
+}
+
+// Spread a voter's stake over its elected targets so their backing is as even as possible,
+// filling the least backed first
+fn balance_voter<AccountId>(
+    voter: &Voter<AccountId>,
+    shares: &mut [u128],
+    backing: &mut [u128],
+    is_elected: &[bool],
+) {
+    let mut edges: Vec<usize> =
+        (0..voter.targets.len()).filter(|edge| is_elected[voter.targets[*edge]]).collect();
+    if edges.len() < 2 {
+        return;
+    }
+    for edge in &edges {
+        let target = voter.targets[*edge];
+        backing[target] = backing[target].saturating_sub(shares[*edge]);
+        shares[*edge] = 0;
+    }
+    edges.sort_by_key(|edge| backing[voter.targets[*edge]]);
+
+    // Raise the least backed targets to a common level using the whole stake
+    let mut filled = edges.len();
+    let mut level = 0;
+    let mut base = 0u128;
+    for (count, edge) in edges.iter().enumerate() {
+        base = base.saturating_add(backing[voter.targets[*edge]]);
+        level = voter.stake.saturating_add(base) / (count as u128 + 1);
+        let next = edges.get(count + 1).map(|next| backing[voter.targets[*next]]);
+        if next.is_none_or(|next| level <= next) {
+            filled = count + 1;
+            break;
+        }
+    }
+    let mut remaining = voter.stake;
+    for edge in &edges[..filled] {
+        let target = voter.targets[*edge];
+        let share = level.saturating_sub(backing[target]).min(remaining);
+        shares[*edge] = share;
+        remaining -= share;
+    }
+    // Rounding leftovers go to the least backed target
+    shares[edges[0]] = shares[edges[0]].saturating_add(remaining);
+    for edge in &edges[..filled] {
+        let target = voter.targets[*edge];
+        backing[target] = backing[target].saturating_add(shares[*edge]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Runtime;
+
+    #[test]
+    fn test_phragmen_balances_stake() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+        }
+        for (who, stake) in [(alice, 100), (bob, 100), (charlie, 200), (dave, 200)] {
+            staking.stake(who, stake).unwrap();
+        }
+        for who in [alice, bob] {
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        staking.nominate(charlie, vec![alice, bob]).unwrap();
+        staking.nominate(dave, vec![alice]).unwrap();
+
+        // The simple method splits Charlie's stake evenly, leaving Alice backed twice as much
+        let simple = SimpleElection::elect(&staking);
+        assert_eq!(simple.iter().map(Exposure::total).collect::<Vec<_>>(), vec![400, 200]);
+
+        // Phragmén moves Charlie's stake to Bob so both are backed equally
+        let balanced = PhragmenElection::elect(&staking);
+        assert_eq!(balanced.iter().map(Exposure::total).collect::<Vec<_>>(), vec![300, 300]);
+        let bob_exposure = balanced.iter().find(|exposure| exposure.validator == bob).unwrap();
+        assert_eq!(bob_exposure.others[0].who, charlie);
+        assert_eq!(bob_exposure.others[0].value, 200u64);
+    }
+}
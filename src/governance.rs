@@ -1,171 +1,6359 @@
-use crate::staking::StakingConfig;
-use crate::system::SystemConfig;
-use std::collections::HashMap;
+use crate::preimage::PreimageStore;
+use crate::staking::{LockIdentifier, StakeProvider, StakingConfig, StakingError, StakingPallet};
+use crate::system::{DispatchError, Origin, SystemConfig, SystemPallet};
+use num::traits::{CheckedAdd, CheckedMul, CheckedSub, One, Zero};
+use num::BigUint;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
 
-pub trait GovernanceConfig: StakingConfig + SystemConfig {}
+// Lock governance keeps on voters' stake, like the one of `pallet_democracy`
+pub const GOVERNANCE_LOCK: LockIdentifier = *b"democrac";
+
+pub trait GovernanceConfig: StakingConfig + SystemConfig + Sized {
+    // The runtime call type that approved proposals can dispatch
+    type RuntimeCall;
+    // The runtime event type, able to carry governance events into the system pallet
+    type RuntimeEvent: From<GovernanceEvent<Self>> + Into<<Self as SystemConfig>::RuntimeEvent>;
+    // Byte encoding of a call, used to recognise identical proposals
+    fn encode_call(call: &Self::RuntimeCall) -> Vec<u8>;
+    // Byte encoding of an account, used to recognise identical treasury spends
+    fn encode_account(who: &Self::AccountId) -> Vec<u8>;
+    // How stake is turned into voting weight
+    type TallyStrategy: TallyStrategy;
+    // Signature over a vote signed off-chain by the voter
+    type Signature;
+    // Check `signature` over `message` against the public key of `signer`
+    fn verify_signature(
+        signer: &Self::AccountId,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> bool;
+    // Voting period, quorum and approval threshold for each class of proposal
+    fn track_info(track: Track) -> TrackInfo<Self::BlockNumber>;
+    // Deposit reserved from the creator of every proposal
+    const PROPOSAL_DEPOSIT: Self::Balance;
+    // Part of the deposit slashed when a proposal fails to reach quorum
+    const QUORUM_FAILURE_PENALTY: Self::Balance;
+    // Base number of blocks a voter's stake stays locked after finalization
+    const VOTE_LOCKING_PERIOD: Self::BlockNumber;
+    // Maximum number of proposals open for voting at the same time
+    const MAX_ACTIVE_PROPOSALS: u32;
+    // Maximum number of proposals a single account may create within `PROPOSAL_RATE_WINDOW`
+    const MAX_PROPOSALS_PER_WINDOW: u32;
+    // Number of blocks over which an account's proposals are counted for rate limiting
+    const PROPOSAL_RATE_WINDOW: Self::BlockNumber;
+    // Number of blocks between the approval of a proposal and the dispatch of its call
+    const ENACTMENT_DELAY: Self::BlockNumber;
+    // Deposit reserved from each account seconding a proposal, returned once it is settled
+    const SECOND_DEPOSIT: Self::Balance;
+    // Number of blocks a rejected proposal cannot be submitted again
+    const RESUBMISSION_COOLDOWN: Self::BlockNumber;
+    // Number of blocks individual votes are kept after a proposal is finalized
+    const VOTE_RETENTION_PERIOD: Self::BlockNumber;
+    // Number of blocks after finalization before a proposal is moved to the archive
+    const ARCHIVE_PERIOD: Self::BlockNumber;
+    // Number of blocks a delegation stays in effect after the delegator revokes it
+    const UNDELEGATION_COOLDOWN: Self::BlockNumber;
+    // Account that approved treasury spends are paid from
+    const TREASURY_ACCOUNT: Self::AccountId;
+    // Paid from the treasury to each account that voted on a finalized proposal, once claimed
+    const PARTICIPATION_REWARD: Self::Balance;
+    // Quorum of proposals fast-tracked by the technical committee
+    const FAST_TRACK_QUORUM: u128;
+    // Number of blocks after the voting period during which committed votes can be revealed
+    const REVEAL_PERIOD: Self::BlockNumber;
+    // Maximum number of tags on a single proposal
+    const MAX_TAGS: u32;
+    // Maximum length of a tag in bytes
+    const MAX_TAG_LEN: u32;
+}
+
+// Strategy for turning the stake behind a vote into voting weight
+pub trait TallyStrategy {
+    fn weight(stake: u128) -> u128;
+}
+
+// One unit of stake is one unit of weight
+pub struct LinearTally;
+
+impl TallyStrategy for LinearTally {
+    fn weight(stake: u128) -> u128 {
+        stake
+    }
+}
+
+// Weight is the square root of the stake, damping the influence of large holders
+pub struct QuadraticTally;
+
+impl TallyStrategy for QuadraticTally {
+    fn weight(stake: u128) -> u128 {
+        stake.isqrt()
+    }
+}
+
+// Classes of proposals, each decided with its own parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Track {
+    Treasury,
+    RuntimeParameter,
+    Text,
+}
+
+// Decision parameters of a single track
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackInfo<BlockNumber> {
+    // Default number of blocks a proposal stays open for voting
+    pub voting_period: BlockNumber,
+    // Minimum total vote weight that must take part for a proposal to be decided
+    pub quorum: u128,
+    // Rule deciding whether the votes approve a proposal
+    pub approval: ApprovalCurve,
+    // Number of distinct accounts that must second a proposal before it opens for voting
+    pub required_seconds: u32,
+}
+
+// How the approval threshold depends on turnout, measured against the electorate
+// (the total staked balance)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApprovalCurve {
+    // Percentage of aye out of aye + nay votes that must be exceeded, whatever the turnout
+    Fixed(u8),
+    // Positive turnout bias: low turnout needs a supermajority of ayes, full turnout only
+    // a simple majority. Approves when nays / sqrt(turnout) < ayes / sqrt(electorate).
+    PositiveTurnoutBias,
+    // Negative turnout bias: low turnout needs a supermajority of nays to reject, full
+    // turnout only a simple majority. Approves when nays / sqrt(electorate) < ayes / sqrt(turnout).
+    NegativeTurnoutBias,
+}
+
+impl ApprovalCurve {
+    // Whether `ayes` against `nays` approve, given the total `turnout` and `electorate`
+    pub fn approves(&self, ayes: u128, nays: u128, turnout: u128, electorate: u128) -> bool {
+        // Turnout can exceed the electorate through conviction multipliers
+        let turnout = turnout.min(electorate);
+        // Compare squares to avoid rounding square roots
+        let ayes_squared = BigUint::from(ayes).pow(2);
+        let nays_squared = BigUint::from(nays).pow(2);
+
+        match self {
+            ApprovalCurve::Fixed(threshold) => {
+                ayes.saturating_mul(100) > (ayes + nays).saturating_mul(*threshold as u128)
+            },
+            ApprovalCurve::PositiveTurnoutBias => {
+                nays_squared * BigUint::from(electorate) < ayes_squared * BigUint::from(turnout)
+            },
+            ApprovalCurve::NegativeTurnoutBias => {
+                nays_squared * BigUint::from(turnout) < ayes_squared * BigUint::from(electorate)
+            },
+        }
+    }
+}
 
 pub struct Proposal<T: GovernanceConfig> {
-    description: String,
-    yes_votes: u32,
-    no_votes: u32,
+    description_hash: T::Hash, // Hash of the description, whose text is kept as a preimage
+    description_len: u32, // Length of the description in bytes
+    proposal_hash: T::Hash, // Hash of the description and call, identifying duplicates
+    track: Track, // Class of the proposal, selecting its decision parameters
+    approval: Option<ApprovalCurve>, // Replaces the track's approval curve, e.g. for the council
+    tally: Tally, // Vote weight counted so far
     status: ProposalStatus,
     creator: T::AccountId,  // Store the creator of the proposal
+    voting_period: T::BlockNumber, // Number of blocks the proposal is open for voting
+    end_block: T::BlockNumber, // Last block at which votes are accepted (set on activation)
+    deposit: T::Balance, // Deposit still reserved from the creator, claimable once settled
+    flagged: bool, // Flagged as malicious by the technical committee or the weighted committee
+    flagged_by: Option<T::AccountId>, // Technical committee member who flagged it, if any
+    call: Option<T::RuntimeCall>, // Call dispatched by the runtime once approved
+    vetoed_by: Option<T::AccountId>, // Technical committee member who vetoed it, if any
+    batch: Option<u32>, // Batch whose proposals must pass or fail together
+    parent: Option<u32>, // Proposal whose description and call this one replaces if approved
+    fast_tracked: bool, // Deadline shortened and quorum lowered for an emergency
+    snapshot_block: T::BlockNumber, // Block whose stake snapshot weighs the votes
+    finalized_at: Option<T::BlockNumber>, // Block at which the proposal was finalized
+    enactment_block: Option<T::BlockNumber>, // Block from which the approved call may run
+    pruned_turnout: Option<TurnoutStats>, // Turnout kept after individual votes are pruned
+    spend: Option<TreasurySpend<T>>, // Payment made from the treasury once approved
+    payload: ProposalPayload, // Whether the proposal is a yes/no question or a choice
+    option_weights: Vec<u128>, // First-preference weight behind each option
+    winning_option: Option<u32>, // Option chosen by an approved multiple-choice proposal
+    tags: Vec<String>, // Lowercase labels front-ends can filter on
+}
+
+// Compact record kept once a finalized proposal is moved out of the live store
+pub struct ArchivedProposal<T: GovernanceConfig> {
+    pub description_hash: T::Hash,
+    pub track: Track,
+    pub status: ProposalStatus, // Final outcome
+    pub tally: Tally,
+    pub finalized_at: T::BlockNumber,
+}
+
+// A proposal looked up in either the live store or the archive
+pub enum ProposalRecord<'a, T: GovernanceConfig> {
+    Live(&'a Proposal<T>),
+    Archived(&'a ArchivedProposal<T>),
+}
+
+impl<T: GovernanceConfig> ProposalRecord<'_, T> {
+    pub fn status(&self) -> &ProposalStatus {
+        match self {
+            ProposalRecord::Live(proposal) => &proposal.status,
+            ProposalRecord::Archived(record) => &record.status,
+        }
+    }
+
+    pub fn description_hash(&self) -> &T::Hash {
+        match self {
+            ProposalRecord::Live(proposal) => &proposal.description_hash,
+            ProposalRecord::Archived(record) => &record.description_hash,
+        }
+    }
+
+    pub fn tally(&self) -> &Tally {
+        match self {
+            ProposalRecord::Live(proposal) => &proposal.tally,
+            ProposalRecord::Archived(record) => &record.tally,
+        }
+    }
+}
+
+// A payment from the treasury account to a beneficiary
+pub struct TreasurySpend<T: GovernanceConfig> {
+    pub beneficiary: T::AccountId,
+    pub amount: T::Balance,
+}
+
+// How the winner of a multiple-choice proposal is determined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChoiceMethod {
+    Plurality, // The option with the most first preferences wins
+    InstantRunoff, // The weakest option is eliminated until one holds a majority
+}
+
+// What voters decide on: a yes/no question or a choice between several options
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProposalPayload {
+    Binary,
+    Options {
+        options: Vec<String>,
+        method: ChoiceMethod,
+    },
+}
+
+// A ballot on a multiple-choice proposal: option indices in order of preference
+pub struct Ballot<T: GovernanceConfig> {
+    pub ranking: Vec<u32>,
+    pub balance: T::Balance,
+    pub weight: u128,
+}
+
+// Result of counting the ballots of a multiple-choice proposal
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionTally {
+    pub totals: Vec<u128>, // Weight behind each option in the final round
+    pub winner: Option<u32>, // None if nobody voted or the final round is tied
+}
+
+// Calls governance makes on itself; they are dispatched by the runtime when a proposal
+// carrying them is enacted
+pub enum GovernanceCall<T: GovernanceConfig> {
+    // Only let the listed accounts create proposals, or let everyone with `None`
+    SetProposerAllowlist(Option<Vec<T::AccountId>>),
+    AddProposer(T::AccountId),
+    RemoveProposer(T::AccountId),
+}
+
+// A single proposal submitted as part of a batch
+pub struct ProposalData<T: GovernanceConfig> {
+    pub track: Track,
+    pub description: String,
+    pub call: Option<T::RuntimeCall>,
+}
+
+impl<T: GovernanceConfig> Proposal<T> {
+    // Get the current status of the proposal
+    pub fn status(&self) -> &ProposalStatus {
+        &self.status
+    }
+
+    // Get the account that created the proposal
+    pub fn creator(&self) -> &T::AccountId {
+        &self.creator
+    }
+
+    // Get the hash of the proposal description
+    pub fn description_hash(&self) -> &T::Hash {
+        &self.description_hash
+    }
+
+    // Get the length of the proposal description in bytes
+    pub fn description_len(&self) -> u32 {
+        self.description_len
+    }
+
+    // Get the track the proposal was submitted on
+    pub fn track(&self) -> Track {
+        self.track
+    }
+
+    // Get the batch the proposal belongs to, if any
+    pub fn batch(&self) -> Option<u32> {
+        self.batch
+    }
+
+    // Get the treasury spend made by the proposal, if any
+    pub fn spend(&self) -> Option<&TreasurySpend<T>> {
+        self.spend.as_ref()
+    }
+
+    // Get the approval curve the proposal is decided by: its track's unless overridden
+    pub fn approval(&self) -> ApprovalCurve {
+        self.approval.unwrap_or(T::track_info(self.track).approval)
+    }
+
+    // Get the proposal this one amends, if it is an amendment
+    pub fn parent(&self) -> Option<u32> {
+        self.parent
+    }
+
+    // Get the last block at which votes are accepted
+    pub fn end_block(&self) -> T::BlockNumber {
+        self.end_block
+    }
+
+    // Get the block at which the approved call is scheduled to be dispatched, if any
+    pub fn enactment_block(&self) -> Option<T::BlockNumber> {
+        self.enactment_block
+    }
+
+    // Get the part of the deposit still reserved from the creator
+    pub fn deposit(&self) -> T::Balance {
+        self.deposit
+    }
+
+    // Whether the proposal was flagged as malicious
+    pub fn is_flagged(&self) -> bool {
+        self.flagged
+    }
+
+    // Get the technical committee member who flagged the proposal as malicious, if any
+    pub fn flagged_by(&self) -> Option<&T::AccountId> {
+        self.flagged_by.as_ref()
+    }
+
+    // Get the account that vetoed the proposal, if any
+    pub fn vetoed_by(&self) -> Option<&T::AccountId> {
+        self.vetoed_by.as_ref()
+    }
+
+    // Get what the proposal asks voters to decide
+    pub fn payload(&self) -> &ProposalPayload {
+        &self.payload
+    }
+
+    // Get the first-preference weight behind each option of a multiple-choice proposal
+    pub fn option_weights(&self) -> &[u128] {
+        &self.option_weights
+    }
+
+    // Get the option chosen by an approved multiple-choice proposal
+    pub fn winning_option(&self) -> Option<u32> {
+        self.winning_option
+    }
+
+    // Get the tags of the proposal
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    // Whether the proposal is still waiting to be decided
+    fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            ProposalStatus::Proposed | ProposalStatus::Pending | ProposalStatus::Active
+        )
+    }
+
+    // Get the vote weight counted so far
+    pub fn tally(&self) -> &Tally {
+        &self.tally
+    }
+
+    // Minimum turnout required by the proposal's track
+    fn quorum(&self) -> u128 {
+        let quorum = T::track_info(self.track).quorum;
+        if self.fast_tracked { quorum.min(T::FAST_TRACK_QUORUM) } else { quorum }
+    }
+
+    // Outcome of the proposal's own tally, measured against its track
+    fn outcome(&self, electorate: u128) -> ProposalStatus {
+        let turnout = self.tally.turnout();
+
+        if turnout < self.quorum() {
+            ProposalStatus::Failed
+        } else if self.approval().approves(self.tally.ayes, self.tally.nays, turnout, electorate) {
+            ProposalStatus::Approved
+        } else {
+            ProposalStatus::Rejected
+        }
+    }
+
+    // Outcome of a multiple-choice proposal: approved once quorum is met and an option wins
+    fn choice_outcome(&self, tally: &OptionTally) -> ProposalStatus {
+        let turnout =
+            self.option_weights.iter().fold(0u128, |total, weight| total.saturating_add(*weight));
+        if turnout < self.quorum() {
+            ProposalStatus::Failed
+        } else if tally.winner.is_some() {
+            ProposalStatus::Approved
+        } else {
+            ProposalStatus::Rejected
+        }
+    }
+}
+
+// Vote weight counted on a yes/no proposal. Adding weight is checked and fails instead of
+// wrapping; removing weight saturates at zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub ayes: u128,
+    pub nays: u128,
+    pub abstentions: u128, // Counts toward quorum only
+}
+
+impl Tally {
+    // Add vote weight to the matching bucket
+    pub fn add(&mut self, vote: Vote, weight: u128) -> Result<(), GovernanceError> {
+        let bucket = match vote {
+            Vote::Aye => &mut self.ayes,
+            Vote::Nay => &mut self.nays,
+            Vote::Abstain => &mut self.abstentions,
+        };
+        *bucket = bucket.checked_add(weight).ok_or(GovernanceError::Overflow)?;
+        Ok(())
+    }
+
+    // Remove previously counted vote weight from the matching bucket
+    pub fn remove(&mut self, vote: Vote, weight: u128) {
+        let bucket = match vote {
+            Vote::Aye => &mut self.ayes,
+            Vote::Nay => &mut self.nays,
+            Vote::Abstain => &mut self.abstentions,
+        };
+        *bucket = bucket.saturating_sub(weight);
+    }
+
+    // Total weight that took part, abstentions included
+    pub fn turnout(&self) -> u128 {
+        self.ayes.saturating_add(self.nays).saturating_add(self.abstentions)
+    }
+}
+
+// How strongly a voter backs their vote: more conviction means more weight
+// but a longer lock on the voter's stake once the proposal is finalized
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conviction {
+    Locked1x,
+    Locked2x,
+    Locked3x,
+    Locked4x,
+    Locked5x,
+    Locked6x,
+}
+
+impl Conviction {
+    // Multiplier applied to the voter's own stake
+    pub fn multiplier(&self) -> u128 {
+        match self {
+            Conviction::Locked1x => 1,
+            Conviction::Locked2x => 2,
+            Conviction::Locked3x => 3,
+            Conviction::Locked4x => 4,
+            Conviction::Locked5x => 5,
+            Conviction::Locked6x => 6,
+        }
+    }
+
+    // Number of locking periods the stake stays locked after finalization
+    pub fn lock_periods(&self) -> u32 {
+        match self {
+            Conviction::Locked1x => 1,
+            Conviction::Locked2x => 2,
+            Conviction::Locked3x => 4,
+            Conviction::Locked4x => 8,
+            Conviction::Locked5x => 16,
+            Conviction::Locked6x => 32,
+        }
+    }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Vote {
+    Aye,
+    Nay,
+    Abstain,
+}
+
+// A vote together with the conviction backing it. Abstentions ignore conviction:
+// they always count once and are never locked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccountVote {
+    pub vote: Vote,
+    pub conviction: Conviction,
+}
+
+impl AccountVote {
+    // Multiplier applied to the voter's own stake
+    pub fn multiplier(&self) -> u128 {
+        match self.vote {
+            Vote::Abstain => 1,
+            _ => self.conviction.multiplier(),
+        }
+    }
+
+    // Byte encoding of the vote, used when committing to it
+    pub fn encode(&self) -> [u8; 2] {
+        [self.vote as u8, self.conviction as u8]
+    }
+}
+
+// A vote as stored on chain, along with the voter's own stake and the total weight counted.
+// Split votes are stored with the side that got the larger part as their `vote`.
+pub struct VoteRecord<T: GovernanceConfig> {
+    pub vote: AccountVote,
+    pub balance: T::Balance,
+    pub weight: u128,
+    pub split: Option<SplitWeights>, // Aye and nay parts of a split vote
+}
+
+// Weight a split vote counts on each side
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SplitWeights {
+    pub aye: u128,
+    pub nay: u128,
+}
+
+impl<T: GovernanceConfig> VoteRecord<T> {
+    // Count the vote's weight in the matching tally buckets
+    fn count_in(&self, tally: &mut Tally) -> Result<(), GovernanceError> {
+        match self.split {
+            Some(split) => {
+                tally.add(Vote::Aye, split.aye)?;
+                tally.add(Vote::Nay, split.nay)
+            },
+            None => tally.add(self.vote.vote, self.weight),
+        }
+    }
+
+    // Take the vote's weight back out of the tally
+    fn remove_from(&self, tally: &mut Tally) {
+        match self.split {
+            Some(split) => {
+                tally.remove(Vote::Aye, split.aye);
+                tally.remove(Vote::Nay, split.nay);
+            },
+            None => tally.remove(self.vote.vote, self.weight),
+        }
+    }
+}
+
+// Participation in a single proposal, for dashboards
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurnoutStats {
+    pub voters: u32, // Accounts that voted directly
+    pub total_weight: u128, // Weight of all votes, abstentions included
+    pub yes_weight: u128,
+    pub no_weight: u128,
+    pub participation_rate: u32, // Share of the electorate's stake that took part, in ppm
+}
+
+// Stake that cannot be unstaked until the given block
+pub struct VotingLock<T: GovernanceConfig> {
+    pub amount: T::Balance,
+    pub until: T::BlockNumber,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ProposalStatus {
+    Proposed, // Waiting for the seconds its track requires
+    Pending, // Waiting in the queue for an active slot
     Active,
     Approved,
     Rejected,
+    Failed, // Not enough stake took part to reach quorum
+    Cancelled, // Withdrawn by the creator before anyone voted
+    Enacted, // Approved and its call handed to the runtime for dispatch
+    Vetoed, // Approved but blocked by the technical committee before enactment
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GovernanceError {
+    ProposalNotFound,
+    ProposalNotActive,
+    ProposalNotApproved,
+    NotTechnicalCommittee,
+    ProposalHasVotes,
+    NotCreator,
+    NoCallToEnact,
+    InvalidVotingPeriod,
+    VotingPeriodEnded,
+    VotingPeriodOpen,
+    AlreadyVoted,
+    NotVoted,
+    NoVotingPower,
+    VoterIsDelegating,
+    CountedByDelegate,
+    SelfDelegation,
+    AlreadyDelegating,
+    DelegateIsDelegating,
+    HasDelegators,
+    NotDelegating,
+    UndelegationPending,
+    NoVotingLock,
+    LockNotExpired,
+    InsufficientDeposit,
+    InvalidDeadline,
+    PreimageMissing,
+    ProposalStillOpen,
+    RetentionPeriodNotOver,
+    ArchivePeriodNotOver,
+    EnactmentPending,
+    DuplicateProposal,
+    ProposalInCooldown,
+    RateLimited,
+    RewardAlreadyClaimed,
+    NotAllowedToPropose,
+    SplitExceedsStake,
+    InvalidTag,
+    TooManyTags,
+    WrongProposalKind,
+    NotAwaitingSeconds,
+    AlreadySeconded,
+    TrackRequiresSeconds,
+    InvalidOptions,
+    InvalidRanking,
+    EmptyBatch,
+    TooManyActiveProposals,
+    ProposalInBatch,
+    NotInRevealPeriod,
+    NoCommitment,
+    InvalidReveal,
+    RevealPeriodOpen,
+    EnactmentDelayNotOver,
+    GovernancePaused,
+    AmendmentOutlastsParent,
+    InvalidSignature,
+    NoDepositToClaim,
+    BadOrigin,
+    Overflow,
+    Staking(StakingError), // An error returned by the staking pallet
+    DispatchFailed(DispatchError), // The enacted call returned an error
+}
+
+impl fmt::Display for GovernanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            GovernanceError::ProposalNotFound => "No proposal found with the given ID",
+            GovernanceError::ProposalNotActive => "Proposal is no longer active",
+            GovernanceError::ProposalNotApproved => "Proposal has not been approved",
+            GovernanceError::NotTechnicalCommittee => "Account is not on the technical committee",
+            GovernanceError::ProposalHasVotes => "Proposal has already been voted on",
+            GovernanceError::NotCreator => "Only the creator can modify a proposal",
+            GovernanceError::NoCallToEnact => "Proposal has no call to enact",
+            GovernanceError::GovernancePaused => "Proposing and voting are paused",
+            GovernanceError::InvalidSignature => "Signature does not match the voter and vote",
+            GovernanceError::NoDepositToClaim => "Proposal has no deposit left to claim",
+            GovernanceError::BadOrigin => "Origin is not allowed to perform this operation",
+            GovernanceError::AmendmentOutlastsParent => {
+                "Amendment voting must end no later than the proposal it amends"
+            },
+            GovernanceError::EnactmentDelayNotOver => {
+                "Proposal cannot be enacted before its enactment block"
+            },
+            GovernanceError::InvalidVotingPeriod => "Voting period must be greater than zero",
+            GovernanceError::VotingPeriodEnded => "Voting period for this proposal has ended",
+            GovernanceError::VotingPeriodOpen => {
+                "Proposal cannot be finalized before its voting period ends"
+            },
+            GovernanceError::AlreadyVoted => "Voter has already cast a vote for this proposal",
+            GovernanceError::NotVoted => "Voter has not voted on this proposal",
+            GovernanceError::NoVotingPower => "Voter has no staked balance to vote with",
+            GovernanceError::VoterIsDelegating => "Voter has delegated their voting power",
+            GovernanceError::CountedByDelegate => {
+                "Voter's stake was already counted through their delegate"
+            },
+            GovernanceError::SelfDelegation => "Cannot delegate to yourself",
+            GovernanceError::AlreadyDelegating => "Account is already delegating",
+            GovernanceError::DelegateIsDelegating => {
+                "Cannot delegate to an account that is itself delegating"
+            },
+            GovernanceError::HasDelegators => "An account with delegators cannot delegate",
+            GovernanceError::NotDelegating => "Account is not delegating",
+            GovernanceError::UndelegationPending => "Account is already undelegating",
+            GovernanceError::NoVotingLock => "No voting lock found for this account",
+            GovernanceError::LockNotExpired => "Voting lock has not expired yet",
+            GovernanceError::InsufficientDeposit => {
+                "Not enough free balance for the proposal deposit"
+            },
+            GovernanceError::PreimageMissing => "Proposal description is no longer stored",
+            GovernanceError::ProposalStillOpen => "Proposal is still open for voting",
+            GovernanceError::DuplicateProposal => "An identical proposal is already open",
+            GovernanceError::ProposalInCooldown => {
+                "An identical proposal was rejected recently and cannot be resubmitted yet"
+            },
+            GovernanceError::NotAllowedToPropose => "Account is not on the proposer allowlist",
+            GovernanceError::SplitExceedsStake => "Split vote uses more than the voter's stake",
+            GovernanceError::InvalidTag => "Tags must be non-empty and within the length limit",
+            GovernanceError::TooManyTags => "Proposal has more tags than allowed",
+            GovernanceError::RewardAlreadyClaimed => {
+                "Participation reward for this proposal was already claimed"
+            },
+            GovernanceError::RateLimited => {
+                "Account has created too many proposals recently, try again later"
+            },
+            GovernanceError::WrongProposalKind => {
+                "Vote does not match the kind of proposal (yes/no or multiple choice)"
+            },
+            GovernanceError::InvalidOptions => "A choice needs at least two options",
+            GovernanceError::NotAwaitingSeconds => "Proposal is not waiting for seconds",
+            GovernanceError::AlreadySeconded => {
+                "Account already seconded or created this proposal"
+            },
+            GovernanceError::TrackRequiresSeconds => {
+                "Proposals on a track that requires seconds cannot be batched"
+            },
+            GovernanceError::InvalidRanking => {
+                "Ranking must list distinct, existing options and cannot be empty"
+            },
+            GovernanceError::ArchivePeriodNotOver => {
+                "Proposal cannot be archived before its archive period is over"
+            },
+            GovernanceError::EnactmentPending => "Proposal's call has not been enacted yet",
+            GovernanceError::RetentionPeriodNotOver => {
+                "Votes of the proposal are still within the retention period"
+            },
+            GovernanceError::InvalidDeadline => {
+                "New deadline must be between now and the current deadline"
+            },
+            GovernanceError::EmptyBatch => "A batch must contain at least one proposal",
+            GovernanceError::TooManyActiveProposals => {
+                "Not enough free slots to open every proposal in the batch"
+            },
+            GovernanceError::ProposalInBatch => "Proposal is part of a batch",
+            GovernanceError::NotInRevealPeriod => "Votes can only be revealed after voting ends",
+            GovernanceError::NoCommitment => "Voter has not committed to a vote",
+            GovernanceError::InvalidReveal => "Revealed vote does not match the commitment",
+            GovernanceError::RevealPeriodOpen => "Committed votes can still be revealed",
+            GovernanceError::Overflow => "Arithmetic overflow",
+            GovernanceError::Staking(error) => return write!(f, "Staking error: {}", error),
+            GovernanceError::DispatchFailed(error) => {
+                return write!(f, "Enacted call failed: {}", error)
+            },
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for GovernanceError {}
+
+pub enum GovernanceEvent<T: GovernanceConfig> {
+    ProposalCreated {
+        proposal_id: u32,
+        creator: T::AccountId,
+        end_block: T::BlockNumber,
+    },
+    ProposalAwaitingSeconds {
+        proposal_id: u32,
+        creator: T::AccountId,
+        required_seconds: u32,
+    },
+    ProposalSeconded {
+        proposal_id: u32,
+        who: T::AccountId,
+        seconds: u32,
+    },
+    ProposalQueued {
+        proposal_id: u32,
+        creator: T::AccountId,
+    },
+    BatchCreated {
+        batch_id: u32,
+        proposal_ids: Vec<u32>,
+    },
+    ProposalActivated {
+        proposal_id: u32,
+        end_block: T::BlockNumber,
+    },
+    Voted {
+        voter: T::AccountId,
+        proposal_id: u32,
+        vote: AccountVote,
+        weight: u128,
+    },
+    BallotCast {
+        voter: T::AccountId,
+        proposal_id: u32,
+        weight: u128,
+    },
+    SplitVoted {
+        voter: T::AccountId,
+        proposal_id: u32,
+        aye: u128,
+        nay: u128,
+    },
+    VoteRemoved {
+        voter: T::AccountId,
+        proposal_id: u32,
+    },
+    VoteCommitted {
+        voter: T::AccountId,
+        proposal_id: u32,
+    },
+    Delegated {
+        from: T::AccountId,
+        track: Track,
+        to: T::AccountId,
+    },
+    UndelegationScheduled {
+        from: T::AccountId,
+        track: Track,
+        effective_at: T::BlockNumber,
+    },
+    Undelegated {
+        from: T::AccountId,
+        track: Track,
+    },
+    ProposalFinalized {
+        proposal_id: u32,
+        status: ProposalStatus,
+    },
+    ProposalCancelled {
+        proposal_id: u32,
+    },
+    ProposalPruned {
+        proposal_id: u32,
+    },
+    ProposalArchived {
+        proposal_id: u32,
+    },
+    ProposalTagged {
+        proposal_id: u32,
+        tags: Vec<String>,
+    },
+    ProposalAmended {
+        proposal_id: u32,
+        description_hash: T::Hash,
+    },
+    AmendmentProposed {
+        proposal_id: u32,
+        parent: u32,
+    },
+    EnactmentScheduled {
+        proposal_id: u32,
+        at: T::BlockNumber,
+    },
+    ProposalEnacted {
+        proposal_id: u32,
+    },
+    ProposalVetoed {
+        proposal_id: u32,
+        vetoer: Option<T::AccountId>, // None when vetoed by the weighted committee
+    },
+    ProposalFlagged {
+        proposal_id: u32,
+        by: Option<T::AccountId>, // None when flagged by the weighted committee
+    },
+    DepositSlashed {
+        proposal_id: u32,
+        amount: T::Balance,
+    },
+    DepositClaimed {
+        proposal_id: u32,
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    ParticipationRewarded {
+        who: T::AccountId,
+        proposal_id: u32,
+        amount: T::Balance,
+    },
+    TreasurySpent {
+        proposal_id: u32,
+        beneficiary: T::AccountId,
+        amount: T::Balance,
+    },
+    ProposalFastTracked {
+        proposal_id: u32,
+        by: Option<T::AccountId>, // None when fast-tracked by the weighted committee
+        end_block: T::BlockNumber,
+        quorum: u128,
+    },
+    ProposerAllowlistChanged {
+        members: Option<Vec<T::AccountId>>,
+    },
+    TechnicalCommitteeChanged {
+        members: Vec<T::AccountId>,
+    },
+    Paused,
+    Unpaused,
+    RejectedWhilePaused {
+        who: T::AccountId,
+    },
+    Unlocked {
+        who: T::AccountId,
+    },
 }
 
 pub struct GovernancePallet<T: GovernanceConfig> {
-    pub proposals: HashMap<u32, Proposal<T>>,
-    pub votes: HashMap<(T::AccountId, u32), bool>, // (voter, proposal_id) -> vote_type
+    proposals: HashMap<u32, Proposal<T>>,
+    archive: BTreeMap<u32, ArchivedProposal<T>>, // proposal_id -> record of an archived proposal
+    votes: HashMap<(T::AccountId, u32), VoteRecord<T>>, // (voter, proposal_id) -> vote
+    account_votes: HashMap<T::AccountId, BTreeSet<u32>>, // voter -> proposals voted on
+    proposal_voters: HashMap<u32, Vec<T::AccountId>>, // proposal_id -> voters, in voting order
+    delegations: HashMap<(T::AccountId, Track), T::AccountId>, // (delegator, track) -> delegate
+    // (delegator, track) -> block at which a revoked delegation stops counting
+    pending_undelegations: HashMap<(T::AccountId, Track), T::BlockNumber>,
+    // (delegator, proposal_id) -> delegate whose vote already counted the delegator's stake
+    delegated_votes: HashMap<(T::AccountId, u32), T::AccountId>,
+    locks: HashMap<T::AccountId, BTreeMap<u32, VotingLock<T>>>, // voter -> proposal_id -> lock
+    ballots: HashMap<(T::AccountId, u32), Ballot<T>>, // (voter, proposal_id) -> ranked ballot
+    seconds: HashMap<u32, Vec<T::AccountId>>, // proposal_id -> accounts that seconded it
+    vote_nonces: HashMap<T::AccountId, u64>, // voter -> signed votes submitted so far
+    // (voter, proposal_id) -> hash of a vote that has not been revealed yet
+    commitments: HashMap<(T::AccountId, u32), T::Hash>,
+    pending_queue: VecDeque<u32>, // Proposals waiting for an active slot, oldest first
+    technical_committee: Vec<T::AccountId>, // Accounts allowed to veto approved proposals
+    proposer_allowlist: Option<Vec<T::AccountId>>, // Accounts allowed to propose, if restricted
+    paused: bool, // Whether proposing and voting are frozen for incident response
+    tagged_proposals: HashMap<String, BTreeSet<u32>>, // tag -> proposals carrying it
+    batches: HashMap<u32, Vec<u32>>, // batch_id -> proposals that pass or fail together
+    // block -> approved proposals whose calls are dispatched at the start of that block
+    scheduled_enactments: BTreeMap<T::BlockNumber, Vec<u32>>,
+    preimages: PreimageStore<T>, // Full text of proposal descriptions, by hash
+    // block -> staked balances at the time proposals were created in that block
+    stake_snapshots: BTreeMap<T::BlockNumber, BTreeMap<T::AccountId, T::Balance>>,
+    open_proposal_hashes: HashMap<T::Hash, u32>, // proposal_hash -> open proposal with it
+    // description or proposal hash -> latest proposal created with it, open or not
+    proposals_by_hash: HashMap<T::Hash, u32>,
+    // proposal_hash -> last block of the resubmission cooldown after a rejection
+    rejected_hashes: HashMap<T::Hash, T::BlockNumber>,
+    claimed_rewards: HashSet<(T::AccountId, u32)>, // (voter, proposal_id) rewards paid out
+    // creator -> blocks at which they created proposals within the rate limit window
+    recent_proposals: HashMap<T::AccountId, Vec<T::BlockNumber>>,
     next_proposal_id: u32,
+    next_batch_id: u32,
 }
 
 impl<T: GovernanceConfig> GovernancePallet<T> {
     pub fn new() -> Self {
         Self {
             proposals: HashMap::new(),
+            archive: BTreeMap::new(),
             votes: HashMap::new(),
+            account_votes: HashMap::new(),
+            proposal_voters: HashMap::new(),
+            delegations: HashMap::new(),
+            pending_undelegations: HashMap::new(),
+            delegated_votes: HashMap::new(),
+            locks: HashMap::new(),
+            ballots: HashMap::new(),
+            seconds: HashMap::new(),
+            vote_nonces: HashMap::new(),
+            commitments: HashMap::new(),
+            pending_queue: VecDeque::new(),
+            technical_committee: Vec::new(),
+            proposer_allowlist: None,
+            paused: false,
+            tagged_proposals: HashMap::new(),
+            batches: HashMap::new(),
+            scheduled_enactments: BTreeMap::new(),
+            preimages: PreimageStore::new(),
+            stake_snapshots: BTreeMap::new(),
+            open_proposal_hashes: HashMap::new(),
+            proposals_by_hash: HashMap::new(),
+            rejected_hashes: HashMap::new(),
+            recent_proposals: HashMap::new(),
+            claimed_rewards: HashSet::new(),
             next_proposal_id: 0,
+            next_batch_id: 0,
         }
     }
 
-    // Create a new proposal
+    // Create a new proposal on `track`, open for `voting_period` blocks (or the track default).
+    // The creator must reserve the configured proposal deposit. Once the maximum number
+    // of active proposals is reached, new proposals are queued until a slot frees up.
+    // Tracks that require seconds hold the proposal back until enough accounts second it.
     pub fn create_proposal(
         &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        track: Track,
+        description: String,
+        voting_period: Option<T::BlockNumber>,
+    ) -> Result<u32, GovernanceError> {
+        self.create_proposal_with_call(
+            system,
+            staking,
+            creator,
+            track,
+            description,
+            voting_period,
+            None,
+        )
+    }
+
+    // Create a new proposal that dispatches `call` through the runtime once approved
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_proposal_with_call(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        track: Track,
+        description: String,
+        voting_period: Option<T::BlockNumber>,
+        call: Option<T::RuntimeCall>,
+    ) -> Result<u32, GovernanceError> {
+        self.do_create_proposal(
+            system,
+            staking,
+            creator,
+            track,
+            description,
+            voting_period,
+            call,
+            None,
+        )
+    }
+
+    // Create a proposal dispatching `call` or paying out `spend` once approved
+    #[allow(clippy::too_many_arguments)]
+    fn do_create_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
         creator: T::AccountId,
+        track: Track,
         description: String,
-    ) -> Result<u32, &'static str> {
+        voting_period: Option<T::BlockNumber>,
+        call: Option<T::RuntimeCall>,
+        spend: Option<TreasurySpend<T>>,
+    ) -> Result<u32, GovernanceError> {
+        self.ensure_not_paused(system, &creator)?;
+
+        let voting_period = voting_period.unwrap_or(T::track_info(track).voting_period);
+        if voting_period.is_zero() {
+            return Err(GovernanceError::InvalidVotingPeriod);
+        }
+
+        let end_block = system
+            .block_number()
+            .checked_add(&voting_period)
+            .ok_or(GovernanceError::Overflow)?;
+
+        let description_len =
+            u32::try_from(description.len()).map_err(|_| GovernanceError::Overflow)?;
+
+        if !self.can_propose(&creator) {
+            return Err(GovernanceError::NotAllowedToPropose);
+        }
+
+        let proposal_hash = Self::proposal_hash(&description, call.as_ref(), spend.as_ref());
+        self.ensure_not_duplicate(system, &proposal_hash)?;
+        self.ensure_within_rate_limit(system, &creator, 1)?;
+
+        let deposit = T::PROPOSAL_DEPOSIT;
+        staking
+            .reserve(creator.clone(), deposit)
+            .map_err(|_| GovernanceError::InsufficientDeposit)?;
+
+        // Votes are weighed by the stake at creation, so bonding later cannot swing the vote
+        self.stake_snapshots
+            .entry(system.block_number())
+            .or_insert_with(|| staking.bonded_snapshot());
+
+        // Only the hash is kept in the proposal; the text goes to the preimage store
+        let description_hash = self.preimages.note_preimage(description.into_bytes());
+
         let current_id = self.next_proposal_id;
+        let creator_id = creator.clone();
+        let required_seconds = T::track_info(track).required_seconds;
+        let has_free_slot = self.active_proposal_count() < T::MAX_ACTIVE_PROPOSALS;
+        let status = if required_seconds > 0 {
+            ProposalStatus::Proposed
+        } else if has_free_slot {
+            ProposalStatus::Active
+        } else {
+            ProposalStatus::Pending
+        };
         
+        self.proposals_by_hash.insert(description_hash.clone(), current_id);
+        self.proposals_by_hash.insert(proposal_hash.clone(), current_id);
         let new_proposal = Proposal {
-            description,
-            yes_votes: 0,
-            no_votes: 0,
-            status: ProposalStatus::Active,
+            description_hash,
+            description_len,
+            proposal_hash: proposal_hash.clone(),
+            track,
+            tally: Tally::default(),
+            status,
             creator,
+            voting_period,
+            end_block,
+            deposit,
+            call,
+            vetoed_by: None,
+            batch: None,
+            fast_tracked: false,
+            snapshot_block: system.block_number(),
+            finalized_at: None,
+            enactment_block: None,
+            parent: None,
+            flagged: false,
+            flagged_by: None,
+            approval: None,
+            pruned_turnout: None,
+            spend,
+            payload: ProposalPayload::Binary,
+            option_weights: Vec::new(),
+            winning_option: None,
+            tags: Vec::new(),
         };
         
         self.proposals.insert(current_id, new_proposal);
+        self.open_proposal_hashes.insert(proposal_hash, current_id);
+        self.recent_proposals.entry(creator_id.clone()).or_default().push(system.block_number());
         self.next_proposal_id += 1;
+
+        if required_seconds > 0 {
+            Self::deposit_event(
+                system,
+                GovernanceEvent::ProposalAwaitingSeconds {
+                    proposal_id: current_id,
+                    creator: creator_id,
+                    required_seconds,
+                },
+            );
+        } else if has_free_slot {
+            Self::deposit_event(
+                system,
+                GovernanceEvent::ProposalCreated {
+                    proposal_id: current_id,
+                    creator: creator_id,
+                    end_block,
+                },
+            );
+        } else {
+            self.pending_queue.push_back(current_id);
+            Self::deposit_event(
+                system,
+                GovernanceEvent::ProposalQueued { proposal_id: current_id, creator: creator_id },
+            );
+        }
         
         Ok(current_id)
     }
 
-    // Vote on a proposal (true = yes, false = no)
-    pub fn vote(
+    // Create a proposal on the treasury track that pays `amount` from the treasury account
+    // to `beneficiary` once it is approved and its enactment delay is over
+    pub fn create_spend_proposal(
         &mut self,
-        voter: T::AccountId,
-        proposal_id: u32,
-        vote_type: bool,
-    ) -> Result<(), &'static str> {
-        let vote_key = (voter.clone(), proposal_id);
-        
-        match self.proposals.get_mut(&proposal_id) {
-            Some(proposal) => {
-                if proposal.status != ProposalStatus::Active {
-                    return Err("Cannot vote on inactive proposal");
-                }
-                
-                if self.votes.contains_key(&vote_key) {
-                    return Err("Voter has already cast a vote for this proposal");
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        beneficiary: T::AccountId,
+        amount: T::Balance,
+        description: String,
+    ) -> Result<u32, GovernanceError> {
+        self.do_create_proposal(
+            system,
+            staking,
+            creator,
+            Track::Treasury,
+            description,
+            None,
+            None,
+            Some(TreasurySpend { beneficiary, amount }),
+        )
+    }
+
+    // Create a proposal asking voters to choose between `options`, decided by `method`.
+    // Voters submit rankings with `vote_ranked`; plurality only counts first preferences.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_choice_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        track: Track,
+        description: String,
+        options: Vec<String>,
+        method: ChoiceMethod,
+        voting_period: Option<T::BlockNumber>,
+    ) -> Result<u32, GovernanceError> {
+        if options.len() < 2 || u32::try_from(options.len()).is_err() {
+            return Err(GovernanceError::InvalidOptions);
+        }
+
+        let proposal_id = self.create_proposal_with_call(
+            system,
+            staking,
+            creator,
+            track,
+            description,
+            voting_period,
+            None,
+        )?;
+        if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+            proposal.option_weights = vec![0; options.len()];
+            proposal.payload = ProposalPayload::Options { options, method };
+        }
+        Ok(proposal_id)
+    }
+
+    // Create a referendum decided by `approval` instead of its track's curve. Used by the
+    // council to submit approved motions with a lowered threshold.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_referendum_with_approval(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        track: Track,
+        description: String,
+        call: Option<T::RuntimeCall>,
+        approval: ApprovalCurve,
+    ) -> Result<u32, GovernanceError> {
+        let proposal_id = self.create_proposal_with_call(
+            system,
+            staking,
+            creator,
+            track,
+            description,
+            None,
+            call,
+        )?;
+        if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+            proposal.approval = Some(approval);
+        }
+        Ok(proposal_id)
+    }
+
+    // File an amendment to an active yes/no proposal, on the same track. Its voting period
+    // (by default the parent's remaining time) must end no later than the parent's, and it
+    // is always finalized first: if approved, its description and call replace the parent's.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_amendment(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        parent_id: u32,
+        description: String,
+        voting_period: Option<T::BlockNumber>,
+        call: Option<T::RuntimeCall>,
+    ) -> Result<u32, GovernanceError> {
+        let parent = self
+            .proposals
+            .get(&parent_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if parent.status != ProposalStatus::Active {
+            return Err(GovernanceError::ProposalNotActive);
+        }
+        if parent.payload != ProposalPayload::Binary {
+            return Err(GovernanceError::WrongProposalKind);
+        }
+
+        let now = system.block_number();
+        let remaining = parent
+            .end_block
+            .checked_sub(&now)
+            .unwrap_or_else(T::BlockNumber::zero);
+        let voting_period = voting_period.unwrap_or(remaining);
+        let end_block = now.checked_add(&voting_period).ok_or(GovernanceError::Overflow)?;
+        if end_block > parent.end_block {
+            return Err(GovernanceError::AmendmentOutlastsParent);
+        }
+        let track = parent.track;
+
+        let proposal_id = self.create_proposal_with_call(
+            system,
+            staking,
+            creator,
+            track,
+            description,
+            Some(voting_period),
+            call,
+        )?;
+        if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+            proposal.parent = Some(parent_id);
+        }
+
+        let event = GovernanceEvent::AmendmentProposed { proposal_id, parent: parent_id };
+        Self::deposit_event(system, event);
+        Ok(proposal_id)
+    }
+
+    // Replace the description and call of an amendment's parent with its own, if the parent
+    // is still open for voting. An amendment never enacts its call by itself.
+    fn apply_amendment(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let amendment = match self.proposals.get_mut(&proposal_id) {
+            Some(amendment) => amendment,
+            None => return Err(GovernanceError::ProposalNotFound),
+        };
+        let parent_id = match amendment.parent {
+            Some(parent_id) => parent_id,
+            None => return Ok(()),
+        };
+        let call = amendment.call.take();
+        let description_hash = amendment.description_hash.clone();
+        let description_len = amendment.description_len;
+        let proposal_hash = amendment.proposal_hash.clone();
+
+        let parent = match self.proposals.get_mut(&parent_id) {
+            Some(parent) if parent.status == ProposalStatus::Active => parent,
+            _ => return Ok(()),
+        };
+        let old_hash = parent.description_hash.clone();
+        self.open_proposal_hashes.remove(&parent.proposal_hash);
+        self.open_proposal_hashes.insert(proposal_hash.clone(), parent_id);
+        parent.description_hash = description_hash.clone();
+        parent.description_len = description_len;
+        parent.proposal_hash = proposal_hash;
+        parent.call = call;
+
+        // Drop the old text unless another proposal still refers to it
+        if !self.proposals.values().any(|proposal| proposal.description_hash == old_hash) {
+            self.preimages.reap_preimage(&old_hash);
+        }
+
+        let event = GovernanceEvent::ProposalAmended { proposal_id: parent_id, description_hash };
+        Self::deposit_event(system, event);
+        Ok(())
+    }
+
+    // Second a proposal waiting for endorsements, reserving the seconding deposit. Once its
+    // track's required number of distinct seconds is reached the proposal opens for voting,
+    // or joins the queue when every active slot is taken.
+    pub fn second(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &who)?;
+
+        let has_free_slot = self.active_proposal_count() < T::MAX_ACTIVE_PROPOSALS;
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Proposed {
+                    return Err(GovernanceError::NotAwaitingSeconds);
                 }
-                
-                self.votes.insert(vote_key, vote_type);
-                
-                match vote_type {
-                    true => proposal.yes_votes += 1,  // Yes vote
-                    false => proposal.no_votes += 1,  // No vote
+
+                let seconds = self.seconds.entry(proposal_id).or_default();
+                if proposal.creator == who || seconds.contains(&who) {
+                    return Err(GovernanceError::AlreadySeconded);
+                }
+
+                staking
+                    .reserve(who.clone(), T::SECOND_DEPOSIT)
+                    .map_err(|_| GovernanceError::InsufficientDeposit)?;
+                seconds.push(who.clone());
+                let count = seconds.len() as u32;
+
+                let event = GovernanceEvent::ProposalSeconded { proposal_id, who, seconds: count };
+                Self::deposit_event(system, event);
+
+                if count < T::track_info(proposal.track).required_seconds {
+                    return Ok(());
+                }
+
+                if has_free_slot {
+                    let end_block = system
+                        .block_number()
+                        .checked_add(&proposal.voting_period)
+                        .ok_or(GovernanceError::Overflow)?;
+                    proposal.status = ProposalStatus::Active;
+                    proposal.end_block = end_block;
+                    Self::deposit_event(
+                        system,
+                        GovernanceEvent::ProposalActivated { proposal_id, end_block },
+                    );
+                } else {
+                    proposal.status = ProposalStatus::Pending;
+                    self.pending_queue.push_back(proposal_id);
+                    let creator = proposal.creator.clone();
+                    Self::deposit_event(
+                        system,
+                        GovernanceEvent::ProposalQueued { proposal_id, creator },
+                    );
                 }
-                
                 Ok(())
             },
-            None => Err("No proposal found with the given ID"),
+            None => Err(GovernanceError::ProposalNotFound),
         }
     }
 
-    // Get proposal details
-    pub fn get_proposal(&self, proposal_id: u32) -> Option<&Proposal<T>> {
-        self.proposals.get(&proposal_id)
+    // Pay the participation reward to an account that voted on a finalized proposal. Each
+    // vote is rewarded once and only while it is still stored, i.e. before pruning.
+    pub fn claim_participation_reward(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if proposal.finalized_at.is_none() {
+            return Err(GovernanceError::ProposalStillOpen);
+        }
+
+        let vote_key = (who.clone(), proposal_id);
+        if !self.votes.contains_key(&vote_key) && !self.ballots.contains_key(&vote_key) {
+            return Err(GovernanceError::NotVoted);
+        }
+        if self.claimed_rewards.contains(&vote_key) {
+            return Err(GovernanceError::RewardAlreadyClaimed);
+        }
+
+        let amount = T::PARTICIPATION_REWARD;
+        staking
+            .transfer(T::TREASURY_ACCOUNT, who.clone(), amount)
+            .map_err(GovernanceError::Staking)?;
+        self.claimed_rewards.insert(vote_key);
+
+        let event = GovernanceEvent::ParticipationRewarded { who, proposal_id, amount };
+        Self::deposit_event(system, event);
+        Ok(())
     }
 
-    // Finalize a proposal (changes status based on votes)
-    pub fn finalize_proposal(&mut self, proposal_id: u32) -> Result<ProposalStatus, &'static str> {
-        match self.proposals.get_mut(&proposal_id) {
+    // Get the accounts that seconded a proposal
+    pub fn seconds_of(&self, proposal_id: u32) -> &[T::AccountId] {
+        self.seconds.get(&proposal_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    // Return the seconding deposits of a proposal that has been settled
+    fn release_seconds(
+        &mut self,
+        staking: &mut StakingPallet<T>,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        for who in self.seconds.remove(&proposal_id).unwrap_or_default() {
+            staking.unreserve(who, T::SECOND_DEPOSIT).map_err(GovernanceError::Staking)?;
+        }
+        Ok(())
+    }
+
+    // Hash identifying a proposal by its description, call and treasury spend
+    pub fn proposal_hash(
+        description: &str,
+        call: Option<&T::RuntimeCall>,
+        spend: Option<&TreasurySpend<T>>,
+    ) -> T::Hash {
+        let mut data = description.as_bytes().to_vec();
+        if let Some(call) = call {
+            data.extend_from_slice(&T::encode_call(call));
+        }
+        if let Some(spend) = spend {
+            data.extend_from_slice(&T::encode_account(&spend.beneficiary));
+            data.extend_from_slice(&spend.amount.into().to_le_bytes());
+        }
+        T::hash(&data)
+    }
+
+    // Reject proposals identical to an open one or to one rejected during the cooldown
+    fn ensure_not_duplicate(
+        &mut self,
+        system: &SystemPallet<T>,
+        proposal_hash: &T::Hash,
+    ) -> Result<(), GovernanceError> {
+        if self.open_proposal_hashes.contains_key(proposal_hash) {
+            return Err(GovernanceError::DuplicateProposal);
+        }
+
+        let now = system.block_number();
+        self.rejected_hashes.retain(|_, cooldown_end| *cooldown_end >= now);
+        if self.rejected_hashes.contains_key(proposal_hash) {
+            return Err(GovernanceError::ProposalInCooldown);
+        }
+        Ok(())
+    }
+
+    // Reject `count` new proposals from `creator` if they would exceed the number of
+    // proposals allowed per rate limit window
+    fn ensure_within_rate_limit(
+        &mut self,
+        system: &SystemPallet<T>,
+        creator: &T::AccountId,
+        count: u32,
+    ) -> Result<(), GovernanceError> {
+        let now = system.block_number();
+        let created = match self.recent_proposals.get_mut(creator) {
+            Some(created) => created,
+            None => return Ok(()),
+        };
+
+        // Forget proposals that have left the window
+        created.retain(|block| match block.checked_add(&T::PROPOSAL_RATE_WINDOW) {
+            Some(window_end) => window_end > now,
+            None => true,
+        });
+        let recent = created.len() as u32;
+        if created.is_empty() {
+            self.recent_proposals.remove(creator);
+        }
+
+        if recent.saturating_add(count) > T::MAX_PROPOSALS_PER_WINDOW {
+            return Err(GovernanceError::RateLimited);
+        }
+        Ok(())
+    }
+
+    // Create a batch of related proposals that all pass or all fail together. Every proposal
+    // opens immediately with the same voting period (by default the longest of their tracks)
+    // and reserves its own deposit.
+    pub fn create_batch(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        proposals: Vec<ProposalData<T>>,
+        voting_period: Option<T::BlockNumber>,
+    ) -> Result<u32, GovernanceError> {
+        self.ensure_not_paused(system, &creator)?;
+
+        let default_period =
+            proposals.iter().map(|data| T::track_info(data.track).voting_period).max();
+        let voting_period = match (voting_period, default_period) {
+            (_, None) => return Err(GovernanceError::EmptyBatch),
+            (Some(voting_period), _) | (None, Some(voting_period)) => voting_period,
+        };
+
+        // Check slots and deposits up front so the batch is never created partially
+        let batch_size = proposals.len() as u32;
+        if self.active_proposal_count().saturating_add(batch_size) > T::MAX_ACTIVE_PROPOSALS {
+            return Err(GovernanceError::TooManyActiveProposals);
+        }
+        self.ensure_within_rate_limit(system, &creator, batch_size)?;
+        let mut total_deposit = T::Balance::zero();
+        for _ in 0..batch_size {
+            total_deposit = total_deposit
+                .checked_add(&T::PROPOSAL_DEPOSIT)
+                .ok_or(GovernanceError::Overflow)?;
+        }
+        if staking.get_free_balance(creator.clone()) < total_deposit {
+            return Err(GovernanceError::InsufficientDeposit);
+        }
+        if proposals.iter().any(|data| T::track_info(data.track).required_seconds > 0) {
+            return Err(GovernanceError::TrackRequiresSeconds);
+        }
+        let mut batch_hashes = Vec::new();
+        for data in &proposals {
+            let proposal_hash = Self::proposal_hash(&data.description, data.call.as_ref(), None);
+            self.ensure_not_duplicate(system, &proposal_hash)?;
+            if batch_hashes.contains(&proposal_hash) {
+                return Err(GovernanceError::DuplicateProposal);
+            }
+            batch_hashes.push(proposal_hash);
+        }
+
+        let batch_id = self.next_batch_id;
+        let mut proposal_ids = Vec::new();
+        for data in proposals {
+            let proposal_id = self.create_proposal_with_call(
+                system,
+                staking,
+                creator.clone(),
+                data.track,
+                data.description,
+                Some(voting_period),
+                data.call,
+            )?;
+            if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+                proposal.batch = Some(batch_id);
+            }
+            proposal_ids.push(proposal_id);
+        }
+
+        self.batches.insert(batch_id, proposal_ids.clone());
+        self.next_batch_id += 1;
+
+        Self::deposit_event(system, GovernanceEvent::BatchCreated { batch_id, proposal_ids });
+
+        Ok(batch_id)
+    }
+
+    // Vote on a proposal, weighted by the voter's staked balance (through the configured
+    // tally strategy) times their conviction, plus the stake of every account delegating
+    // to them that has not voted itself.
+    // Delegated stake always counts once and is never locked.
+    pub fn vote(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        vote: AccountVote,
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &voter)?;
+        self.apply_undelegations(system);
+
+        if self.commitments.contains_key(&(voter.clone(), proposal_id)) {
+            return Err(GovernanceError::AlreadyVoted);
+        }
+
+        self.do_vote(system, staking, voter, proposal_id, vote, false)
+    }
+
+    // Commit to a vote without revealing it, by submitting `hash(vote ‖ salt)`
+    // (see `vote_commitment`). The vote is only counted once revealed.
+    pub fn commit_vote(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        commitment: T::Hash,
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &voter)?;
+        self.apply_undelegations(system);
+
+        let vote_key = (voter.clone(), proposal_id);
+
+        if self.is_delegating(&voter, proposal_id) {
+            return Err(GovernanceError::VoterIsDelegating);
+        }
+
+        match self.proposals.get(&proposal_id) {
             Some(proposal) => {
                 if proposal.status != ProposalStatus::Active {
-                    return Err("Cannot finalize an already finalized proposal");
+                    return Err(GovernanceError::ProposalNotActive);
                 }
-                
-                let new_status = if proposal.yes_votes > proposal.no_votes {
-                    ProposalStatus::Approved
-                } else {
-                    ProposalStatus::Rejected
-                };
-                
-                proposal.status = new_status.clone();
-                
-                Ok(new_status)
+
+                if system.block_number() > proposal.end_block {
+                    return Err(GovernanceError::VotingPeriodEnded);
+                }
+
+                if proposal.payload != ProposalPayload::Binary {
+                    return Err(GovernanceError::WrongProposalKind);
+                }
+
+                if self.votes.contains_key(&vote_key) || self.commitments.contains_key(&vote_key) {
+                    return Err(GovernanceError::AlreadyVoted);
+                }
+
+                self.commitments.insert(vote_key, commitment);
+                Self::deposit_event(system, GovernanceEvent::VoteCommitted { voter, proposal_id });
+                Ok(())
             },
-            None => Err("No proposal found with the given ID"),
+            None => Err(GovernanceError::ProposalNotFound),
         }
     }
-    
-    // Get full proposal details including description and creator
-    pub fn get_proposal_details(
-        &self,
+
+    // Open a committed vote once the voting period has ended and count it,
+    // weighted by the voter's stake at reveal time
+    pub fn reveal_vote(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
         proposal_id: u32,
-    ) -> Result<(String, T::AccountId), &'static str> {
-        match self.proposals.get(&proposal_id) {
+        vote: AccountVote,
+        salt: &[u8],
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &voter)?;
+
+        let vote_key = (voter.clone(), proposal_id);
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        let reveal_end = proposal
+            .end_block
+            .checked_add(&T::REVEAL_PERIOD)
+            .ok_or(GovernanceError::Overflow)?;
+        let now = system.block_number();
+        if now <= proposal.end_block || now > reveal_end {
+            return Err(GovernanceError::NotInRevealPeriod);
+        }
+
+        match self.commitments.get(&vote_key) {
+            Some(commitment) if *commitment == Self::vote_commitment(vote, salt) => {},
+            Some(_) => return Err(GovernanceError::InvalidReveal),
+            None => return Err(GovernanceError::NoCommitment),
+        }
+
+        self.do_vote(system, staking, voter, proposal_id, vote, true)?;
+        self.commitments.remove(&vote_key);
+        Ok(())
+    }
+
+    // Submit a ranked ballot on a multiple-choice proposal. Only the voter's own stake
+    // counts: delegations apply to yes/no proposals.
+    pub fn vote_ranked(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        ranking: Vec<u32>,
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &voter)?;
+        self.apply_undelegations(system);
+
+        let vote_key = (voter.clone(), proposal_id);
+
+        if self.is_delegating(&voter, proposal_id) {
+            return Err(GovernanceError::VoterIsDelegating);
+        }
+
+        let balance = self.snapshot_stake(staking, proposal_id, &voter);
+        let weight = T::TallyStrategy::weight(balance.into());
+
+        match self.proposals.get_mut(&proposal_id) {
             Some(proposal) => {
-                Ok((proposal.description.clone(), proposal.creator.clone()))
+                if proposal.status != ProposalStatus::Active {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                if system.block_number() > proposal.end_block {
+                    return Err(GovernanceError::VotingPeriodEnded);
+                }
+
+                let option_count = match &proposal.payload {
+                    ProposalPayload::Options { options, .. } => options.len(),
+                    ProposalPayload::Binary => return Err(GovernanceError::WrongProposalKind),
+                };
+
+                let mut seen = BTreeSet::new();
+                let valid = !ranking.is_empty()
+                    && ranking
+                        .iter()
+                        .all(|option| (*option as usize) < option_count && seen.insert(*option));
+                if !valid {
+                    return Err(GovernanceError::InvalidRanking);
+                }
+
+                if self.ballots.contains_key(&vote_key) {
+                    return Err(GovernanceError::AlreadyVoted);
+                }
+
+                if weight == 0 {
+                    return Err(GovernanceError::NoVotingPower);
+                }
+
+                let first = &mut proposal.option_weights[ranking[0] as usize];
+                *first = first.checked_add(weight).ok_or(GovernanceError::Overflow)?;
+                self.ballots.insert(vote_key, Ballot { ranking, balance, weight });
+                self.account_votes.entry(voter.clone()).or_default().insert(proposal_id);
+
+                let now = system.block_number();
+                Self::extend_lock(&mut self.locks, staking, &voter, proposal_id, balance, now);
+
+                Self::deposit_event(
+                    system,
+                    GovernanceEvent::BallotCast { voter, proposal_id, weight },
+                );
+                Ok(())
             },
-            None => Err("No proposal found with the given ID"),
+            None => Err(GovernanceError::ProposalNotFound),
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::Runtime;
+    // Count the ballots of a multiple-choice proposal with its tallying method
+    pub fn tally_options(&self, proposal_id: u32) -> Result<OptionTally, GovernanceError> {
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        let (option_count, method) = match &proposal.payload {
+            ProposalPayload::Options { options, method } => (options.len(), *method),
+            ProposalPayload::Binary => return Err(GovernanceError::WrongProposalKind),
+        };
 
-    #[test]
-    fn test_governance_should_work() {
-        let alice = 1u64;
-        let bob = 2u64;
-        let charlie = 3u64;
+        let ballots: Vec<&Ballot<T>> = self
+            .ballots
+            .iter()
+            .filter(|((_, voted_on), _)| *voted_on == proposal_id)
+            .map(|(_, ballot)| ballot)
+            .collect();
 
-        let mut governance = GovernancePallet::<Runtime>::new();
+        Ok(match method {
+            ChoiceMethod::Plurality => {
+                let totals = proposal.option_weights.clone();
+                let winner = Self::leading_option(&totals, &(0..option_count).collect());
+                OptionTally { totals, winner }
+            },
+            ChoiceMethod::InstantRunoff => Self::instant_runoff(option_count, &ballots)?,
+        })
+    }
 
-        // Create a proposal
-        let proposal_id = governance
-            .create_proposal(alice, "Increase validator rewards".to_string())
-            .unwrap();
+    // The option among `continuing` with strictly more weight than any other
+    fn leading_option(totals: &[u128], continuing: &BTreeSet<usize>) -> Option<u32> {
+        let best = continuing.iter().map(|option| totals[*option]).max()?;
+        let mut leaders = continuing.iter().filter(|option| totals[**option] == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(leader), None) if best > 0 => Some(*leader as u32),
+            _ => None,
+        }
+    }
 
-        // Cast votes
-        governance.vote(alice, proposal_id, true).unwrap(); // Yes vote
-        governance.vote(bob, proposal_id, true).unwrap(); // Yes vote
-        governance.vote(charlie, proposal_id, false).unwrap(); // No vote
+    // Count each ballot for its highest-ranked remaining option and eliminate the weakest
+    // option until one holds a majority. Ties for last place eliminate the later option.
+    fn instant_runoff(
+        option_count: usize,
+        ballots: &[&Ballot<T>],
+    ) -> Result<OptionTally, GovernanceError> {
+        let mut continuing: BTreeSet<usize> = (0..option_count).collect();
+        loop {
+            let mut totals = vec![0u128; option_count];
+            for ballot in ballots {
+                let preferred = ballot
+                    .ranking
+                    .iter()
+                    .map(|option| *option as usize)
+                    .find(|option| continuing.contains(option));
+                if let Some(option) = preferred {
+                    totals[option] =
+                        totals[option].checked_add(ballot.weight).ok_or(GovernanceError::Overflow)?;
+                }
+            }
 
-        // Check proposal status before finalization
-        let proposal = governance.get_proposal(proposal_id).unwrap();
-        assert_eq!(proposal.yes_votes, 2);
-        assert_eq!(proposal.no_votes, 1);
+            let counted = totals
+                .iter()
+                .try_fold(0u128, |counted, total| counted.checked_add(*total))
+                .ok_or(GovernanceError::Overflow)?;
+            let majority =
+                continuing.iter().find(|option| totals[**option] > counted - totals[**option]);
+            if let Some(option) = majority {
+                return Ok(OptionTally { totals, winner: Some(*option as u32) });
+            }
 
-        // Finalize proposal
-        let status = governance.finalize_proposal(proposal_id).unwrap();
-        assert!(matches!(status, ProposalStatus::Approved));
+            let weakest = continuing.iter().map(|option| totals[*option]).min();
+            let all_tied = continuing.iter().all(|option| Some(totals[*option]) == weakest);
+            if counted == 0 || all_tied {
+                return Ok(OptionTally { totals, winner: None });
+            }
 
-        // Check proposal is now approved
-        let finalized_proposal = governance.get_proposal(proposal_id).unwrap();
-        assert!(matches!(
-            finalized_proposal.status,
-            ProposalStatus::Approved
-        ));
+            let eliminated =
+                continuing.iter().rev().find(|option| Some(totals[**option]) == weakest).copied();
+            if let Some(option) = eliminated {
+                continuing.remove(&option);
+            }
+        }
+    }
+
+    // Vote aye with `aye` and nay with `nay` of the voter's stake on the same proposal.
+    // Split votes carry no conviction and count neither delegated stake nor a lock period
+    // beyond the base one.
+    pub fn vote_split(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        aye: T::Balance,
+        nay: T::Balance,
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &voter)?;
+        self.apply_undelegations(system);
+
+        let vote_key = (voter.clone(), proposal_id);
+
+        if self.is_delegating(&voter, proposal_id) {
+            return Err(GovernanceError::VoterIsDelegating);
+        }
+
+        if self.delegated_votes.contains_key(&vote_key) {
+            return Err(GovernanceError::CountedByDelegate);
+        }
+
+        let balance = aye.checked_add(&nay).ok_or(GovernanceError::Overflow)?;
+        if balance > self.snapshot_stake(staking, proposal_id, &voter) {
+            return Err(GovernanceError::SplitExceedsStake);
+        }
+        let split = SplitWeights {
+            aye: T::TallyStrategy::weight(aye.into()),
+            nay: T::TallyStrategy::weight(nay.into()),
+        };
+        let weight = split.aye.checked_add(split.nay).ok_or(GovernanceError::Overflow)?;
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Active {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                if system.block_number() > proposal.end_block {
+                    return Err(GovernanceError::VotingPeriodEnded);
+                }
+
+                if proposal.payload != ProposalPayload::Binary {
+                    return Err(GovernanceError::WrongProposalKind);
+                }
+
+                if self.votes.contains_key(&vote_key) || self.commitments.contains_key(&vote_key) {
+                    return Err(GovernanceError::AlreadyVoted);
+                }
+
+                if weight == 0 {
+                    return Err(GovernanceError::NoVotingPower);
+                }
+
+                let side = if split.aye >= split.nay { Vote::Aye } else { Vote::Nay };
+                let vote = AccountVote { vote: side, conviction: Conviction::Locked1x };
+                let record = VoteRecord { vote, balance, weight, split: Some(split) };
+                let mut tally = proposal.tally;
+                record.count_in(&mut tally)?;
+                proposal.tally = tally;
+                self.votes.insert(vote_key, record);
+                self.account_votes.entry(voter.clone()).or_default().insert(proposal_id);
+                self.proposal_voters.entry(proposal_id).or_default().push(voter.clone());
+
+                let now = system.block_number();
+                Self::extend_lock(&mut self.locks, staking, &voter, proposal_id, balance, now);
+
+                let event = GovernanceEvent::SplitVoted {
+                    voter,
+                    proposal_id,
+                    aye: split.aye,
+                    nay: split.nay,
+                };
+                Self::deposit_event(system, event);
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Count a vote the voter signed off-chain, submitted on their behalf by anyone. The
+    // signature covers `signed_vote_payload` with the voter's current nonce, so it cannot be
+    // replayed once accepted.
+    pub fn vote_signed(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        vote: AccountVote,
+        signature: &T::Signature,
+    ) -> Result<(), GovernanceError> {
+        let nonce = self.vote_nonce(&voter);
+        let payload = Self::signed_vote_payload(proposal_id, vote, nonce);
+        if !T::verify_signature(&voter, &payload, signature) {
+            return Err(GovernanceError::InvalidSignature);
+        }
+
+        self.vote(system, staking, voter.clone(), proposal_id, vote)?;
+        self.vote_nonces.insert(voter, nonce.checked_add(1).ok_or(GovernanceError::Overflow)?);
+        Ok(())
+    }
+
+    // Bytes a voter signs to vote off-chain: the proposal ID, the vote and their nonce
+    pub fn signed_vote_payload(proposal_id: u32, vote: AccountVote, nonce: u64) -> Vec<u8> {
+        let mut data = proposal_id.to_le_bytes().to_vec();
+        data.extend_from_slice(&vote.encode());
+        data.extend_from_slice(&nonce.to_le_bytes());
+        data
+    }
+
+    // Get the nonce the next signed vote of an account must be signed with
+    pub fn vote_nonce(&self, who: &T::AccountId) -> u64 {
+        self.vote_nonces.get(who).copied().unwrap_or(0)
+    }
+
+    // Compute the commitment for a vote, to be submitted with `commit_vote`
+    pub fn vote_commitment(vote: AccountVote, salt: &[u8]) -> T::Hash {
+        let mut data = vote.encode().to_vec();
+        data.extend_from_slice(salt);
+        T::hash(&data)
+    }
+
+    // Count a vote; revealed votes are accepted after the voting period has ended
+    fn do_vote(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        vote: AccountVote,
+        revealed: bool,
+    ) -> Result<(), GovernanceError> {
+        let vote_key = (voter.clone(), proposal_id);
+
+        if self.is_delegating(&voter, proposal_id) {
+            return Err(GovernanceError::VoterIsDelegating);
+        }
+
+        if self.delegated_votes.contains_key(&vote_key) {
+            return Err(GovernanceError::CountedByDelegate);
+        }
+
+        let delegators = self.counted_delegators(&voter, proposal_id);
+        let (balance, weight) =
+            self.vote_weight(staking, proposal_id, &voter, &delegators, vote)?;
+        
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Active {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                if !revealed && system.block_number() > proposal.end_block {
+                    return Err(GovernanceError::VotingPeriodEnded);
+                }
+
+                if proposal.payload != ProposalPayload::Binary {
+                    return Err(GovernanceError::WrongProposalKind);
+                }
+                
+                if self.votes.contains_key(&vote_key) {
+                    return Err(GovernanceError::AlreadyVoted);
+                }
+
+                if weight == 0 {
+                    return Err(GovernanceError::NoVotingPower);
+                }
+
+                let record = VoteRecord { vote, balance, weight, split: None };
+                record.count_in(&mut proposal.tally)?;
+                self.votes.insert(vote_key, record);
+                self.account_votes.entry(voter.clone()).or_default().insert(proposal_id);
+                self.proposal_voters.entry(proposal_id).or_default().push(voter.clone());
+                // The stake behind the vote stays locked until the proposal is finalized,
+                // after which the conviction period applies
+                if vote.vote != Vote::Abstain {
+                    let now = system.block_number();
+                    Self::extend_lock(&mut self.locks, staking, &voter, proposal_id, balance, now);
+                }
+                for delegator in delegators {
+                    self.delegated_votes.insert((delegator, proposal_id), voter.clone());
+                }
+
+                let event = GovernanceEvent::Voted { voter, proposal_id, vote, weight };
+                Self::deposit_event(system, event);
+                
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Change an existing vote, recomputing its weight from the current stake
+    pub fn change_vote(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+        vote: AccountVote,
+    ) -> Result<(), GovernanceError> {
+        self.ensure_not_paused(system, &voter)?;
+        self.apply_undelegations(system);
+
+        let vote_key = (voter.clone(), proposal_id);
+        let delegators = self.counted_delegators(&voter, proposal_id);
+        let (balance, weight) =
+            self.vote_weight(staking, proposal_id, &voter, &delegators, vote)?;
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Active {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                if system.block_number() > proposal.end_block {
+                    return Err(GovernanceError::VotingPeriodEnded);
+                }
+
+                let previous = match self.votes.get(&vote_key) {
+                    Some(record) => record,
+                    None => return Err(GovernanceError::NotVoted),
+                };
+
+                if weight == 0 {
+                    return Err(GovernanceError::NoVotingPower);
+                }
+
+                let record = VoteRecord { vote, balance, weight, split: None };
+                let mut tally = proposal.tally;
+                previous.remove_from(&mut tally);
+                record.count_in(&mut tally)?;
+                proposal.tally = tally;
+
+                self.votes.insert(vote_key, record);
+                // The lock follows the new vote, so switching to abstain releases it
+                let account_locks = self.locks.entry(voter.clone()).or_default();
+                if vote.vote == Vote::Abstain {
+                    account_locks.remove(&proposal_id);
+                } else {
+                    let until = system.block_number();
+                    let lock = account_locks
+                        .entry(proposal_id)
+                        .or_insert(VotingLock { amount: balance, until });
+                    lock.amount = balance;
+                }
+                Self::update_staking_lock(&mut self.locks, staking, &voter);
+
+                // Delegators no longer counted, for example after undelegating, are dropped
+                self.delegated_votes.retain(|(delegator, voted_on), delegate| {
+                    *voted_on != proposal_id || *delegate != voter || delegators.contains(delegator)
+                });
+                for delegator in delegators {
+                    self.delegated_votes.insert((delegator, proposal_id), voter.clone());
+                }
+
+                let event = GovernanceEvent::Voted { voter, proposal_id, vote, weight };
+                Self::deposit_event(system, event);
+
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Retract a vote, releasing any delegated stake it counted and the lock it placed
+    pub fn remove_vote(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        voter: T::AccountId,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let vote_key = (voter.clone(), proposal_id);
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Active {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                if system.block_number() > proposal.end_block {
+                    return Err(GovernanceError::VotingPeriodEnded);
+                }
+
+                let previous = match self.votes.remove(&vote_key) {
+                    Some(record) => record,
+                    None => return Err(GovernanceError::NotVoted),
+                };
+                if let Some(voted_on) = self.account_votes.get_mut(&voter) {
+                    voted_on.remove(&proposal_id);
+                    if voted_on.is_empty() {
+                        self.account_votes.remove(&voter);
+                    }
+                }
+                if let Some(voters) = self.proposal_voters.get_mut(&proposal_id) {
+                    voters.retain(|other| *other != voter);
+                    if voters.is_empty() {
+                        self.proposal_voters.remove(&proposal_id);
+                    }
+                }
+
+                previous.remove_from(&mut proposal.tally);
+
+                self.delegated_votes.retain(|(_, voted_on), delegate| {
+                    *voted_on != proposal_id || *delegate != voter
+                });
+                if let Some(account_locks) = self.locks.get_mut(&voter) {
+                    account_locks.remove(&proposal_id);
+                    Self::update_staking_lock(&mut self.locks, staking, &voter);
+                }
+
+                Self::deposit_event(system, GovernanceEvent::VoteRemoved { voter, proposal_id });
+
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Whether `who` has delegated their vote on the track of a proposal
+    fn is_delegating(&self, who: &T::AccountId, proposal_id: u32) -> bool {
+        self.proposals
+            .get(&proposal_id)
+            .is_some_and(|proposal| self.delegations.contains_key(&(who.clone(), proposal.track)))
+    }
+
+    // Delegators whose stake counts towards `voter`'s vote: they must have delegated the
+    // proposal's track and must not have voted directly nor already been counted through
+    // another delegate
+    fn counted_delegators(&self, voter: &T::AccountId, proposal_id: u32) -> Vec<T::AccountId> {
+        let track = match self.proposals.get(&proposal_id) {
+            Some(proposal) => proposal.track,
+            None => return Vec::new(),
+        };
+        self.delegations
+            .iter()
+            .filter(|((delegator, delegated_track), delegate)| {
+                if *delegated_track != track {
+                    return false;
+                }
+                let delegator_key = ((*delegator).clone(), proposal_id);
+                let counted_elsewhere = self
+                    .delegated_votes
+                    .get(&delegator_key)
+                    .is_some_and(|counted_by| counted_by != voter);
+                *delegate == voter && !self.votes.contains_key(&delegator_key) && !counted_elsewhere
+            })
+            .map(|((delegator, _), _)| delegator.clone())
+            .collect()
+    }
+
+    // The voter's own stake and the total weight of a vote including delegated stake
+    fn vote_weight(
+        &self,
+        stake: &impl StakeProvider<T>,
+        proposal_id: u32,
+        voter: &T::AccountId,
+        delegators: &[T::AccountId],
+        vote: AccountVote,
+    ) -> Result<(T::Balance, u128), GovernanceError> {
+        let balance = self.snapshot_stake(stake, proposal_id, voter);
+        let mut weight = T::TallyStrategy::weight(balance.into())
+            .checked_mul(vote.multiplier())
+            .ok_or(GovernanceError::Overflow)?;
+        for delegator in delegators {
+            let delegated = self.snapshot_stake(stake, proposal_id, delegator).into();
+            weight = weight
+                .checked_add(T::TallyStrategy::weight(delegated))
+                .ok_or(GovernanceError::Overflow)?;
+        }
+        Ok((balance, weight))
+    }
+
+    // Stake an account can vote with on a proposal: what it had staked when the proposal
+    // was created, capped at what it still has staked now
+    fn snapshot_stake(
+        &self,
+        stake: &impl StakeProvider<T>,
+        proposal_id: u32,
+        who: &T::AccountId,
+    ) -> T::Balance {
+        let snapshot = self
+            .proposals
+            .get(&proposal_id)
+            .and_then(|proposal| self.stake_snapshots.get(&proposal.snapshot_block))
+            .and_then(|snapshot| snapshot.get(who).copied())
+            .unwrap_or_else(T::Balance::zero);
+        let current = stake.bonded_of(who);
+        if current < snapshot { current } else { snapshot }
+    }
+
+    // Drop stake snapshots no open proposal refers to any more
+    fn prune_snapshots(&mut self) {
+        let proposals = &self.proposals;
+        self.stake_snapshots.retain(|block, _| {
+            proposals.values().any(|proposal| {
+                proposal.snapshot_block == *block
+                    && proposal.is_open()
+            })
+        });
+    }
+
+    // Delegate voting power on proposals of `track` to another account. Each track is
+    // delegated separately, so a voter can keep voting on other tracks themselves.
+    pub fn delegate(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        from: T::AccountId,
+        track: Track,
+        to: T::AccountId,
+    ) -> Result<(), GovernanceError> {
+        self.apply_undelegations(system);
+
+        if from == to {
+            return Err(GovernanceError::SelfDelegation);
+        }
+
+        if self.delegations.contains_key(&(from.clone(), track)) {
+            return Err(GovernanceError::AlreadyDelegating);
+        }
+
+        // Only one level of delegation is supported, which also rules out cycles
+        if self.delegations.contains_key(&(to.clone(), track)) {
+            return Err(GovernanceError::DelegateIsDelegating);
+        }
+
+        let has_delegators = self
+            .delegations
+            .iter()
+            .any(|((_, delegated_track), delegate)| *delegated_track == track && *delegate == from);
+        if has_delegators {
+            return Err(GovernanceError::HasDelegators);
+        }
+
+        self.delegations.insert((from.clone(), track), to.clone());
+        Self::deposit_event(system, GovernanceEvent::Delegated { from, track, to });
+        Ok(())
+    }
+
+    // Revoke a delegation. It stays in effect for the undelegation cooldown, so delegates
+    // cannot be switched right before a proposal is finalized.
+    pub fn undelegate(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        from: T::AccountId,
+        track: Track,
+    ) -> Result<(), GovernanceError> {
+        self.apply_undelegations(system);
+
+        let key = (from.clone(), track);
+        if !self.delegations.contains_key(&key) {
+            return Err(GovernanceError::NotDelegating);
+        }
+
+        if self.pending_undelegations.contains_key(&key) {
+            return Err(GovernanceError::UndelegationPending);
+        }
+
+        let effective_at = system
+            .block_number()
+            .checked_add(&T::UNDELEGATION_COOLDOWN)
+            .ok_or(GovernanceError::Overflow)?;
+        self.pending_undelegations.insert(key, effective_at);
+        let event = GovernanceEvent::UndelegationScheduled { from, track, effective_at };
+        Self::deposit_event(system, event);
+
+        self.apply_undelegations(system);
+        Ok(())
+    }
+
+    // Get the block at which a revoked delegation stops counting, if one is pending
+    pub fn undelegation_effective_at(
+        &self,
+        who: &T::AccountId,
+        track: Track,
+    ) -> Option<T::BlockNumber> {
+        self.pending_undelegations.get(&(who.clone(), track)).copied()
+    }
+
+    // Remove every revoked delegation whose cooldown has passed
+    fn apply_undelegations(&mut self, system: &mut SystemPallet<T>) {
+        let now = system.block_number();
+        let mut matured: Vec<(T::AccountId, Track)> = Vec::new();
+        self.pending_undelegations.retain(|from, effective_at| {
+            if *effective_at <= now {
+                matured.push(from.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        for (from, track) in matured {
+            self.delegations.remove(&(from.clone(), track));
+            Self::deposit_event(system, GovernanceEvent::Undelegated { from, track });
+        }
+    }
+
+    // Replace the set of accounts allowed to veto approved proposals. Requires root.
+    pub fn set_technical_committee(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        members: Vec<T::AccountId>,
+    ) -> Result<(), GovernanceError> {
+        Self::ensure_root(&origin)?;
+
+        self.technical_committee = members.clone();
+        Self::deposit_event(system, GovernanceEvent::TechnicalCommitteeChanged { members });
+        Ok(())
+    }
+
+    // Freeze proposing, seconding and voting for incident response. Requires root or the
+    // council; queries and finalization keep working.
+    pub fn pause(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+    ) -> Result<(), GovernanceError> {
+        Self::ensure_root_or_council(&origin)?;
+
+        self.paused = true;
+        Self::deposit_event(system, GovernanceEvent::Paused);
+        Ok(())
+    }
+
+    // Lift a pause set by `pause`. Requires root or the council.
+    pub fn unpause(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+    ) -> Result<(), GovernanceError> {
+        Self::ensure_root_or_council(&origin)?;
+
+        self.paused = false;
+        Self::deposit_event(system, GovernanceEvent::Unpaused);
+        Ok(())
+    }
+
+    // Ensure a call was made by root
+    fn ensure_root(origin: &Origin<T>) -> Result<(), GovernanceError> {
+        match origin {
+            Origin::Root => Ok(()),
+            _ => Err(GovernanceError::BadOrigin),
+        }
+    }
+
+    // Ensure a call was made by root or by the council as a whole
+    fn ensure_root_or_council(origin: &Origin<T>) -> Result<(), GovernanceError> {
+        match origin {
+            Origin::Root | Origin::Council => Ok(()),
+            _ => Err(GovernanceError::BadOrigin),
+        }
+    }
+
+    // Ensure a call was signed by a technical committee member or made by the weighted
+    // committee, returning the member if there is one
+    fn ensure_technical_committee(
+        &self,
+        origin: Origin<T>,
+    ) -> Result<Option<T::AccountId>, GovernanceError> {
+        match origin {
+            Origin::Signed(who) if self.technical_committee.contains(&who) => Ok(Some(who)),
+            Origin::Committee => Ok(None),
+            Origin::Signed(_) => Err(GovernanceError::NotTechnicalCommittee),
+            _ => Err(GovernanceError::BadOrigin),
+        }
+    }
+
+    // Whether proposing and voting are currently frozen
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Reject an attempt by `who` to propose or vote while governance is paused
+    fn ensure_not_paused(
+        &self,
+        system: &mut SystemPallet<T>,
+        who: &T::AccountId,
+    ) -> Result<(), GovernanceError> {
+        if self.paused {
+            let event = GovernanceEvent::RejectedWhilePaused { who: who.clone() };
+            Self::deposit_event(system, event);
+            return Err(GovernanceError::GovernancePaused);
+        }
+        Ok(())
+    }
+
+    // Execute a governance call from an enacted proposal
+    pub fn dispatch(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        call: GovernanceCall<T>,
+    ) -> Result<(), &'static str> {
+        match call {
+            GovernanceCall::SetProposerAllowlist(members) => self.proposer_allowlist = members,
+            GovernanceCall::AddProposer(who) => match &mut self.proposer_allowlist {
+                Some(members) if !members.contains(&who) => members.push(who),
+                Some(_) => return Err("Account is already on the proposer allowlist"),
+                None => return Err("Proposer allowlist is not enabled"),
+            },
+            GovernanceCall::RemoveProposer(who) => match &mut self.proposer_allowlist {
+                Some(members) if members.contains(&who) => members.retain(|member| *member != who),
+                Some(_) => return Err("Account is not on the proposer allowlist"),
+                None => return Err("Proposer allowlist is not enabled"),
+            },
+        }
+
+        let members = self.proposer_allowlist.clone();
+        Self::deposit_event(system, GovernanceEvent::ProposerAllowlistChanged { members });
+        Ok(())
+    }
+
+    // Whether `who` may create proposals: anyone can unless the allowlist is enabled
+    pub fn can_propose(&self, who: &T::AccountId) -> bool {
+        match &self.proposer_allowlist {
+            Some(members) => members.contains(who),
+            None => true,
+        }
+    }
+
+    // Get the proposer allowlist, or None if anyone may propose
+    pub fn proposer_allowlist(&self) -> Option<&[T::AccountId]> {
+        self.proposer_allowlist.as_deref()
+    }
+
+    // Veto an approved proposal before it is enacted, recording who vetoed it. Must be
+    // signed by a technical committee member or made by the weighted committee.
+    pub fn veto_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let vetoer = self.ensure_technical_committee(origin)?;
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Approved {
+                    return Err(GovernanceError::ProposalNotApproved);
+                }
+
+                proposal.status = ProposalStatus::Vetoed;
+                proposal.vetoed_by = vetoer.clone();
+
+                let event = GovernanceEvent::ProposalVetoed { proposal_id, vetoer };
+                Self::deposit_event(system, event);
+
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Flag an open proposal as malicious, so that its deposit is slashed if it is rejected.
+    // Must be signed by a technical committee member or made by the weighted committee.
+    pub fn flag_malicious(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let who = self.ensure_technical_committee(origin)?;
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if !proposal.is_open() {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                proposal.flagged = true;
+                proposal.flagged_by = who.clone();
+                let event = GovernanceEvent::ProposalFlagged { proposal_id, by: who };
+                Self::deposit_event(system, event);
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Return what is left of the deposit of a settled proposal to its creator
+    pub fn claim_deposit(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.creator != who {
+                    return Err(GovernanceError::NotCreator);
+                }
+                if proposal.is_open() {
+                    return Err(GovernanceError::ProposalStillOpen);
+                }
+                if proposal.deposit.is_zero() {
+                    return Err(GovernanceError::NoDepositToClaim);
+                }
+
+                let amount = proposal.deposit;
+                staking.unreserve(who.clone(), amount).map_err(GovernanceError::Staking)?;
+                proposal.deposit = T::Balance::zero();
+
+                let event = GovernanceEvent::DepositClaimed { proposal_id, who, amount };
+                Self::deposit_event(system, event);
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Shorten the voting period of an active proposal to end at `new_deadline` and lower its
+    // quorum, for emergencies such as security fixes. Must be signed by a technical committee
+    // member or made by the weighted committee.
+    pub fn fast_track(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        origin: Origin<T>,
+        proposal_id: u32,
+        new_deadline: T::BlockNumber,
+    ) -> Result<(), GovernanceError> {
+        let who = self.ensure_technical_committee(origin)?;
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Active {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                if proposal.batch.is_some() {
+                    return Err(GovernanceError::ProposalInBatch);
+                }
+
+                if new_deadline < system.block_number() || new_deadline >= proposal.end_block {
+                    return Err(GovernanceError::InvalidDeadline);
+                }
+
+                proposal.end_block = new_deadline;
+                proposal.fast_tracked = true;
+                let quorum = T::track_info(proposal.track).quorum.min(T::FAST_TRACK_QUORUM);
+
+                let event = GovernanceEvent::ProposalFastTracked {
+                    proposal_id,
+                    by: who,
+                    end_block: new_deadline,
+                    quorum,
+                };
+                Self::deposit_event(system, event);
+
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Mark an approved proposal as enacted and hand its call over for dispatch, once its
+    // enactment block is reached. Called by the runtime, which owns the pallets the call
+    // is routed to.
+    pub fn enact_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        proposal_id: u32,
+    ) -> Result<T::RuntimeCall, GovernanceError> {
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.status != ProposalStatus::Approved {
+                    return Err(GovernanceError::ProposalNotApproved);
+                }
+                if let Some(enactment_block) = proposal.enactment_block {
+                    if system.block_number() < enactment_block {
+                        return Err(GovernanceError::EnactmentDelayNotOver);
+                    }
+                }
+
+                let call = proposal.call.take().ok_or(GovernanceError::NoCallToEnact)?;
+                proposal.status = ProposalStatus::Enacted;
+
+                Self::deposit_event(system, GovernanceEvent::ProposalEnacted { proposal_id });
+
+                Ok(call)
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Cancel a queued proposal or one that has not received any votes yet, refunding the deposit
+    pub fn cancel_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let has_votes = self.votes.keys().any(|(_, voted_on)| *voted_on == proposal_id);
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                if proposal.creator != creator {
+                    return Err(GovernanceError::NotCreator);
+                }
+
+                if proposal.batch.is_some() {
+                    return Err(GovernanceError::ProposalInBatch);
+                }
+
+                if !matches!(
+                    proposal.status,
+                    ProposalStatus::Proposed | ProposalStatus::Active | ProposalStatus::Pending
+                ) {
+                    return Err(GovernanceError::ProposalNotActive);
+                }
+
+                let has_ballots = proposal.option_weights.iter().any(|weight| *weight > 0);
+                if has_votes || has_ballots {
+                    return Err(GovernanceError::ProposalHasVotes);
+                }
+
+                staking
+                    .unreserve(proposal.creator.clone(), proposal.deposit)
+                    .map_err(GovernanceError::Staking)?;
+                proposal.deposit = T::Balance::zero();
+                proposal.status = ProposalStatus::Cancelled;
+                proposal.finalized_at = Some(system.block_number());
+                self.open_proposal_hashes.remove(&proposal.proposal_hash);
+            },
+            None => return Err(GovernanceError::ProposalNotFound),
+        }
+
+        self.pending_queue.retain(|queued_id| *queued_id != proposal_id);
+        self.release_seconds(staking, proposal_id)?;
+        self.prune_snapshots();
+        Self::deposit_event(system, GovernanceEvent::ProposalCancelled { proposal_id });
+        self.activate_pending_proposals(system);
+
+        Ok(())
+    }
+
+    // Lock `amount` of `who`'s stake for `proposal_id` until at least `until`. A repeated
+    // lock for the same proposal keeps the largest amount and latest expiry.
+    fn extend_lock(
+        locks: &mut HashMap<T::AccountId, BTreeMap<u32, VotingLock<T>>>,
+        staking: &mut StakingPallet<T>,
+        who: &T::AccountId,
+        proposal_id: u32,
+        amount: T::Balance,
+        until: T::BlockNumber,
+    ) {
+        let lock = locks.entry(who.clone()).or_default().entry(proposal_id).or_insert(
+            VotingLock { amount: T::Balance::zero(), until },
+        );
+        if amount > lock.amount {
+            lock.amount = amount;
+        }
+        if until > lock.until {
+            lock.until = until;
+        }
+        Self::update_staking_lock(locks, staking, who);
+    }
+
+    // Lock the largest amount any of `who`'s proposal locks asks for, since the locks overlap
+    fn update_staking_lock(
+        locks: &mut HashMap<T::AccountId, BTreeMap<u32, VotingLock<T>>>,
+        staking: &mut StakingPallet<T>,
+        who: &T::AccountId,
+    ) {
+        let largest = locks
+            .get(who)
+            .and_then(|account_locks| {
+                account_locks.values().map(|lock| lock.amount).reduce(|largest, amount| {
+                    if amount > largest { amount } else { largest }
+                })
+            });
+        match largest {
+            Some(amount) => staking.set_lock(GOVERNANCE_LOCK, who.clone(), amount),
+            None => {
+                locks.remove(who);
+                staking.remove_lock(GOVERNANCE_LOCK, who.clone());
+            },
+        }
+    }
+
+    // Release every expired lock of an account. Locks for proposals still open for voting
+    // stay in place, and the stake stays locked for the largest remaining one.
+    pub fn unlock(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+    ) -> Result<(), GovernanceError> {
+        let now = system.block_number();
+        let proposals = &self.proposals;
+
+        match self.locks.get_mut(&who) {
+            Some(account_locks) => {
+                let before = account_locks.len();
+                account_locks.retain(|proposal_id, lock| {
+                    now < lock.until
+                        || proposals
+                            .get(proposal_id)
+                            .is_some_and(|proposal| proposal.status == ProposalStatus::Active)
+                });
+                if account_locks.len() == before {
+                    return Err(GovernanceError::LockNotExpired);
+                }
+
+                Self::update_staking_lock(&mut self.locks, staking, &who);
+                Self::deposit_event(system, GovernanceEvent::Unlocked { who });
+                Ok(())
+            },
+            None => Err(GovernanceError::NoVotingLock),
+        }
+    }
+
+    // Get proposal details
+    pub fn get_proposal(&self, proposal_id: u32) -> Option<&Proposal<T>> {
+        self.proposals.get(&proposal_id)
+    }
+
+    // Iterate over all proposals in ID order. Clients can paginate with `skip` and `take`.
+    pub fn iter_proposals(&self) -> impl Iterator<Item = (u32, &Proposal<T>)> + '_ {
+        let mut proposal_ids: Vec<u32> = self.proposals.keys().copied().collect();
+        proposal_ids.sort();
+        proposal_ids
+            .into_iter()
+            .filter_map(|proposal_id| Some((proposal_id, self.proposals.get(&proposal_id)?)))
+    }
+
+    // Iterate over the proposals open for voting, in ID order
+    pub fn active_proposals(&self) -> impl Iterator<Item = (u32, &Proposal<T>)> + '_ {
+        self.proposals_by_status(ProposalStatus::Active)
+    }
+
+    // Iterate over the proposals with the given status, in ID order
+    pub fn proposals_by_status(
+        &self,
+        status: ProposalStatus,
+    ) -> impl Iterator<Item = (u32, &Proposal<T>)> + '_ {
+        self.iter_proposals().filter(move |(_, proposal)| proposal.status == status)
+    }
+
+    // Iterate over the proposals created by an account, in ID order
+    pub fn proposals_by_creator<'a>(
+        &'a self,
+        creator: &'a T::AccountId,
+    ) -> impl Iterator<Item = (u32, &'a Proposal<T>)> + 'a {
+        self.iter_proposals().filter(move |(_, proposal)| proposal.creator == *creator)
+    }
+
+    // Iterate over the proposals carrying `tag`, in ID order
+    pub fn proposals_with_tag(&self, tag: &str) -> impl Iterator<Item = (u32, &Proposal<T>)> + '_ {
+        self.tagged_proposals
+            .get(&tag.trim().to_lowercase())
+            .into_iter()
+            .flatten()
+            .filter_map(|proposal_id| Some((*proposal_id, self.proposals.get(proposal_id)?)))
+    }
+
+    // Replace the tags of a proposal that is still open. Only the creator can tag it; tags
+    // are trimmed, lowercased and deduplicated.
+    pub fn set_tags(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        creator: T::AccountId,
+        proposal_id: u32,
+        tags: Vec<String>,
+    ) -> Result<(), GovernanceError> {
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() || tag.len() > T::MAX_TAG_LEN as usize {
+                return Err(GovernanceError::InvalidTag);
+            }
+            if !normalized.contains(&tag) {
+                normalized.push(tag);
+            }
+        }
+        if normalized.len() > T::MAX_TAGS as usize {
+            return Err(GovernanceError::TooManyTags);
+        }
+
+        let proposal = self
+            .proposals
+            .get_mut(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if proposal.creator != creator {
+            return Err(GovernanceError::NotCreator);
+        }
+        if !proposal.is_open() {
+            return Err(GovernanceError::ProposalNotActive);
+        }
+
+        for tag in &proposal.tags {
+            if let Some(proposal_ids) = self.tagged_proposals.get_mut(tag) {
+                proposal_ids.remove(&proposal_id);
+                if proposal_ids.is_empty() {
+                    self.tagged_proposals.remove(tag);
+                }
+            }
+        }
+        for tag in &normalized {
+            self.tagged_proposals.entry(tag.clone()).or_default().insert(proposal_id);
+        }
+        proposal.tags = normalized.clone();
+
+        let event = GovernanceEvent::ProposalTagged { proposal_id, tags: normalized };
+        Self::deposit_event(system, event);
+        Ok(())
+    }
+
+    // Get the vote an account cast on a proposal
+    pub fn get_vote(&self, voter: &T::AccountId, proposal_id: u32) -> Option<&VoteRecord<T>> {
+        self.votes.get(&(voter.clone(), proposal_id))
+    }
+
+    // Get every account that voted on a proposal and how, in the order the votes were cast.
+    // Ranked ballots are not included.
+    pub fn voters_of(&self, proposal_id: u32) -> Vec<(T::AccountId, Vote)> {
+        match self.proposal_voters.get(&proposal_id) {
+            Some(voters) => voters
+                .iter()
+                .filter_map(|voter| {
+                    let record = self.votes.get(&(voter.clone(), proposal_id))?;
+                    Some((voter.clone(), record.vote.vote))
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Get the account a voter delegates to on a track, if any
+    pub fn get_delegate(&self, delegator: &T::AccountId, track: Track) -> Option<&T::AccountId> {
+        self.delegations.get(&(delegator.clone(), track))
+    }
+
+    // Get the lock an account holds for its vote on a proposal, if any
+    pub fn get_lock(&self, who: &T::AccountId, proposal_id: u32) -> Option<&VotingLock<T>> {
+        self.locks.get(who)?.get(&proposal_id)
+    }
+
+    // Get the current technical committee
+    pub fn technical_committee(&self) -> &[T::AccountId] {
+        &self.technical_committee
+    }
+
+    // Get the proposals of a batch
+    pub fn get_batch(&self, batch_id: u32) -> Option<&[u32]> {
+        self.batches.get(&batch_id).map(|proposal_ids| proposal_ids.as_slice())
+    }
+
+    // Get the proposals waiting for an active slot, oldest first
+    pub fn pending_proposals(&self) -> impl Iterator<Item = u32> + '_ {
+        self.pending_queue.iter().copied()
+    }
+
+    // Store a description again after it was reaped, returning its hash
+    pub fn note_description(&mut self, description: String) -> T::Hash {
+        self.preimages.note_preimage(description.into_bytes())
+    }
+
+    // Get the number of blocks left before voting closes (zero once the deadline has passed)
+    pub fn remaining_voting_time(
+        &self,
+        system: &SystemPallet<T>,
+        proposal_id: u32,
+    ) -> Result<T::BlockNumber, GovernanceError> {
+        match self.proposals.get(&proposal_id) {
+            Some(proposal) if proposal.status == ProposalStatus::Pending => {
+                Ok(proposal.voting_period)
+            },
+            Some(proposal) => Ok(proposal
+                .end_block
+                .checked_sub(&system.block_number())
+                .unwrap_or_else(T::BlockNumber::zero)),
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Finalize a proposal (changes status based on votes), scheduling the enactment of approved
+    // calls and treasury spends.
+    // The deposit is slashed if the proposal is rejected and refunded otherwise,
+    // and every voter's stake is locked according to their conviction.
+    // Proposals in a batch are finalized together and only approved if all of them pass.
+    pub fn finalize_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        proposal_id: u32,
+    ) -> Result<ProposalStatus, GovernanceError> {
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(GovernanceError::ProposalNotActive);
+        }
+
+        let electorate = staking.total_bonded();
+
+        // Proposals in a batch are finalized together
+        let members = match proposal.batch.and_then(|batch_id| self.batches.get(&batch_id)) {
+            Some(members) => members.clone(),
+            None => vec![proposal_id],
+        };
+
+        // Amendments are decided before the proposals they amend
+        let mut amendments: Vec<u32> = self
+            .proposals
+            .iter()
+            .filter(|(_, amendment)| {
+                amendment.status == ProposalStatus::Active
+                    && amendment.parent.is_some_and(|parent| members.contains(&parent))
+            })
+            .map(|(amendment_id, _)| *amendment_id)
+            .collect();
+        amendments.sort();
+        for amendment_id in amendments {
+            self.finalize_proposal(system, staking, amendment_id)?;
+        }
+
+        let mut outcomes = Vec::new();
+        let mut winners = Vec::new();
+        for member_id in &members {
+            let member = self
+                .proposals
+                .get(member_id)
+                .ok_or(GovernanceError::ProposalNotFound)?;
+            if member.status != ProposalStatus::Active {
+                return Err(GovernanceError::ProposalNotActive);
+            }
+            if system.block_number() < member.end_block {
+                return Err(GovernanceError::VotingPeriodOpen);
+            }
+
+            // Only revealed votes are counted, so wait until every commitment is opened
+            // or the reveal period is over
+            let has_commitments = self.commitments.keys().any(|(_, id)| id == member_id);
+            let reveal_end = member
+                .end_block
+                .checked_add(&T::REVEAL_PERIOD)
+                .ok_or(GovernanceError::Overflow)?;
+            if has_commitments && system.block_number() <= reveal_end {
+                return Err(GovernanceError::RevealPeriodOpen);
+            }
+
+            let outcome = match member.payload {
+                ProposalPayload::Binary => member.outcome(electorate),
+                ProposalPayload::Options { .. } => {
+                    let tally = self.tally_options(*member_id)?;
+                    let outcome = member.choice_outcome(&tally);
+                    if outcome == ProposalStatus::Approved {
+                        winners.push((*member_id, tally.winner));
+                    }
+                    outcome
+                },
+            };
+            outcomes.push((*member_id, outcome));
+        }
+
+        for (member_id, winner) in winners {
+            if let Some(member) = self.proposals.get_mut(&member_id) {
+                member.winning_option = winner;
+            }
+        }
+
+        // A batch is approved only if every proposal in it is approved on its own
+        let batch_approved =
+            outcomes.iter().all(|(_, outcome)| *outcome == ProposalStatus::Approved);
+        for (member_id, outcome) in outcomes {
+            let status = match outcome {
+                ProposalStatus::Approved if !batch_approved => ProposalStatus::Rejected,
+                _ => outcome.clone(),
+            };
+            self.conclude_proposal(system, staking, member_id, outcome, status)?;
+        }
+        self.prune_snapshots();
+
+        self.activate_pending_proposals(system);
+
+        self.proposals
+            .get(&proposal_id)
+            .map(|proposal| proposal.status.clone())
+            .ok_or(GovernanceError::ProposalNotFound)
+    }
+
+    // Settle a finalized proposal: handle the deposit according to its own `outcome`, lock
+    // the stake of its voters and record its final `status`
+    fn conclude_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        proposal_id: u32,
+        outcome: ProposalStatus,
+        status: ProposalStatus,
+    ) -> Result<(), GovernanceError> {
+        self.commitments.retain(|(_, id), _| *id != proposal_id);
+        self.release_seconds(staking, proposal_id)?;
+        if status == ProposalStatus::Approved {
+            self.apply_amendment(system, proposal_id)?;
+        }
+
+        match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) => {
+                // The deposit is slashed in full if a proposal flagged as malicious is
+                // rejected and in part if quorum is not reached. Whatever is left stays
+                // reserved until the creator claims it.
+                let penalty = match outcome {
+                    ProposalStatus::Rejected if proposal.flagged => proposal.deposit,
+                    ProposalStatus::Failed => T::QUORUM_FAILURE_PENALTY,
+                    _ => T::Balance::zero(),
+                };
+                if !penalty.is_zero() {
+                    let amount = staking.slash_reserved(proposal.creator.clone(), penalty);
+                    proposal.deposit = proposal
+                        .deposit
+                        .checked_sub(&amount)
+                        .unwrap_or_else(T::Balance::zero);
+                    Self::deposit_event(
+                        system,
+                        GovernanceEvent::DepositSlashed { proposal_id, amount },
+                    );
+                }
+
+                // Only the winning side stays locked for its conviction period; the locks of
+                // everyone else, including split voters, expire with the proposal
+                let now = system.block_number();
+                let winning_side = match status {
+                    ProposalStatus::Approved => Some(Vote::Aye),
+                    ProposalStatus::Rejected => Some(Vote::Nay),
+                    _ => None,
+                };
+                for ((voter, voted_on), record) in self.votes.iter() {
+                    let on_winning_side = Some(record.vote.vote) == winning_side;
+                    if *voted_on != proposal_id || !on_winning_side || record.split.is_some() {
+                        continue;
+                    }
+
+                    let lock_duration = T::VOTE_LOCKING_PERIOD
+                        .checked_mul(&record.vote.conviction.lock_periods().into())
+                        .ok_or(GovernanceError::Overflow)?;
+                    let until = now
+                        .checked_add(&lock_duration)
+                        .ok_or(GovernanceError::Overflow)?;
+
+                    let balance = record.balance;
+                    Self::extend_lock(&mut self.locks, staking, voter, proposal_id, balance, until);
+                }
+                for ((voter, voted_on), ballot) in self.ballots.iter() {
+                    let backed_winner = status == ProposalStatus::Approved
+                        && ballot.ranking.first().copied() == proposal.winning_option;
+                    if *voted_on != proposal_id || !backed_winner {
+                        continue;
+                    }
+                    let until = now
+                        .checked_add(&T::VOTE_LOCKING_PERIOD)
+                        .ok_or(GovernanceError::Overflow)?;
+                    let balance = ballot.balance;
+                    Self::extend_lock(&mut self.locks, staking, voter, proposal_id, balance, until);
+                }
+
+                proposal.status = status.clone();
+                proposal.finalized_at = Some(now);
+
+                // An approved amendment's hash now belongs to its still open parent
+                if self.open_proposal_hashes.get(&proposal.proposal_hash) == Some(&proposal_id) {
+                    self.open_proposal_hashes.remove(&proposal.proposal_hash);
+                }
+                if status == ProposalStatus::Rejected {
+                    let cooldown_end = now
+                        .checked_add(&T::RESUBMISSION_COOLDOWN)
+                        .ok_or(GovernanceError::Overflow)?;
+                    self.rejected_hashes.insert(proposal.proposal_hash.clone(), cooldown_end);
+                }
+
+                Self::deposit_event(
+                    system,
+                    GovernanceEvent::ProposalFinalized { proposal_id, status },
+                );
+
+                // Approved calls and spends are not enacted right away, giving accounts time to
+                // react and the technical committee time to veto them
+                let enactable = proposal.call.is_some() || proposal.spend.is_some();
+                if proposal.status == ProposalStatus::Approved && enactable {
+                    let at = now
+                        .checked_add(&T::ENACTMENT_DELAY)
+                        .ok_or(GovernanceError::Overflow)?;
+                    proposal.enactment_block = Some(at);
+                    self.scheduled_enactments.entry(at).or_default().push(proposal_id);
+                    Self::deposit_event(
+                        system,
+                        GovernanceEvent::EnactmentScheduled { proposal_id, at },
+                    );
+                }
+
+                Ok(())
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Start-of-block hook: enact every proposal scheduled up to `block_number`, paying out
+    // treasury spends and returning the calls for the runtime to dispatch. Proposals vetoed
+    // in the meantime are skipped.
+    pub fn on_initialize(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        block_number: T::BlockNumber,
+    ) -> Vec<(u32, T::RuntimeCall)> {
+        let later = match block_number.checked_add(&T::BlockNumber::one()) {
+            Some(next) => self.scheduled_enactments.split_off(&next),
+            None => BTreeMap::new(),
+        };
+        let due = std::mem::replace(&mut self.scheduled_enactments, later);
+
+        let mut calls = Vec::new();
+        for proposal_id in due.into_values().flatten() {
+            let is_spend = self
+                .proposals
+                .get(&proposal_id)
+                .is_some_and(|proposal| proposal.spend.is_some());
+            if is_spend {
+                self.enact_spend(system, staking, proposal_id, block_number);
+            } else if let Ok(call) = self.enact_proposal(system, proposal_id) {
+                calls.push((proposal_id, call));
+            }
+        }
+        calls
+    }
+
+    // Pay out an approved treasury spend. If the treasury cannot cover the payment the
+    // proposal stays approved and the payment is retried in the next block.
+    fn enact_spend(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        proposal_id: u32,
+        block_number: T::BlockNumber,
+    ) {
+        let proposal = match self.proposals.get_mut(&proposal_id) {
+            Some(proposal) if proposal.status == ProposalStatus::Approved => proposal,
+            _ => return,
+        };
+        if let Some(spend) = &proposal.spend {
+            let beneficiary = spend.beneficiary.clone();
+            let amount = spend.amount;
+            if staking.transfer(T::TREASURY_ACCOUNT, beneficiary.clone(), amount).is_ok() {
+                proposal.status = ProposalStatus::Enacted;
+                let event = GovernanceEvent::TreasurySpent { proposal_id, beneficiary, amount };
+                Self::deposit_event(system, event);
+            } else if let Some(at) = block_number.checked_add(&T::BlockNumber::one()) {
+                proposal.enactment_block = Some(at);
+                self.scheduled_enactments.entry(at).or_default().push(proposal_id);
+                let event = GovernanceEvent::EnactmentScheduled { proposal_id, at };
+                Self::deposit_event(system, event);
+            }
+        }
+    }
+
+    // End-of-block hook: finalize every active proposal whose voting period has ended and
+    // archive those past their archive period.
+    // Proposals still waiting on committed votes are retried in a later block.
+    pub fn on_finalize(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        block_number: T::BlockNumber,
+    ) {
+        self.apply_undelegations(system);
+
+        let mut expired: Vec<u32> = self
+            .proposals
+            .iter()
+            .filter(|(_, proposal)| {
+                proposal.status == ProposalStatus::Active && proposal.end_block <= block_number
+            })
+            .map(|(proposal_id, _)| *proposal_id)
+            .collect();
+        expired.sort();
+
+        for proposal_id in expired {
+            let _ = self.finalize_proposal(system, staking, proposal_id);
+        }
+
+        // Move proposals past their archive period out of the live store
+        let mut archivable: Vec<u32> = self
+            .proposals
+            .iter()
+            .filter(|(_, proposal)| {
+                !proposal.is_open()
+                    && proposal.finalized_at.is_some_and(|finalized_at| {
+                        finalized_at
+                            .checked_add(&T::ARCHIVE_PERIOD)
+                            .is_some_and(|archive_at| archive_at < block_number)
+                    })
+            })
+            .map(|(proposal_id, _)| *proposal_id)
+            .collect();
+        archivable.sort();
+
+        for proposal_id in archivable {
+            let _ = self.archive_proposal(system, staking, proposal_id);
+        }
+    }
+
+    // Get every vote cast by an account, ordered by proposal ID
+    pub fn votes_by_account(&self, account: &T::AccountId) -> Vec<(u32, AccountVote)> {
+        match self.account_votes.get(account) {
+            Some(voted_on) => voted_on
+                .iter()
+                .filter_map(|proposal_id| {
+                    let record = self.votes.get(&(account.clone(), *proposal_id))?;
+                    Some((*proposal_id, record.vote))
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Turnout of a proposal measured against the current electorate (the total staked
+    // balance). Delegated stake counts as taking part through the delegate's vote.
+    pub fn turnout(
+        &self,
+        stake: &impl StakeProvider<T>,
+        proposal_id: u32,
+    ) -> Result<TurnoutStats, GovernanceError> {
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if let Some(stats) = &proposal.pruned_turnout {
+            return Ok(stats.clone());
+        }
+
+        let mut voters = 0u32;
+        let mut participating: u128 = 0;
+        for ((_, voted_on), record) in self.votes.iter() {
+            if *voted_on == proposal_id {
+                voters = voters.saturating_add(1);
+                participating = participating.saturating_add(record.balance.into());
+            }
+        }
+        for (delegator, voted_on) in self.delegated_votes.keys() {
+            if *voted_on == proposal_id {
+                let delegated = stake.bonded_of(delegator).into();
+                participating = participating.saturating_add(delegated);
+            }
+        }
+
+        let electorate = stake.total_bonded();
+        let participating = participating.min(electorate);
+        let participation_rate = match participating.checked_mul(1_000_000) {
+            Some(scaled) => scaled.checked_div(electorate),
+            None => participating.checked_div(electorate / 1_000_000),
+        }
+        .unwrap_or(0) as u32;
+
+        Ok(TurnoutStats {
+            voters,
+            total_weight: proposal.tally.turnout(),
+            yes_weight: proposal.tally.ayes,
+            no_weight: proposal.tally.nays,
+            participation_rate,
+        })
+    }
+
+    // Remove the individual votes of a decided proposal once the retention period has passed.
+    // The proposal keeps its tallies, and its turnout is recorded as it was at pruning time.
+    pub fn prune_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &StakingPallet<T>,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if proposal.is_open() {
+            return Err(GovernanceError::ProposalStillOpen);
+        }
+        if let Some(finalized_at) = proposal.finalized_at {
+            let retained_until = finalized_at
+                .checked_add(&T::VOTE_RETENTION_PERIOD)
+                .ok_or(GovernanceError::Overflow)?;
+            if system.block_number() <= retained_until {
+                return Err(GovernanceError::RetentionPeriodNotOver);
+            }
+        }
+
+        let stats = self.turnout(staking, proposal_id)?;
+        self.clear_votes(proposal_id);
+
+        if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+            proposal.pruned_turnout = Some(stats);
+        }
+
+        Self::deposit_event(system, GovernanceEvent::ProposalPruned { proposal_id });
+        Ok(())
+    }
+
+    // Remove every vote, ballot and delegated vote cast on a proposal
+    fn clear_votes(&mut self, proposal_id: u32) {
+        let account_votes = &mut self.account_votes;
+        self.votes.retain(|(voter, voted_on), _| {
+            if *voted_on != proposal_id {
+                return true;
+            }
+            if let Some(voted_on) = account_votes.get_mut(voter) {
+                voted_on.remove(&proposal_id);
+                if voted_on.is_empty() {
+                    account_votes.remove(voter);
+                }
+            }
+            false
+        });
+        self.ballots.retain(|(voter, voted_on), _| {
+            if *voted_on != proposal_id {
+                return true;
+            }
+            if let Some(voted_on) = account_votes.get_mut(voter) {
+                voted_on.remove(&proposal_id);
+                if voted_on.is_empty() {
+                    account_votes.remove(voter);
+                }
+            }
+            false
+        });
+        self.proposal_voters.remove(&proposal_id);
+        self.delegated_votes.retain(|(_, voted_on), _| *voted_on != proposal_id);
+        self.claimed_rewards.retain(|(_, voted_on)| *voted_on != proposal_id);
+    }
+
+    // Move a finalized proposal into the archive once its archive period has passed,
+    // keeping only its description hash, outcome and tally. Its votes are removed with it,
+    // and a deposit the creator never claimed is returned to them.
+    pub fn archive_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        proposal_id: u32,
+    ) -> Result<(), GovernanceError> {
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        let finalized_at = match proposal.finalized_at {
+            Some(finalized_at) if !proposal.is_open() => finalized_at,
+            _ => return Err(GovernanceError::ProposalStillOpen),
+        };
+        let enactable = proposal.call.is_some() || proposal.spend.is_some();
+        if proposal.status == ProposalStatus::Approved && enactable {
+            return Err(GovernanceError::EnactmentPending);
+        }
+        let archive_at = finalized_at
+            .checked_add(&T::ARCHIVE_PERIOD)
+            .ok_or(GovernanceError::Overflow)?;
+        if system.block_number() <= archive_at {
+            return Err(GovernanceError::ArchivePeriodNotOver);
+        }
+
+        if !proposal.deposit.is_zero() {
+            let creator = proposal.creator.clone();
+            self.claim_deposit(system, staking, creator, proposal_id)?;
+        }
+
+        self.clear_votes(proposal_id);
+        self.seconds.remove(&proposal_id);
+        let proposal = self
+            .proposals
+            .remove(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        for tag in &proposal.tags {
+            if let Some(tagged) = self.tagged_proposals.get_mut(tag) {
+                tagged.remove(&proposal_id);
+                if tagged.is_empty() {
+                    self.tagged_proposals.remove(tag);
+                }
+            }
+        }
+
+        let record = ArchivedProposal {
+            description_hash: proposal.description_hash,
+            track: proposal.track,
+            status: proposal.status,
+            tally: proposal.tally,
+            finalized_at,
+        };
+        self.archive.insert(proposal_id, record);
+
+        Self::deposit_event(system, GovernanceEvent::ProposalArchived { proposal_id });
+        Ok(())
+    }
+
+    // Get the archived record of a proposal
+    pub fn get_archived(&self, proposal_id: u32) -> Option<&ArchivedProposal<T>> {
+        self.archive.get(&proposal_id)
+    }
+
+    // Iterate over all archived proposals in ID order
+    pub fn archived_proposals(&self) -> impl Iterator<Item = (u32, &ArchivedProposal<T>)> + '_ {
+        self.archive.iter().map(|(proposal_id, record)| (*proposal_id, record))
+    }
+
+    // Look a proposal up in the live store, falling back to the archive
+    pub fn lookup_proposal(&self, proposal_id: u32) -> Option<ProposalRecord<'_, T>> {
+        match self.proposals.get(&proposal_id) {
+            Some(proposal) => Some(ProposalRecord::Live(proposal)),
+            None => self.archive.get(&proposal_id).map(ProposalRecord::Archived),
+        }
+    }
+
+    // Find the latest proposal created with a description or proposal hash, even once archived
+    pub fn proposal_by_hash(&self, hash: &T::Hash) -> Option<u32> {
+        self.proposals_by_hash.get(hash).copied()
+    }
+
+    // Number of proposals currently open for voting
+    pub fn active_proposal_count(&self) -> u32 {
+        self.proposals
+            .values()
+            .filter(|proposal| proposal.status == ProposalStatus::Active)
+            .count() as u32
+    }
+
+    // Move queued proposals into free active slots, oldest first
+    fn activate_pending_proposals(&mut self, system: &mut SystemPallet<T>) {
+        while self.active_proposal_count() < T::MAX_ACTIVE_PROPOSALS {
+            let proposal_id = match self.pending_queue.pop_front() {
+                Some(proposal_id) => proposal_id,
+                None => break,
+            };
+
+            if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+                let end_block = match system.block_number().checked_add(&proposal.voting_period) {
+                    Some(end_block) => end_block,
+                    None => continue,
+                };
+                proposal.status = ProposalStatus::Active;
+                proposal.end_block = end_block;
+
+                Self::deposit_event(
+                    system,
+                    GovernanceEvent::ProposalActivated { proposal_id, end_block },
+                );
+            }
+        }
+    }
+    
+    // Convert a governance event into the runtime event type and store it in the system pallet
+    fn deposit_event(system: &mut SystemPallet<T>, event: GovernanceEvent<T>) {
+        let event: <T as GovernanceConfig>::RuntimeEvent = event.into();
+        system.deposit_event(event.into());
+    }
+    
+    // Replace the description of an active proposal. Only the creator can do this, and only
+    // before any aye or nay vote (or vote commitment) has been cast.
+    pub fn amend_proposal(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        creator: T::AccountId,
+        proposal_id: u32,
+        new_description: String,
+    ) -> Result<(), GovernanceError> {
+        let has_commitments = self.commitments.keys().any(|(_, id)| *id == proposal_id);
+
+        let proposal = self
+            .proposals
+            .get(&proposal_id)
+            .ok_or(GovernanceError::ProposalNotFound)?;
+        if proposal.creator != creator {
+            return Err(GovernanceError::NotCreator);
+        }
+        if proposal.status != ProposalStatus::Active {
+            return Err(GovernanceError::ProposalNotActive);
+        }
+        let has_ballots = proposal.option_weights.iter().any(|weight| *weight > 0);
+        let has_votes = proposal.tally.ayes != 0 || proposal.tally.nays != 0;
+        if has_votes || has_commitments || has_ballots {
+            return Err(GovernanceError::ProposalHasVotes);
+        }
+
+        let description_len =
+            u32::try_from(new_description.len()).map_err(|_| GovernanceError::Overflow)?;
+        let old_hash = proposal.description_hash.clone();
+        let old_proposal_hash = proposal.proposal_hash.clone();
+        let proposal_hash =
+            Self::proposal_hash(&new_description, proposal.call.as_ref(), proposal.spend.as_ref());
+        if proposal_hash != old_proposal_hash {
+            self.ensure_not_duplicate(system, &proposal_hash)?;
+        }
+
+        let description_hash = self.preimages.note_preimage(new_description.into_bytes());
+        if let Some(proposal) = self.proposals.get_mut(&proposal_id) {
+            proposal.description_hash = description_hash.clone();
+            proposal.description_len = description_len;
+            proposal.proposal_hash = proposal_hash.clone();
+        }
+        self.open_proposal_hashes.remove(&old_proposal_hash);
+        self.proposals_by_hash.insert(description_hash.clone(), proposal_id);
+        self.proposals_by_hash.insert(proposal_hash.clone(), proposal_id);
+        self.open_proposal_hashes.insert(proposal_hash, proposal_id);
+
+        // Drop the old text unless another proposal still refers to it
+        if !self.proposals.values().any(|proposal| proposal.description_hash == old_hash) {
+            self.preimages.reap_preimage(&old_hash);
+        }
+
+        let event = GovernanceEvent::ProposalAmended { proposal_id, description_hash };
+        Self::deposit_event(system, event);
+        Ok(())
+    }
+
+    // Get full proposal details including description and creator.
+    // Fails once the description preimage has been reaped.
+    pub fn get_proposal_details(
+        &self,
+        proposal_id: u32,
+    ) -> Result<(String, T::AccountId), GovernanceError> {
+        match self.proposals.get(&proposal_id) {
+            Some(proposal) => {
+                let description = self
+                    .preimages
+                    .get_preimage(&proposal.description_hash)
+                    .ok_or(GovernanceError::PreimageMissing)?;
+                let description = String::from_utf8_lossy(description).into_owned();
+                Ok((description, proposal.creator.clone()))
+            },
+            None => Err(GovernanceError::ProposalNotFound),
+        }
+    }
+
+    // Drop the stored description of a proposal that has been decided, as long as
+    // no open proposal shares the same description
+    pub fn reap_description(&mut self, proposal_id: u32) -> Result<(), GovernanceError> {
+        let hash = match self.lookup_proposal(proposal_id) {
+            Some(record) => record.description_hash().clone(),
+            None => return Err(GovernanceError::ProposalNotFound),
+        };
+
+        let still_needed = self.proposals.values().any(|proposal| {
+            proposal.description_hash == hash
+                && proposal.is_open()
+        });
+        if still_needed {
+            return Err(GovernanceError::ProposalStillOpen);
+        }
+
+        if !self.preimages.reap_preimage(&hash) {
+            return Err(GovernanceError::PreimageMissing);
+        }
+        Ok(())
+    }
+}
+
+impl<T: GovernanceConfig> Default for GovernancePallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking::StakingCall;
+    use crate::{sign, Runtime, RuntimeCall, RuntimeEvent};
+
+    const AYE: AccountVote = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+    const NAY: AccountVote = AccountVote { vote: Vote::Nay, conviction: Conviction::Locked1x };
+    const ABSTAIN: AccountVote =
+        AccountVote { vote: Vote::Abstain, conviction: Conviction::Locked1x };
+
+    // Move on to the last block of a proposal's voting period, when it can be finalized
+    fn end_voting(
+        system: &mut SystemPallet<Runtime>,
+        governance: &GovernancePallet<Runtime>,
+        proposal_id: u32,
+    ) {
+        let end_block = governance.get_proposal(proposal_id).unwrap().end_block();
+        if system.block_number() < end_block {
+            system.set_block_number(end_block);
+        }
+    }
+
+    #[test]
+    fn test_governance_should_work() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        // Give every voter some stake to vote with
+        for voter in [alice, bob, charlie] {
+            staking.set_balance(voter, 1000);
+            staking.stake(voter, 100).unwrap();
+        }
+
+        // Create a proposal
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Increase validator rewards".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Cast votes
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap(); // Yes vote
+        governance.vote(&mut system, &mut staking, bob, proposal_id, AYE).unwrap(); // Yes vote
+        governance.vote(&mut system, &mut staking, charlie, proposal_id, NAY).unwrap(); // No vote
+
+        // Check proposal status before finalization
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 200);
+        assert_eq!(proposal.tally.nays, 100);
+
+        // Finalize proposal
+        end_voting(&mut system, &governance, proposal_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Approved));
+
+        // Check proposal is now approved
+        let finalized_proposal = governance.get_proposal(proposal_id).unwrap();
+        assert!(matches!(
+            finalized_proposal.status,
+            ProposalStatus::Approved
+        ));
+    }
+
+    #[test]
+    fn test_voting_deadline() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for voter in [alice, bob] {
+            staking.set_balance(voter, 1000);
+            staking.stake(voter, 100).unwrap();
+        }
+
+        // Zero-length voting periods are not allowed
+        let description = "Too short".to_string();
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            alice,
+            Track::Text,
+            description,
+            Some(0),
+        );
+        assert!(result.is_err());
+
+        // Create a proposal open for 10 blocks
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Short vote".to_string(),
+                Some(10),
+            )
+            .unwrap();
+        assert_eq!(governance.remaining_voting_time(&system, proposal_id), Ok(10));
+
+        // Proposals cannot be finalized before the end block
+        system.set_block_number(9);
+        let result = governance.finalize_proposal(&mut system, &mut staking, proposal_id);
+        assert_eq!(result, Err(GovernanceError::VotingPeriodOpen));
+        assert_eq!(governance.get_proposal(proposal_id).unwrap().status, ProposalStatus::Active);
+
+        // Votes are accepted up to and including the end block
+        system.set_block_number(10);
+        assert_eq!(governance.remaining_voting_time(&system, proposal_id), Ok(0));
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+
+        // Votes after the deadline are rejected
+        system.inc_block_number();
+        let result = governance.vote(&mut system, &mut staking, bob, proposal_id, AYE);
+        assert_eq!(result, Err(GovernanceError::VotingPeriodEnded));
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id);
+        assert_eq!(status, Ok(ProposalStatus::Approved));
+
+        // Proposals without an explicit period use the configured default
+        let default_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Default vote".to_string(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            governance.remaining_voting_time(&system, default_id),
+            Ok(Runtime::track_info(Track::Text).voting_period)
+        );
+    }
+
+    #[test]
+    fn test_stake_weighted_voting() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        // Alice is a whale, Bob and Charlie hold small stakes
+        staking.set_balance(alice, 10_000);
+        staking.stake(alice, 5_000).unwrap();
+        staking.set_balance(bob, 1_000);
+        staking.stake(bob, 1_000).unwrap();
+        staking.set_balance(charlie, 1_000);
+        staking.stake(charlie, 500).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                charlie,
+                Track::Text,
+                "Lower staking fees".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Accounts without stake cannot vote
+        let result = governance.vote(&mut system, &mut staking, 4u64, proposal_id, AYE);
+        assert_eq!(result, Err(GovernanceError::NoVotingPower));
+
+        governance.vote(&mut system, &mut staking, bob, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, charlie, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, alice, proposal_id, NAY).unwrap();
+
+        // Two voters in favour are outweighed by a single larger stake
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 1_500);
+        assert_eq!(proposal.tally.nays, 5_000);
+
+        end_voting(&mut system, &governance, proposal_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Rejected));
+    }
+
+    #[test]
+    fn test_quorum_requirement() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        // Alice alone cannot reach quorum
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 40).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 60).unwrap();
+        assert_eq!(Runtime::track_info(Track::Text).quorum, 100);
+
+        let lonely_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Only Alice cares".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, lonely_id, AYE).unwrap();
+        end_voting(&mut system, &governance, lonely_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, lonely_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Failed));
+
+        // Together they reach quorum exactly
+        let shared_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Both care".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, shared_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, shared_id, NAY).unwrap();
+        end_voting(&mut system, &governance, shared_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, shared_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Rejected));
+
+        // A failed proposal cannot be finalized again
+        assert!(governance.finalize_proposal(&mut system, &mut staking, lonely_id).is_err());
+    }
+
+    #[test]
+    fn test_proposal_deposit() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let deposit = <Runtime as GovernanceConfig>::PROPOSAL_DEPOSIT;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 1000).unwrap();
+
+        // Bob has no free balance left to cover the deposit
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            bob,
+            Track::Text,
+            "Broke".to_string(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::InsufficientDeposit));
+
+        // The deposit is reserved on creation and can be claimed back after approval
+        let approved_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Good idea".to_string(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(staking.get_free_balance(alice), 500 - deposit);
+        assert_eq!(staking.get_reserved_balance(alice), deposit);
+
+        governance.vote(&mut system, &mut staking, alice, approved_id, AYE).unwrap();
+        let result = governance.claim_deposit(&mut system, &mut staking, alice, approved_id);
+        assert_eq!(result, Err(GovernanceError::ProposalStillOpen));
+        end_voting(&mut system, &governance, approved_id);
+        governance.finalize_proposal(&mut system, &mut staking, approved_id).unwrap();
+        assert_eq!(staking.get_reserved_balance(alice), deposit);
+        governance.claim_deposit(&mut system, &mut staking, alice, approved_id).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 500);
+        assert_eq!(staking.get_reserved_balance(alice), 0);
+        let result = governance.claim_deposit(&mut system, &mut staking, alice, approved_id);
+        assert_eq!(result, Err(GovernanceError::NoDepositToClaim));
+
+        // The deposit is slashed on rejection of a proposal flagged as malicious
+        governance.set_technical_committee(&mut system, Origin::Root, vec![charlie]).unwrap();
+        let rejected_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Bad idea".to_string(),
+                None,
+            )
+            .unwrap();
+        let result = governance.flag_malicious(&mut system, Origin::Signed(alice), rejected_id);
+        assert_eq!(result, Err(GovernanceError::NotTechnicalCommittee));
+        governance.flag_malicious(&mut system, Origin::Signed(charlie), rejected_id).unwrap();
+        governance.vote(&mut system, &mut staking, bob, rejected_id, NAY).unwrap();
+        end_voting(&mut system, &governance, rejected_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, rejected_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Rejected));
+        assert_eq!(staking.get_free_balance(alice), 500 - deposit);
+        assert_eq!(staking.get_reserved_balance(alice), 0);
+        let result = governance.claim_deposit(&mut system, &mut staking, alice, rejected_id);
+        assert_eq!(result, Err(GovernanceError::NoDepositToClaim));
+    }
+
+    #[test]
+    fn test_deposit_refunds() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let dave = 4u64;
+        let deposit = <Runtime as GovernanceConfig>::PROPOSAL_DEPOSIT;
+        let penalty = <Runtime as GovernanceConfig>::QUORUM_FAILURE_PENALTY;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 1000).unwrap();
+        staking.set_balance(dave, 100);
+        staking.stake(dave, 50).unwrap();
+
+        let mut ids = Vec::new();
+        let cases = [("Honest but unpopular", bob, NAY), ("Ignored", dave, AYE)];
+        for (description, voter, vote) in cases {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    description.to_string(),
+                    None,
+                )
+                .unwrap();
+            governance.vote(&mut system, &mut staking, voter, id, vote).unwrap();
+            ids.push(id);
+        }
+
+        // A plain rejection is refunded in full
+        end_voting(&mut system, &governance, ids[0]);
+        let status = governance.finalize_proposal(&mut system, &mut staking, ids[0]).unwrap();
+        assert_eq!(status, ProposalStatus::Rejected);
+        governance.claim_deposit(&mut system, &mut staking, alice, ids[0]).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 500 - deposit);
+
+        // Missing quorum costs part of the deposit
+        end_voting(&mut system, &governance, ids[1]);
+        let status = governance.finalize_proposal(&mut system, &mut staking, ids[1]).unwrap();
+        assert_eq!(status, ProposalStatus::Failed);
+        assert_eq!(governance.get_proposal(ids[1]).unwrap().deposit(), deposit - penalty);
+        let result = governance.claim_deposit(&mut system, &mut staking, bob, ids[1]);
+        assert_eq!(result, Err(GovernanceError::NotCreator));
+        governance.claim_deposit(&mut system, &mut staking, alice, ids[1]).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 500 - penalty);
+        assert_eq!(staking.get_reserved_balance(alice), 0);
+    }
+
+    #[test]
+    fn test_vote_delegation() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 100), (bob, 200), (charlie, 300), (dave, 400)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        // Invalid delegations
+        assert!(governance.delegate(&mut system, alice, Track::Text, alice).is_err());
+        governance.delegate(&mut system, bob, Track::Text, alice).unwrap();
+        governance.delegate(&mut system, charlie, Track::Text, alice).unwrap();
+        let text = Track::Text;
+        assert!(governance.delegate(&mut system, bob, text, dave).is_err()); // Already delegating
+        assert!(governance.delegate(&mut system, dave, text, bob).is_err()); // Bob is delegating
+        // Alice has delegators
+        assert!(governance.delegate(&mut system, alice, text, dave).is_err());
+
+        let first_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                dave,
+                Track::Text,
+                "First".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Charlie votes directly before Alice, so only Bob's stake is delegated to Alice
+        governance.undelegate(&mut system, charlie, Track::Text).unwrap();
+        system.set_block_number(5);
+        governance.vote(&mut system, &mut staking, charlie, first_id, NAY).unwrap();
+        governance.delegate(&mut system, charlie, Track::Text, alice).unwrap();
+        governance.vote(&mut system, &mut staking, alice, first_id, AYE).unwrap();
+
+        let proposal = governance.get_proposal(first_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 300);
+        assert_eq!(proposal.tally.nays, 300);
+
+        // Delegators cannot vote while delegating
+        let second_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                dave,
+                Track::Text,
+                "Second".to_string(),
+                None,
+            )
+            .unwrap();
+        let result = governance.vote(&mut system, &mut staking, bob, second_id, NAY);
+        assert_eq!(result, Err(GovernanceError::VoterIsDelegating));
+
+        // Once counted through a delegate, undelegating does not allow a second vote
+        governance.vote(&mut system, &mut staking, alice, second_id, AYE).unwrap();
+        governance.undelegate(&mut system, bob, Track::Text).unwrap();
+        system.set_block_number(10);
+        let result = governance.vote(&mut system, &mut staking, bob, second_id, NAY);
+        assert_eq!(result, Err(GovernanceError::CountedByDelegate));
+
+        let proposal = governance.get_proposal(second_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 600);
+        assert_eq!(proposal.tally.nays, 0);
+
+        assert!(governance.undelegate(&mut system, bob, Track::Text).is_err());
+    }
+
+    #[test]
+    fn test_conviction_voting() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let locking_period = <Runtime as GovernanceConfig>::VOTE_LOCKING_PERIOD;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Conviction".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Alice's 6x conviction outweighs Bob's larger stake at 1x
+        let strong_aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked6x };
+        governance.vote(&mut system, &mut staking, alice, proposal_id, strong_aye).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 600);
+        assert_eq!(proposal.tally.nays, 500);
+
+        end_voting(&mut system, &governance, proposal_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Approved));
+        let finalized_at = system.block_number();
+
+        // Alice won and is locked for 32 periods, while Bob's lock ends with the proposal
+        let lock = governance.get_lock(&alice, proposal_id).unwrap();
+        assert_eq!(lock.until, finalized_at + 32 * locking_period);
+        assert_eq!(governance.get_lock(&bob, proposal_id).unwrap().until, 0);
+        assert_eq!(staking.unbond(alice, 1, 0), Err(StakingError::LockedBalance));
+        governance.unlock(&mut system, &mut staking, bob).unwrap();
+        staking.unbond(bob, 500, 0).unwrap();
+        assert!(governance.unlock(&mut system, &mut staking, bob).is_err());
+
+        // Unlocking only works once the lock has expired
+        system.set_block_number(finalized_at + locking_period);
+        let result = governance.unlock(&mut system, &mut staking, alice);
+        assert_eq!(result, Err(GovernanceError::LockNotExpired));
+
+        system.set_block_number(finalized_at + 32 * locking_period);
+        governance.unlock(&mut system, &mut staking, alice).unwrap();
+        staking.unbond(alice, 100, 0).unwrap();
+    }
+
+    #[test]
+    fn test_cancel_proposal() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 100).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Oops, typo".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Only the creator may cancel
+        let result = governance.cancel_proposal(&mut system, &mut staking, bob, proposal_id);
+        assert_eq!(result, Err(GovernanceError::NotCreator));
+
+        // Cancelling refunds the deposit and closes the proposal
+        governance.cancel_proposal(&mut system, &mut staking, alice, proposal_id).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 1000);
+        assert!(matches!(
+            governance.get_proposal(proposal_id).unwrap().status,
+            ProposalStatus::Cancelled
+        ));
+        assert!(governance.vote(&mut system, &mut staking, bob, proposal_id, AYE).is_err());
+        assert!(governance.cancel_proposal(&mut system, &mut staking, alice, proposal_id).is_err());
+
+        // Proposals with votes can no longer be cancelled
+        let voted_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Popular".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, bob, voted_id, AYE).unwrap();
+        let result = governance.cancel_proposal(&mut system, &mut staking, alice, voted_id);
+        assert_eq!(result, Err(GovernanceError::ProposalHasVotes));
+    }
+
+    #[test]
+    fn test_change_and_remove_vote() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 100), (bob, 200), (charlie, 300)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+        governance.delegate(&mut system, charlie, Track::Text, bob).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Flip-flop".to_string(),
+                Some(10),
+            )
+            .unwrap();
+
+        // Changing or removing requires an existing vote
+        let result = governance.change_vote(&mut system, &mut staking, alice, proposal_id, NAY);
+        assert_eq!(result, Err(GovernanceError::NotVoted));
+        assert!(governance.remove_vote(&mut system, &mut staking, alice, proposal_id).is_err());
+
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, AYE).unwrap();
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 600);
+        assert_eq!(proposal.tally.nays, 0);
+
+        // Bob switches sides with more conviction, taking Charlie's delegated stake along
+        let strong_nay = AccountVote { vote: Vote::Nay, conviction: Conviction::Locked2x };
+        governance.change_vote(&mut system, &mut staking, bob, proposal_id, strong_nay).unwrap();
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 100);
+        assert_eq!(proposal.tally.nays, 700);
+
+        // Once Charlie has undelegated, Bob's next change stops counting Charlie's stake
+        governance.undelegate(&mut system, charlie, Track::Text).unwrap();
+        system.set_block_number(5);
+        governance.change_vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.nays, 200);
+        governance.vote(&mut system, &mut staking, charlie, proposal_id, AYE).unwrap();
+
+        // Removing Bob's vote leaves Charlie's own vote in place
+        governance.remove_vote(&mut system, &mut staking, bob, proposal_id).unwrap();
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 400);
+        assert_eq!(proposal.tally.nays, 0);
+
+        // Votes are frozen once the deadline has passed
+        system.set_block_number(11);
+        let result = governance.change_vote(&mut system, &mut staking, alice, proposal_id, NAY);
+        assert!(result.is_err());
+        assert!(governance.remove_vote(&mut system, &mut staking, alice, proposal_id).is_err());
+    }
+
+    #[test]
+    fn test_abstain_votes() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 30), (bob, 20), (charlie, 80)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Abstain".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Conviction is ignored for abstentions
+        let strong_abstain = AccountVote { vote: Vote::Abstain, conviction: Conviction::Locked6x };
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        governance.vote(&mut system, &mut staking, charlie, proposal_id, strong_abstain).unwrap();
+
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.tally.ayes, 30);
+        assert_eq!(proposal.tally.nays, 20);
+        assert_eq!(proposal.tally.abstentions, 80);
+
+        // Abstentions bring turnout over the quorum without affecting the outcome
+        end_voting(&mut system, &governance, proposal_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert!(matches!(status, ProposalStatus::Approved));
+
+        // Abstaining voters are not locked
+        assert!(governance.locks.contains_key(&alice));
+        assert!(!governance.locks.contains_key(&charlie));
+
+        // Moving an abstention to a side updates both buckets
+        let second_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Second".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, charlie, second_id, ABSTAIN).unwrap();
+        governance.change_vote(&mut system, &mut staking, charlie, second_id, NAY).unwrap();
+        let proposal = governance.get_proposal(second_id).unwrap();
+        assert_eq!(proposal.tally.abstentions, 0);
+        assert_eq!(proposal.tally.nays, 80);
+    }
+
+    #[test]
+    fn test_governance_events() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 200).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Observable".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+
+        let cancelled_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Withdrawn".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.cancel_proposal(&mut system, &mut staking, alice, cancelled_id).unwrap();
+
+        // Failed calls do not emit anything
+        assert!(governance.vote(&mut system, &mut staking, bob, proposal_id, AYE).is_err());
+
+        let events = system.events();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(
+            events[0],
+            RuntimeEvent::Governance(GovernanceEvent::ProposalCreated {
+                proposal_id: 0,
+                creator: 1,
+                end_block: 100,
+            })
+        ));
+        assert!(matches!(
+            events[1],
+            RuntimeEvent::Governance(GovernanceEvent::Voted {
+                voter: 1,
+                proposal_id: 0,
+                weight: 200,
+                ..
+            })
+        ));
+        assert!(matches!(
+            events[2],
+            RuntimeEvent::Governance(GovernanceEvent::ProposalFinalized {
+                proposal_id: 0,
+                status: ProposalStatus::Approved,
+            })
+        ));
+        assert!(matches!(
+            events[3],
+            RuntimeEvent::Governance(GovernanceEvent::ProposalCreated { proposal_id: 1, .. })
+        ));
+        assert!(matches!(
+            events[4],
+            RuntimeEvent::Governance(GovernanceEvent::ProposalCancelled { proposal_id: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_governance_errors() {
+        let alice = 1u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        // Errors can be matched on by callers
+        let result = governance.vote(&mut system, &mut staking, alice, 42, AYE);
+        assert_eq!(result, Err(GovernanceError::ProposalNotFound));
+        let result = governance.finalize_proposal(&mut system, &mut staking, 42);
+        assert!(matches!(result, Err(GovernanceError::ProposalNotFound)));
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Errors".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        let result = governance.vote(&mut system, &mut staking, alice, proposal_id, NAY);
+        assert_eq!(result, Err(GovernanceError::AlreadyVoted));
+
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        let result = governance.finalize_proposal(&mut system, &mut staking, proposal_id);
+        assert!(matches!(result, Err(GovernanceError::ProposalNotActive)));
+
+        // ... and still render a readable message
+        assert_eq!(
+            GovernanceError::AlreadyVoted.to_string(),
+            "Voter has already cast a vote for this proposal"
+        );
+        assert_eq!(
+            GovernanceError::Staking(StakingError::InsufficientReservedBalance).to_string(),
+            "Staking error: Not enough reserved balance"
+        );
+    }
+
+    #[test]
+    fn test_pending_proposal_queue() {
+        let alice = 1u64;
+        let max_active = <Runtime as GovernanceConfig>::MAX_ACTIVE_PROPOSALS;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 10_000);
+        staking.stake(alice, 100).unwrap();
+
+        // Fill every active slot
+        for i in 0..max_active {
+            governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    format!("Proposal {}", i),
+                    Some(20),
+                )
+                .unwrap();
+        }
+        assert_eq!(governance.active_proposal_count(), max_active);
+
+        // Further proposals are queued in order and cannot be voted on yet
+        let first_queued = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Queued 1".to_string(),
+                Some(5),
+            )
+            .unwrap();
+        let second_queued = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Queued 2".to_string(),
+                None,
+            )
+            .unwrap();
+        let status = governance.get_proposal(first_queued).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Pending);
+        assert_eq!(governance.active_proposal_count(), max_active);
+        let result = governance.vote(&mut system, &mut staking, alice, first_queued, AYE);
+        assert_eq!(result, Err(GovernanceError::ProposalNotActive));
+        assert_eq!(governance.remaining_voting_time(&system, first_queued), Ok(5));
+
+        // Finalizing an active proposal activates the oldest queued one, starting its period now
+        system.set_block_number(20);
+        governance.vote(&mut system, &mut staking, alice, 0, AYE).unwrap();
+        governance.finalize_proposal(&mut system, &mut staking, 0).unwrap();
+        let status = governance.get_proposal(first_queued).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Active);
+        let status = governance.get_proposal(second_queued).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Pending);
+        assert_eq!(governance.remaining_voting_time(&system, first_queued), Ok(5));
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::ProposalActivated {
+                proposal_id: 10,
+                end_block: 25,
+            }))
+        ));
+        governance.vote(&mut system, &mut staking, alice, first_queued, AYE).unwrap();
+
+        // Cancelling also frees a slot
+        governance.cancel_proposal(&mut system, &mut staking, alice, 1).unwrap();
+        let status = governance.get_proposal(second_queued).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Active);
+        assert!(governance.pending_queue.is_empty());
+    }
+
+    #[test]
+    fn test_proposal_tracks() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 700).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+        staking.set_balance(charlie, 100);
+
+        let mut ids = Vec::new();
+        for track in [Track::Text, Track::Treasury, Track::RuntimeParameter] {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    track,
+                    format!("{:?}", track),
+                    None,
+                )
+                .unwrap();
+            assert_eq!(governance.get_proposal(id).unwrap().track(), track);
+            if track == Track::RuntimeParameter {
+                governance.second(&mut system, &mut staking, bob, id).unwrap();
+                governance.second(&mut system, &mut staking, charlie, id).unwrap();
+            }
+            governance.vote(&mut system, &mut staking, alice, id, AYE).unwrap();
+            governance.vote(&mut system, &mut staking, bob, id, NAY).unwrap();
+            ids.push(id);
+        }
+
+        // Each track uses its own default voting period
+        assert_eq!(governance.remaining_voting_time(&system, ids[1]), Ok(200));
+        assert_eq!(governance.remaining_voting_time(&system, ids[2]), Ok(300));
+
+        // 700 aye against 500 nay is a simple majority, but not the 60% treasury needs,
+        // and a turnout of 1200 misses the runtime parameter quorum
+        end_voting(&mut system, &governance, ids[0]);
+        let status = governance.finalize_proposal(&mut system, &mut staking, ids[0]).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        end_voting(&mut system, &governance, ids[1]);
+        let status = governance.finalize_proposal(&mut system, &mut staking, ids[1]).unwrap();
+        assert_eq!(status, ProposalStatus::Rejected);
+        end_voting(&mut system, &governance, ids[2]);
+        let status = governance.finalize_proposal(&mut system, &mut staking, ids[2]).unwrap();
+        assert_eq!(status, ProposalStatus::Failed);
+    }
+
+    #[test]
+    fn test_commit_reveal_voting() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 100), (bob, 50), (charlie, 30)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Secret".to_string(),
+                Some(10),
+            )
+            .unwrap();
+
+        // Commitments hide the vote until the voting period is over
+        let alice_commitment = GovernancePallet::<Runtime>::vote_commitment(AYE, b"alice salt");
+        let bob_commitment = GovernancePallet::<Runtime>::vote_commitment(NAY, b"bob salt");
+        let charlie_commitment = GovernancePallet::<Runtime>::vote_commitment(NAY, b"charlie");
+        governance.commit_vote(&mut system, alice, proposal_id, alice_commitment).unwrap();
+        governance.commit_vote(&mut system, bob, proposal_id, bob_commitment).unwrap();
+        governance.commit_vote(&mut system, charlie, proposal_id, charlie_commitment).unwrap();
+        let result = governance.vote(&mut system, &mut staking, alice, proposal_id, AYE);
+        assert_eq!(result, Err(GovernanceError::AlreadyVoted));
+
+        system.set_block_number(5);
+        let salt = b"alice salt";
+        let result =
+            governance.reveal_vote(&mut system, &mut staking, alice, proposal_id, AYE, salt);
+        assert_eq!(result, Err(GovernanceError::NotInRevealPeriod));
+
+        // Reveals must match the commitment
+        system.set_block_number(11);
+        let result =
+            governance.reveal_vote(&mut system, &mut staking, alice, proposal_id, NAY, salt);
+        assert_eq!(result, Err(GovernanceError::InvalidReveal));
+        let result =
+            governance.reveal_vote(&mut system, &mut staking, dave, proposal_id, AYE, b"dave");
+        assert_eq!(result, Err(GovernanceError::NoCommitment));
+        governance.reveal_vote(&mut system, &mut staking, alice, proposal_id, AYE, salt).unwrap();
+        governance
+            .reveal_vote(&mut system, &mut staking, bob, proposal_id, NAY, b"bob salt")
+            .unwrap();
+
+        // Charlie never reveals, so finalization waits for the reveal period to end
+        end_voting(&mut system, &governance, proposal_id);
+        let result = governance.finalize_proposal(&mut system, &mut staking, proposal_id);
+        assert_eq!(result, Err(GovernanceError::RevealPeriodOpen));
+
+        system.set_block_number(21);
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        assert!(!governance.votes.contains_key(&(charlie, proposal_id)));
+        assert!(governance.commitments.is_empty());
+    }
+
+    #[test]
+    fn test_votes_by_account() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+
+        for i in 0..3 {
+            governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    format!("{}", i),
+                    None,
+                )
+                .unwrap();
+        }
+        governance.vote(&mut system, &mut staking, alice, 2, NAY).unwrap();
+        governance.vote(&mut system, &mut staking, alice, 0, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, alice, 1, ABSTAIN).unwrap();
+
+        assert_eq!(governance.votes_by_account(&alice), vec![(0, AYE), (1, ABSTAIN), (2, NAY)]);
+        assert!(governance.votes_by_account(&bob).is_empty());
+
+        // Changed and removed votes are reflected in the history
+        governance.change_vote(&mut system, &mut staking, alice, 0, NAY).unwrap();
+        governance.remove_vote(&mut system, &mut staking, alice, 1).unwrap();
+        assert_eq!(governance.votes_by_account(&alice), vec![(0, NAY), (2, NAY)]);
+    }
+
+    #[test]
+    fn test_tally_strategies() {
+        assert_eq!(LinearTally::weight(0), 0);
+        assert_eq!(LinearTally::weight(900), 900);
+
+        // Quadratic weight rounds the square root down
+        assert_eq!(QuadraticTally::weight(0), 0);
+        assert_eq!(QuadraticTally::weight(900), 30);
+        assert_eq!(QuadraticTally::weight(99), 9);
+        assert_eq!(QuadraticTally::weight(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_proposal_batches() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 300).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 400).unwrap();
+        staking.set_balance(charlie, 15);
+
+        let batch = |descriptions: &[&str]| -> Vec<ProposalData<Runtime>> {
+            descriptions
+                .iter()
+                .map(|description| ProposalData {
+                    track: Track::Text,
+                    description: description.to_string(),
+                    call: None,
+                })
+                .collect()
+        };
+
+        // Nothing is created unless every deposit can be reserved
+        let result = governance.create_batch(&mut system, &mut staking, alice, batch(&[]), None);
+        assert_eq!(result, Err(GovernanceError::EmptyBatch));
+        let proposals = batch(&["A", "B"]);
+        let result = governance.create_batch(&mut system, &mut staking, charlie, proposals, None);
+        assert_eq!(result, Err(GovernanceError::InsufficientDeposit));
+        assert_eq!(governance.active_proposal_count(), 0);
+
+        let batch_id = governance
+            .create_batch(&mut system, &mut staking, alice, batch(&["Popular", "Contested"]), None)
+            .unwrap();
+        assert_eq!(governance.batches.get(&batch_id), Some(&vec![0, 1]));
+        assert_eq!(governance.get_proposal(1).unwrap().batch(), Some(batch_id));
+        assert_eq!(staking.get_reserved_balance(alice), 20);
+        let result = governance.cancel_proposal(&mut system, &mut staking, alice, 0);
+        assert_eq!(result, Err(GovernanceError::ProposalInBatch));
+
+        // Proposal 0 passes on its own, but proposal 1 fails and takes the batch down with it
+        governance.vote(&mut system, &mut staking, alice, 0, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, alice, 1, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, 1, NAY).unwrap();
+        end_voting(&mut system, &governance, 0);
+        let status = governance.finalize_proposal(&mut system, &mut staking, 0).unwrap();
+        assert_eq!(status, ProposalStatus::Rejected);
+        assert_eq!(governance.get_proposal(1).unwrap().status(), &ProposalStatus::Rejected);
+        let result = governance.finalize_proposal(&mut system, &mut staking, 1);
+        assert_eq!(result, Err(GovernanceError::ProposalNotActive));
+
+        // Both deposits are refunded since neither proposal was flagged as malicious
+        for proposal_id in [0, 1] {
+            governance.claim_deposit(&mut system, &mut staking, alice, proposal_id).unwrap();
+        }
+        assert_eq!(staking.get_reserved_balance(alice), 0);
+        assert_eq!(staking.get_free_balance(alice), 700);
+
+        // A batch where every proposal passes is approved as a whole
+        governance
+            .create_batch(&mut system, &mut staking, alice, batch(&["First", "Second"]), None)
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, 2, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, alice, 3, AYE).unwrap();
+        end_voting(&mut system, &governance, 3);
+        let status = governance.finalize_proposal(&mut system, &mut staking, 3).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        assert_eq!(governance.get_proposal(2).unwrap().status(), &ProposalStatus::Approved);
+    }
+
+    #[test]
+    fn test_fast_track() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let guardian = 50u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 60).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 10).unwrap();
+        governance.set_technical_committee(&mut system, Origin::Root, vec![guardian]).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Hotfix".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Only the technical committee can fast-track, and only to an earlier deadline
+        let result = governance.fast_track(&mut system, Origin::Signed(alice), proposal_id, 10);
+        assert_eq!(result, Err(GovernanceError::NotTechnicalCommittee));
+        let result = governance.fast_track(&mut system, Origin::Signed(guardian), proposal_id, 200);
+        assert_eq!(result, Err(GovernanceError::InvalidDeadline));
+        system.set_block_number(5);
+        let result = governance.fast_track(&mut system, Origin::Signed(guardian), proposal_id, 3);
+        assert_eq!(result, Err(GovernanceError::InvalidDeadline));
+
+        governance.fast_track(&mut system, Origin::Signed(guardian), proposal_id, 10).unwrap();
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::ProposalFastTracked {
+                proposal_id: 0,
+                by: Some(50),
+                end_block: 10,
+                quorum: 50,
+            }))
+        ));
+        assert_eq!(governance.remaining_voting_time(&system, proposal_id), Ok(5));
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+
+        // Voting closes at the new deadline, and 60 stake is enough for the lowered quorum
+        system.set_block_number(11);
+        let result = governance.vote(&mut system, &mut staking, bob, proposal_id, NAY);
+        assert_eq!(result, Err(GovernanceError::VotingPeriodEnded));
+        end_voting(&mut system, &governance, proposal_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+    }
+
+    #[test]
+    fn test_treasury_spend() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let treasury = <Runtime as GovernanceConfig>::TREASURY_ACCOUNT;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 2000);
+        staking.stake(alice, 1000).unwrap();
+        staking.set_balance(treasury, 500);
+
+        let description = "Pay Bob".to_string();
+        let paid_id = governance
+            .create_spend_proposal(&mut system, &mut staking, alice, bob, 300, description)
+            .unwrap();
+        let proposal = governance.get_proposal(paid_id).unwrap();
+        assert_eq!(proposal.track(), Track::Treasury);
+        assert_eq!(proposal.spend().map(|spend| spend.amount), Some(300));
+
+        // Approval schedules the payment, which is only made once the enactment delay is over
+        governance.vote(&mut system, &mut staking, alice, paid_id, AYE).unwrap();
+        end_voting(&mut system, &governance, paid_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, paid_id).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        assert_eq!(staking.get_free_balance(bob), 0);
+        let at = governance.get_proposal(paid_id).unwrap().enactment_block().unwrap();
+        assert_eq!(at, system.block_number() + <Runtime as GovernanceConfig>::ENACTMENT_DELAY);
+        assert!(governance.on_initialize(&mut system, &mut staking, at - 1).is_empty());
+        assert_eq!(staking.get_free_balance(bob), 0);
+
+        assert!(governance.on_initialize(&mut system, &mut staking, at).is_empty());
+        assert_eq!(governance.get_proposal(paid_id).unwrap().status, ProposalStatus::Enacted);
+        assert_eq!(staking.get_free_balance(bob), 300);
+        assert_eq!(staking.get_free_balance(treasury), 200);
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::TreasurySpent {
+                proposal_id: 0,
+                beneficiary: 2,
+                amount: 300,
+            }))
+        ));
+
+        // A spend vetoed during the enactment delay is never paid
+        let description = "Pay Bob again".to_string();
+        let vetoed_id = governance
+            .create_spend_proposal(&mut system, &mut staking, alice, bob, 100, description)
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, vetoed_id, AYE).unwrap();
+        end_voting(&mut system, &governance, vetoed_id);
+        governance.finalize_proposal(&mut system, &mut staking, vetoed_id).unwrap();
+        governance.set_technical_committee(&mut system, Origin::Root, vec![charlie]).unwrap();
+        governance.veto_proposal(&mut system, Origin::Signed(charlie), vetoed_id).unwrap();
+        let at = governance.get_proposal(vetoed_id).unwrap().enactment_block().unwrap();
+        governance.on_initialize(&mut system, &mut staking, at);
+        assert_eq!(governance.get_proposal(vetoed_id).unwrap().status, ProposalStatus::Vetoed);
+        assert_eq!(staking.get_free_balance(bob), 300);
+        assert_eq!(staking.get_free_balance(treasury), 200);
+
+        // A spend the treasury cannot cover stays approved and is retried every block
+        let description = "Overspend".to_string();
+        let unpaid_id = governance
+            .create_spend_proposal(&mut system, &mut staking, alice, bob, 500, description)
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, unpaid_id, AYE).unwrap();
+        end_voting(&mut system, &governance, unpaid_id);
+        governance.finalize_proposal(&mut system, &mut staking, unpaid_id).unwrap();
+        let at = governance.get_proposal(unpaid_id).unwrap().enactment_block().unwrap();
+        governance.on_initialize(&mut system, &mut staking, at);
+        let proposal = governance.get_proposal(unpaid_id).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Approved);
+        assert_eq!(proposal.enactment_block(), Some(at + 1));
+        assert_eq!(staking.get_free_balance(treasury), 200);
+
+        // Nor can it be archived before it is paid
+        let archive_period = <Runtime as GovernanceConfig>::ARCHIVE_PERIOD;
+        while system.block_number() <= at + archive_period {
+            system.inc_block_number();
+        }
+        let result = governance.archive_proposal(&mut system, &mut staking, unpaid_id);
+        assert_eq!(result, Err(GovernanceError::EnactmentPending));
+
+        // Once the treasury is refilled the next retry pays it
+        staking.set_balance(treasury, 500);
+        governance.on_initialize(&mut system, &mut staking, at + 1);
+        assert_eq!(governance.get_proposal(unpaid_id).unwrap().status, ProposalStatus::Enacted);
+        assert_eq!(staking.get_free_balance(bob), 800);
+        assert_eq!(staking.get_free_balance(treasury), 0);
+    }
+
+    #[test]
+    fn test_description_preimages() {
+        let alice = 1u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+
+        let description = "A rather long description of the proposal".to_string();
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                description.clone(),
+                None,
+            )
+            .unwrap();
+
+        // Only the hash and length are kept in the proposal
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.description_hash(), &Runtime::hash(description.as_bytes()));
+        assert_eq!(proposal.description_len(), description.len() as u32);
+        assert_eq!(governance.get_proposal_details(proposal_id), Ok((description.clone(), alice)));
+
+        // The text can only be reaped once the proposal is decided
+        let result = governance.reap_description(proposal_id);
+        assert_eq!(result, Err(GovernanceError::ProposalStillOpen));
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        governance.reap_description(proposal_id).unwrap();
+        let result = governance.get_proposal_details(proposal_id);
+        assert_eq!(result, Err(GovernanceError::PreimageMissing));
+
+        // Noting the text again restores the details
+        governance.preimages.note_preimage(description.clone().into_bytes());
+        assert_eq!(governance.get_proposal_details(proposal_id), Ok((description, alice)));
+    }
+
+    #[test]
+    fn test_undelegation_cooldown() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 100), (bob, 50)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Cooldown".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.delegate(&mut system, bob, Track::Text, alice).unwrap();
+
+        // The delegation keeps counting until the cooldown has passed
+        system.set_block_number(3);
+        governance.undelegate(&mut system, bob, Track::Text).unwrap();
+        assert_eq!(governance.undelegation_effective_at(&bob, Track::Text), Some(8));
+        let result = governance.undelegate(&mut system, bob, Track::Text);
+        assert_eq!(result, Err(GovernanceError::UndelegationPending));
+        let result = governance.delegate(&mut system, bob, Track::Text, charlie);
+        assert_eq!(result, Err(GovernanceError::AlreadyDelegating));
+        let result = governance.vote(&mut system, &mut staking, bob, proposal_id, NAY);
+        assert_eq!(result, Err(GovernanceError::VoterIsDelegating));
+
+        system.set_block_number(8);
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        assert_eq!(governance.undelegation_effective_at(&bob, Track::Text), None);
+        assert!(!governance.delegations.contains_key(&(bob, Track::Text)));
+
+        // The end-of-block hook also applies matured undelegations
+        governance.delegate(&mut system, bob, Track::Text, charlie).unwrap();
+        governance.undelegate(&mut system, bob, Track::Text).unwrap();
+        governance.on_finalize(&mut system, &mut staking, 8);
+        assert!(governance.delegations.contains_key(&(bob, Track::Text)));
+        system.set_block_number(13);
+        governance.on_finalize(&mut system, &mut staking, 13);
+        assert!(!governance.delegations.contains_key(&(bob, Track::Text)));
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::Undelegated {
+                from: 2,
+                track: Track::Text,
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_approval_curves() {
+        // A fixed threshold ignores turnout
+        let fixed = ApprovalCurve::Fixed(50);
+        assert!(fixed.approves(51, 49, 100, 1000));
+        assert!(!fixed.approves(50, 50, 100, 1000));
+        assert!(!fixed.approves(0, 0, 0, 1000));
+
+        // At full turnout both biased curves reduce to a simple majority
+        for curve in [ApprovalCurve::PositiveTurnoutBias, ApprovalCurve::NegativeTurnoutBias] {
+            assert!(curve.approves(51, 49, 100, 100));
+            assert!(!curve.approves(50, 50, 100, 100));
+            assert!(!curve.approves(0, 0, 0, 100));
+        }
+
+        // With a quarter turnout, positive bias needs more than twice as many ayes as nays
+        let positive = ApprovalCurve::PositiveTurnoutBias;
+        assert!(!positive.approves(16, 8, 25, 100));
+        assert!(positive.approves(17, 8, 25, 100));
+
+        // ... and negative bias only rejects with at least twice as many nays as ayes
+        let negative = ApprovalCurve::NegativeTurnoutBias;
+        assert!(!negative.approves(8, 16, 25, 100));
+        assert!(negative.approves(8, 15, 25, 100));
+
+        // Turnout inflated by conviction is capped at the electorate
+        assert_eq!(positive.approves(60, 40, 400, 100), positive.approves(60, 40, 100, 100));
+    }
+
+    #[test]
+    fn test_amend_proposal() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for who in [alice, bob] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Tpyo".to_string(),
+                None,
+            )
+            .unwrap();
+        let old_hash = *governance.get_proposal(proposal_id).unwrap().description_hash();
+
+        // Only the creator can amend
+        let result = governance.amend_proposal(&mut system, bob, proposal_id, "Typo".to_string());
+        assert_eq!(result, Err(GovernanceError::NotCreator));
+
+        // Abstentions do not prevent amending
+        governance.vote(&mut system, &mut staking, bob, proposal_id, ABSTAIN).unwrap();
+        governance.amend_proposal(&mut system, alice, proposal_id, "Typo".to_string()).unwrap();
+        let details = governance.get_proposal_details(proposal_id).unwrap();
+        assert_eq!(details, ("Typo".to_string(), alice));
+        assert_eq!(governance.preimages.get_preimage(&old_hash), None);
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::ProposalAmended { proposal_id: 0, .. }))
+        ));
+
+        // Once an aye or nay is cast the description is final
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        let description = "Typo!".to_string();
+        let result = governance.amend_proposal(&mut system, alice, proposal_id, description);
+        assert_eq!(result, Err(GovernanceError::ProposalHasVotes));
+    }
+
+    #[test]
+    fn test_proposal_queries() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for who in [alice, bob] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+
+        for (creator, description) in [(alice, "A"), (bob, "B"), (alice, "C"), (bob, "D")] {
+            governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    creator,
+                    Track::Text,
+                    description.into(),
+                    None,
+                )
+                .unwrap();
+        }
+        governance.vote(&mut system, &mut staking, alice, 1, AYE).unwrap();
+        end_voting(&mut system, &governance, 1);
+        governance.finalize_proposal(&mut system, &mut staking, 1).unwrap();
+        governance.cancel_proposal(&mut system, &mut staking, alice, 2).unwrap();
+
+        let ids = |proposals: Vec<(u32, &Proposal<Runtime>)>| -> Vec<u32> {
+            proposals.into_iter().map(|(proposal_id, _)| proposal_id).collect()
+        };
+        assert_eq!(ids(governance.active_proposals().collect()), vec![0, 3]);
+        let approved = governance.proposals_by_status(ProposalStatus::Approved).collect();
+        assert_eq!(ids(approved), vec![1]);
+        assert_eq!(ids(governance.proposals_by_creator(&alice).collect()), vec![0, 2]);
+
+        // Results come in ID order, so they can be paginated
+        assert_eq!(ids(governance.iter_proposals().skip(1).take(2).collect()), vec![1, 2]);
+        assert_eq!(governance.get_vote(&alice, 1).map(|record| record.weight), Some(100));
+    }
+
+    #[test]
+    fn test_turnout_stats() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 100), (bob, 200), (charlie, 300), (dave, 400)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Turnout".to_string(),
+                None,
+            )
+            .unwrap();
+        let stats = governance.turnout(&staking, proposal_id).unwrap();
+        assert_eq!(stats.voters, 0);
+        assert_eq!(stats.participation_rate, 0);
+
+        // Charlie's stake takes part through Bob; conviction adds weight but not participation
+        governance.delegate(&mut system, charlie, Track::Text, bob).unwrap();
+        let strong_aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked2x };
+        governance.vote(&mut system, &mut staking, alice, proposal_id, strong_aye).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+
+        let stats = governance.turnout(&staking, proposal_id).unwrap();
+        assert_eq!(
+            stats,
+            TurnoutStats {
+                voters: 2,
+                total_weight: 700,
+                yes_weight: 200,
+                no_weight: 500,
+                participation_rate: 600_000,
+            }
+        );
+        assert_eq!(governance.turnout(&staking, 7), Err(GovernanceError::ProposalNotFound));
+    }
+
+    #[test]
+    fn test_stake_snapshot() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        staking.set_balance(bob, 1000);
+
+        let first_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "First".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // Stake bonded after creation does not count, and unbonded stake stops counting
+        staking.stake(bob, 500).unwrap();
+        let result = governance.vote(&mut system, &mut staking, bob, first_id, AYE);
+        assert_eq!(result, Err(GovernanceError::NoVotingPower));
+        staking.unbond(alice, 40, 0).unwrap();
+        governance.vote(&mut system, &mut staking, alice, first_id, AYE).unwrap();
+        assert_eq!(governance.get_vote(&alice, first_id).unwrap().weight, 60);
+
+        // Proposals created later snapshot the new stake
+        system.set_block_number(3);
+        let second_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Second".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, bob, second_id, NAY).unwrap();
+        assert_eq!(governance.get_vote(&bob, second_id).unwrap().weight, 500);
+        assert_eq!(governance.stake_snapshots.len(), 2);
+
+        // Snapshots are dropped once no open proposal needs them
+        end_voting(&mut system, &governance, first_id);
+        governance.finalize_proposal(&mut system, &mut staking, first_id).unwrap();
+        assert_eq!(governance.stake_snapshots.len(), 1);
+    }
+
+    #[test]
+    fn test_prune_proposal() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let retention_period = <Runtime as GovernanceConfig>::VOTE_RETENTION_PERIOD;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 100), (bob, 50), (charlie, 50)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Prune".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.delegate(&mut system, charlie, Track::Text, bob).unwrap();
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+
+        let result = governance.prune_proposal(&mut system, &staking, proposal_id);
+        assert_eq!(result, Err(GovernanceError::ProposalStillOpen));
+
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        let turnout = governance.turnout(&staking, proposal_id).unwrap();
+        let retained_until = system.block_number() + retention_period;
+        system.set_block_number(retained_until);
+        let result = governance.prune_proposal(&mut system, &staking, proposal_id);
+        assert_eq!(result, Err(GovernanceError::RetentionPeriodNotOver));
+
+        // Individual votes are dropped, but the tallies and turnout remain
+        system.set_block_number(retained_until + 1);
+        governance.prune_proposal(&mut system, &staking, proposal_id).unwrap();
+        assert!(governance.get_vote(&alice, proposal_id).is_none());
+        assert!(governance.votes_by_account(&bob).is_empty());
+        assert!(governance.delegated_votes.is_empty());
+        let proposal = governance.get_proposal(proposal_id).unwrap();
+        assert_eq!((proposal.tally.ayes, proposal.tally.nays), (100, 100));
+
+        staking.unbond(charlie, 50, 0).unwrap();
+        assert_eq!(governance.turnout(&staking, proposal_id), Ok(turnout));
+    }
+
+    #[test]
+    fn test_duplicate_proposals() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+
+        let text = "Rename the network".to_string();
+        let proposal_id = governance
+            .create_proposal(&mut system, &mut staking, alice, Track::Text, text.clone(), Some(5))
+            .unwrap();
+
+        // An identical proposal cannot be opened twice, even by someone else
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            bob,
+            Track::Text,
+            text.clone(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::DuplicateProposal));
+
+        // The same text with a different call is a different proposal
+        let call = RuntimeCall::Staking(StakingCall::SetBalance { who: bob, amount: 1 });
+        governance
+            .create_proposal_with_call(
+                &mut system,
+                &mut staking,
+                bob,
+                Track::Text,
+                text.clone(),
+                None,
+                Some(call),
+            )
+            .unwrap();
+
+        // Treasury spends with the same text differ by beneficiary and amount
+        for (to, amount) in [(bob, 100), (bob, 200), (alice, 100)] {
+            let description = text.clone();
+            governance
+                .create_spend_proposal(&mut system, &mut staking, alice, to, amount, description)
+                .unwrap();
+        }
+        let description = text.clone();
+        let result =
+            governance.create_spend_proposal(&mut system, &mut staking, bob, bob, 100, description);
+        assert_eq!(result, Err(GovernanceError::DuplicateProposal));
+
+        // Once rejected, the proposal is locked out for the resubmission cooldown
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        system.set_block_number(5);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            bob,
+            Track::Text,
+            text.clone(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::ProposalInCooldown));
+
+        system.set_block_number(106);
+        governance
+            .create_proposal(&mut system, &mut staking, bob, Track::Text, text, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_votes_lock_stake() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Lock".to_string(),
+                Some(10),
+            )
+            .unwrap();
+
+        // Voting locks the stake right away, abstaining does not
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, ABSTAIN).unwrap();
+        assert_eq!(staking.get_lock(&alice, GOVERNANCE_LOCK), 500);
+        assert_eq!(staking.get_lock(&bob, GOVERNANCE_LOCK), 0);
+        assert_eq!(staking.unbond(alice, 1, 0), Err(StakingError::LockedBalance));
+        staking.unbond(bob, 500, 0).unwrap();
+
+        // The lock cannot be released while the proposal is open
+        system.set_block_number(10);
+        let result = governance.unlock(&mut system, &mut staking, alice);
+        assert_eq!(result, Err(GovernanceError::LockNotExpired));
+
+        // After finalization the conviction period still has to pass
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        let result = governance.unlock(&mut system, &mut staking, alice);
+        assert_eq!(result, Err(GovernanceError::LockNotExpired));
+
+        system.set_block_number(20);
+        governance.unlock(&mut system, &mut staking, alice).unwrap();
+        staking.unbond(alice, 500, 0).unwrap();
+    }
+
+    #[test]
+    fn test_choice_proposals() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 400), (bob, 350), (charlie, 300)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+        }
+
+        let options: Vec<String> = ["Red", "Green", "Blue"].map(String::from).to_vec();
+        let result = governance.create_choice_proposal(
+            &mut system,
+            &mut staking,
+            alice,
+            Track::Text,
+            "Logo colour".to_string(),
+            vec!["Red".to_string()],
+            ChoiceMethod::Plurality,
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::InvalidOptions));
+
+        let mut ids = Vec::new();
+        for method in [ChoiceMethod::Plurality, ChoiceMethod::InstantRunoff] {
+            let id = governance
+                .create_choice_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    format!("Logo colour by {:?}", method),
+                    options.clone(),
+                    method,
+                    Some(10),
+                )
+                .unwrap();
+            governance.vote_ranked(&mut system, &mut staking, alice, id, vec![0]).unwrap();
+            governance.vote_ranked(&mut system, &mut staking, bob, id, vec![1, 0]).unwrap();
+            governance.vote_ranked(&mut system, &mut staking, charlie, id, vec![2, 1]).unwrap();
+            ids.push(id);
+        }
+
+        // Ballots must rank distinct, existing options, and yes/no votes do not apply
+        let result = governance.vote(&mut system, &mut staking, alice, ids[0], AYE);
+        assert_eq!(result, Err(GovernanceError::WrongProposalKind));
+        let result = governance.vote_ranked(&mut system, &mut staking, alice, ids[0], vec![3]);
+        assert_eq!(result, Err(GovernanceError::InvalidRanking));
+        let result = governance.vote_ranked(&mut system, &mut staking, alice, ids[0], vec![]);
+        assert_eq!(result, Err(GovernanceError::InvalidRanking));
+        let result = governance.vote_ranked(&mut system, &mut staking, alice, ids[0], vec![1, 1]);
+        assert_eq!(result, Err(GovernanceError::InvalidRanking));
+
+        // Ranked ballots keep the creator from cancelling
+        let result = governance.cancel_proposal(&mut system, &mut staking, alice, ids[0]);
+        assert_eq!(result, Err(GovernanceError::ProposalHasVotes));
+
+        // Plurality picks the most first preferences
+        assert_eq!(governance.get_proposal(ids[0]).unwrap().option_weights(), &[400, 350, 300]);
+        system.set_block_number(10);
+        let status = governance.finalize_proposal(&mut system, &mut staking, ids[0]).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        assert_eq!(governance.get_proposal(ids[0]).unwrap().winning_option(), Some(0));
+
+        // Instant runoff eliminates Blue and moves Charlie's ballot to Green
+        let tally = governance.tally_options(ids[1]).unwrap();
+        assert_eq!(tally, OptionTally { totals: vec![400, 650, 0], winner: Some(1) });
+        end_voting(&mut system, &governance, ids[1]);
+        governance.finalize_proposal(&mut system, &mut staking, ids[1]).unwrap();
+        assert_eq!(governance.get_proposal(ids[1]).unwrap().winning_option(), Some(1));
+    }
+
+    #[test]
+    fn test_seconding() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let second_deposit = <Runtime as GovernanceConfig>::SECOND_DEPOSIT;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 100);
+        staking.set_balance(charlie, 100);
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::RuntimeParameter,
+                "Raise the validator count".to_string(),
+                None,
+            )
+            .unwrap();
+        let status = governance.get_proposal(proposal_id).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Proposed);
+
+        // The proposal cannot be voted on until it has enough distinct seconds
+        let result = governance.vote(&mut system, &mut staking, alice, proposal_id, AYE);
+        assert_eq!(result, Err(GovernanceError::ProposalNotActive));
+        let result = governance.second(&mut system, &mut staking, alice, proposal_id);
+        assert_eq!(result, Err(GovernanceError::AlreadySeconded));
+        let result = governance.second(&mut system, &mut staking, dave, proposal_id);
+        assert_eq!(result, Err(GovernanceError::InsufficientDeposit));
+
+        system.set_block_number(5);
+        governance.second(&mut system, &mut staking, bob, proposal_id).unwrap();
+        let result = governance.second(&mut system, &mut staking, bob, proposal_id);
+        assert_eq!(result, Err(GovernanceError::AlreadySeconded));
+        assert_eq!(staking.get_reserved_balance(bob), second_deposit);
+
+        // The last second opens the voting period
+        governance.second(&mut system, &mut staking, charlie, proposal_id).unwrap();
+        assert_eq!(governance.seconds_of(proposal_id), &[bob, charlie]);
+        let status = governance.get_proposal(proposal_id).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Active);
+        assert_eq!(governance.remaining_voting_time(&system, proposal_id), Ok(300));
+        let result = governance.second(&mut system, &mut staking, dave, proposal_id);
+        assert_eq!(result, Err(GovernanceError::NotAwaitingSeconds));
+
+        // Seconding deposits are returned once the proposal is settled
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert_eq!(staking.get_reserved_balance(bob), 0);
+        assert_eq!(staking.get_free_balance(charlie), 100);
+        assert!(governance.seconds_of(proposal_id).is_empty());
+    }
+
+    #[test]
+    fn test_tally_overflow() {
+        let mut tally = Tally::default();
+        tally.add(Vote::Aye, u128::MAX).unwrap();
+        tally.add(Vote::Nay, 10).unwrap();
+
+        // Overflowing a bucket is an error and leaves the tally untouched
+        assert_eq!(tally.add(Vote::Aye, 1), Err(GovernanceError::Overflow));
+        assert_eq!(tally.ayes, u128::MAX);
+        assert_eq!(tally.turnout(), u128::MAX);
+
+        // Removing more than was counted stops at zero
+        tally.remove(Vote::Nay, 20);
+        assert_eq!(tally, Tally { ayes: u128::MAX, nays: 0, abstentions: 0 });
+    }
+
+    #[test]
+    fn test_proposal_rate_limit() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let max_per_window = <Runtime as GovernanceConfig>::MAX_PROPOSALS_PER_WINDOW;
+        let window = <Runtime as GovernanceConfig>::PROPOSAL_RATE_WINDOW;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 10_000);
+        staking.set_balance(bob, 1000);
+
+        for i in 0..max_per_window {
+            system.set_block_number((i % 2) as u64);
+            governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    format!("Spam {}", i),
+                    None,
+                )
+                .unwrap();
+        }
+
+        // Alice has used up her allowance for the window, other accounts are unaffected
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            alice,
+            Track::Text,
+            "One more".to_string(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::RateLimited));
+        governance
+            .create_proposal(&mut system, &mut staking, bob, Track::Text, "Bob".to_string(), None)
+            .unwrap();
+
+        // Proposals created at block 0 leave the window first
+        system.set_block_number(window);
+        governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Later".to_string(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(governance.recent_proposals.get(&alice).unwrap().len(), 11);
+    }
+
+    #[test]
+    fn test_participation_rewards() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let treasury = <Runtime as GovernanceConfig>::TREASURY_ACCOUNT;
+        let reward = <Runtime as GovernanceConfig>::PARTICIPATION_REWARD;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+        staking.set_balance(treasury, 1);
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Reward voters".to_string(),
+                Some(5),
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+
+        // Rewards can only be claimed once the proposal is finalized
+        let result = governance.claim_participation_reward(&mut system, &mut staking, alice, 0);
+        assert_eq!(result, Err(GovernanceError::ProposalStillOpen));
+
+        system.set_block_number(5);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        let free_before = staking.get_free_balance(bob);
+        governance.claim_participation_reward(&mut system, &mut staking, bob, proposal_id).unwrap();
+        assert_eq!(staking.get_free_balance(bob), free_before + reward);
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::ParticipationRewarded {
+                who: 2,
+                proposal_id: 0,
+                amount: 1,
+            }))
+        ));
+
+        // No second payout, no reward without a vote, and none once the treasury is empty
+        let result = governance.claim_participation_reward(&mut system, &mut staking, bob, 0);
+        assert_eq!(result, Err(GovernanceError::RewardAlreadyClaimed));
+        let result = governance.claim_participation_reward(&mut system, &mut staking, charlie, 0);
+        assert_eq!(result, Err(GovernanceError::NotVoted));
+        let result = governance.claim_participation_reward(&mut system, &mut staking, alice, 0);
+        assert_eq!(result, Err(GovernanceError::Staking(StakingError::InsufficientBalance)));
+    }
+
+    #[test]
+    fn test_split_votes() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 2000);
+        staking.stake(alice, 1000).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Split".to_string(),
+                None,
+            )
+            .unwrap();
+
+        // A split cannot use more stake than the voter has
+        let result = governance.vote_split(&mut system, &mut staking, alice, proposal_id, 600, 500);
+        assert_eq!(result, Err(GovernanceError::SplitExceedsStake));
+
+        governance.vote_split(&mut system, &mut staking, alice, proposal_id, 600, 400).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        let tally = *governance.get_proposal(proposal_id).unwrap().tally();
+        assert_eq!(tally, Tally { ayes: 600, nays: 900, abstentions: 0 });
+        assert_eq!(staking.get_lock(&alice, GOVERNANCE_LOCK), 1000);
+
+        // Changing or removing a split vote takes both parts back out of the tally
+        governance.change_vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        let tally = *governance.get_proposal(proposal_id).unwrap().tally();
+        assert_eq!(tally, Tally { ayes: 1000, nays: 500, abstentions: 0 });
+
+        governance.remove_vote(&mut system, &mut staking, alice, proposal_id).unwrap();
+        governance.vote_split(&mut system, &mut staking, alice, proposal_id, 100, 300).unwrap();
+        let record = governance.get_vote(&alice, proposal_id).unwrap();
+        assert_eq!(record.vote.vote, Vote::Nay);
+        assert_eq!(record.split, Some(SplitWeights { aye: 100, nay: 300 }));
+        governance.remove_vote(&mut system, &mut staking, alice, proposal_id).unwrap();
+        let tally = *governance.get_proposal(proposal_id).unwrap().tally();
+        assert_eq!(tally, Tally { ayes: 0, nays: 500, abstentions: 0 });
+    }
+
+    #[test]
+    fn test_split_votes_are_not_locked() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 2000);
+        staking.stake(alice, 1000).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 500).unwrap();
+
+        // Alice's split leans towards the side that wins each proposal
+        let mut ids = Vec::new();
+        let votes = [("Passes", 600, 400, AYE), ("Fails", 100, 300, NAY)];
+        for (description, aye, nay, bob_vote) in votes {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    description.to_string(),
+                    None,
+                )
+                .unwrap();
+            governance.vote_split(&mut system, &mut staking, alice, id, aye, nay).unwrap();
+            governance.vote(&mut system, &mut staking, bob, id, bob_vote).unwrap();
+            ids.push(id);
+        }
+        for id in &ids {
+            end_voting(&mut system, &governance, *id);
+            governance.finalize_proposal(&mut system, &mut staking, *id).unwrap();
+        }
+        let status = governance.get_proposal(ids[0]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Approved);
+        let status = governance.get_proposal(ids[1]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Rejected);
+
+        // Neither split keeps a lock past its proposal, while Bob's winning votes do
+        governance.unlock(&mut system, &mut staking, alice).unwrap();
+        assert!(ids.iter().all(|id| governance.get_lock(&alice, *id).is_none()));
+        assert_eq!(staking.get_lock(&alice, GOVERNANCE_LOCK), 0);
+        let result = governance.unlock(&mut system, &mut staking, bob);
+        assert_eq!(result, Err(GovernanceError::LockNotExpired));
+        assert_eq!(staking.get_lock(&bob, GOVERNANCE_LOCK), 500);
+    }
+
+    #[test]
+    fn test_proposal_tags() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+
+        let mut ids = Vec::new();
+        for description in ["Fund the wiki", "Fund the explorer", "Rename the chain"] {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    description.to_string(),
+                    None,
+                )
+                .unwrap();
+            ids.push(id);
+        }
+
+        let tags = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        governance.set_tags(&mut system, alice, ids[0], tags(&["Treasury", "docs"])).unwrap();
+        governance.set_tags(&mut system, alice, ids[1], tags(&[" treasury "])).unwrap();
+        governance.set_tags(&mut system, alice, ids[2], tags(&["branding"])).unwrap();
+        assert_eq!(governance.get_proposal(ids[0]).unwrap().tags(), &["treasury", "docs"]);
+
+        // Tags are matched case-insensitively, in ID order
+        let tagged: Vec<u32> =
+            governance.proposals_with_tag("TREASURY").map(|(id, _)| id).collect();
+        assert_eq!(tagged, vec![ids[0], ids[1]]);
+
+        // Retagging replaces the old tags
+        governance.set_tags(&mut system, alice, ids[0], tags(&["docs"])).unwrap();
+        let tagged: Vec<u32> =
+            governance.proposals_with_tag("treasury").map(|(id, _)| id).collect();
+        assert_eq!(tagged, vec![ids[1]]);
+
+        // Only the creator can tag, and tags are bounded
+        let result = governance.set_tags(&mut system, bob, ids[2], tags(&["spam"]));
+        assert_eq!(result, Err(GovernanceError::NotCreator));
+        let result = governance.set_tags(&mut system, alice, ids[2], tags(&[""]));
+        assert_eq!(result, Err(GovernanceError::InvalidTag));
+        let too_many = tags(&["a", "b", "c", "d", "e", "f"]);
+        let result = governance.set_tags(&mut system, alice, ids[2], too_many);
+        assert_eq!(result, Err(GovernanceError::TooManyTags));
+    }
+
+    #[test]
+    fn test_per_track_delegation() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 300).unwrap();
+
+        // Bob hands his treasury votes to Alice but keeps text votes himself
+        governance.delegate(&mut system, bob, Track::Treasury, alice).unwrap();
+        assert_eq!(governance.get_delegate(&bob, Track::Treasury), Some(&alice));
+        assert_eq!(governance.get_delegate(&bob, Track::Text), None);
+
+        let mut ids = Vec::new();
+        for track in [Track::Treasury, Track::Text] {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    track,
+                    format!("Delegation on {:?}", track),
+                    None,
+                )
+                .unwrap();
+            governance.vote(&mut system, &mut staking, alice, id, AYE).unwrap();
+            ids.push(id);
+        }
+
+        let result = governance.vote(&mut system, &mut staking, bob, ids[0], NAY);
+        assert_eq!(result, Err(GovernanceError::VoterIsDelegating));
+        governance.vote(&mut system, &mut staking, bob, ids[1], NAY).unwrap();
+
+        assert_eq!(governance.get_proposal(ids[0]).unwrap().tally().ayes, 800);
+        assert_eq!(governance.get_proposal(ids[1]).unwrap().tally().ayes, 500);
+        assert_eq!(governance.get_proposal(ids[1]).unwrap().tally().nays, 300);
+
+        // Delegations are revoked per track as well
+        let result = governance.undelegate(&mut system, bob, Track::Text);
+        assert_eq!(result, Err(GovernanceError::NotDelegating));
+        governance.undelegate(&mut system, bob, Track::Treasury).unwrap();
+        assert_eq!(governance.undelegation_effective_at(&bob, Track::Treasury), Some(5));
+    }
+
+    #[test]
+    fn test_pause() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 300).unwrap();
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Before the incident".to_string(),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+
+        let result = governance.pause(&mut system, Origin::Signed(bob));
+        assert_eq!(result, Err(GovernanceError::BadOrigin));
+        assert!(!governance.is_paused());
+
+        governance.pause(&mut system, Origin::Root).unwrap();
+        assert!(governance.is_paused());
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::Paused))
+        ));
+
+        // Proposing and voting are rejected with a dedicated error and event
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            alice,
+            Track::Text,
+            "During the incident".to_string(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::GovernancePaused));
+        let result = governance.vote(&mut system, &mut staking, bob, proposal_id, NAY);
+        assert_eq!(result, Err(GovernanceError::GovernancePaused));
+        assert!(matches!(
+            system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::RejectedWhilePaused { who: 2 }))
+        ));
+
+        // Queries keep working
+        assert_eq!(governance.get_proposal(proposal_id).unwrap().tally().ayes, 500);
+        assert_eq!(governance.get_vote(&bob, proposal_id).map(|record| record.vote), None);
+
+        governance.unpause(&mut system, Origin::Root).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        assert_eq!(governance.get_proposal(proposal_id).unwrap().tally().nays, 300);
+    }
+
+    #[test]
+    fn test_archive() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 300).unwrap();
+
+        let mut ids = Vec::new();
+        for description in ["Archived by hand", "Archived by the hook"] {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    description.to_string(),
+                    None,
+                )
+                .unwrap();
+            governance.set_tags(&mut system, alice, id, vec!["history".to_string()]).unwrap();
+            governance.vote(&mut system, &mut staking, alice, id, AYE).unwrap();
+            governance.vote(&mut system, &mut staking, bob, id, NAY).unwrap();
+            ids.push(id);
+        }
+        for id in &ids {
+            end_voting(&mut system, &governance, *id);
+            governance.finalize_proposal(&mut system, &mut staking, *id).unwrap();
+        }
+
+        // Finalized proposals stay live until the archive period is over
+        for _ in 0..Runtime::ARCHIVE_PERIOD {
+            system.inc_block_number();
+        }
+        let result = governance.archive_proposal(&mut system, &mut staking, ids[0]);
+        assert_eq!(result, Err(GovernanceError::ArchivePeriodNotOver));
+        system.inc_block_number();
+        governance.archive_proposal(&mut system, &mut staking, ids[0]).unwrap();
+
+        // Only the compact record is left
+        assert!(governance.get_proposal(ids[0]).is_none());
+        assert!(governance.get_vote(&alice, ids[0]).is_none());
+        assert_eq!(governance.proposals_with_tag("history").count(), 1);
+        let record = governance.get_archived(ids[0]).unwrap();
+        assert_eq!(record.status, ProposalStatus::Approved);
+        assert_eq!(record.tally.ayes, 500);
+        assert_eq!(record.tally.nays, 300);
+
+        // Lookups cover both stores
+        let record = governance.lookup_proposal(ids[0]).unwrap();
+        assert!(matches!(record, ProposalRecord::Archived(_)));
+        let record = governance.lookup_proposal(ids[1]).unwrap();
+        assert!(matches!(record, ProposalRecord::Live(_)));
+        assert_eq!(record.status(), &ProposalStatus::Approved);
+
+        // The end-of-block hook archives the rest
+        let block_number = system.block_number();
+        governance.on_finalize(&mut system, &mut staking, block_number);
+        let archived: Vec<u32> = governance.archived_proposals().map(|(id, _)| id).collect();
+        assert_eq!(archived, ids);
+        assert_eq!(governance.iter_proposals().count(), 0);
+    }
+
+    #[test]
+    fn test_voters_of() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+
+        let proposal_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Who voted how".to_string(),
+                None,
+            )
+            .unwrap();
+        assert!(governance.voters_of(proposal_id).is_empty());
+
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, bob, proposal_id, NAY).unwrap();
+        governance.vote(&mut system, &mut staking, charlie, proposal_id, ABSTAIN).unwrap();
+        assert_eq!(
+            governance.voters_of(proposal_id),
+            vec![(alice, Vote::Aye), (bob, Vote::Nay), (charlie, Vote::Abstain)]
+        );
+
+        // Changed and removed votes are reflected
+        governance.change_vote(&mut system, &mut staking, bob, proposal_id, AYE).unwrap();
+        governance.remove_vote(&mut system, &mut staking, charlie, proposal_id).unwrap();
+        assert_eq!(governance.voters_of(proposal_id), vec![(alice, Vote::Aye), (bob, Vote::Aye)]);
+    }
+
+    #[test]
+    fn test_amendments() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+
+        let call = RuntimeCall::Staking(StakingCall::SetBalance { who: bob, amount: 100 });
+        let parent_id = governance
+            .create_proposal_with_call(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Give Bob 100".to_string(),
+                Some(20),
+                Some(call),
+            )
+            .unwrap();
+
+        // Amendments cannot stay open longer than their parent
+        let call = RuntimeCall::Staking(StakingCall::SetBalance { who: bob, amount: 50 });
+        let result = governance.create_amendment(
+            &mut system,
+            &mut staking,
+            alice,
+            parent_id,
+            "Give Bob 50 instead".to_string(),
+            Some(21),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::AmendmentOutlastsParent));
+        let amendment_id = governance
+            .create_amendment(
+                &mut system,
+                &mut staking,
+                alice,
+                parent_id,
+                "Give Bob 50 instead".to_string(),
+                None,
+                Some(call),
+            )
+            .unwrap();
+        assert_eq!(governance.get_proposal(amendment_id).unwrap().parent(), Some(parent_id));
+
+        governance.vote(&mut system, &mut staking, alice, parent_id, AYE).unwrap();
+        governance.vote(&mut system, &mut staking, alice, amendment_id, AYE).unwrap();
+
+        // Finalizing the parent decides the amendment first, which replaces the parent's text
+        end_voting(&mut system, &governance, parent_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, parent_id).unwrap();
+        assert_eq!(status, ProposalStatus::Approved);
+        let amendment = governance.get_proposal(amendment_id).unwrap();
+        assert_eq!(amendment.status(), &ProposalStatus::Approved);
+        assert_eq!(amendment.enactment_block(), None);
+        let description_hash = *amendment.description_hash();
+        let parent = governance.get_proposal(parent_id).unwrap();
+        assert_eq!(parent.description_hash(), &description_hash);
+        assert!(system.events().iter().any(|event| matches!(
+            event,
+            RuntimeEvent::Governance(GovernanceEvent::ProposalAmended { proposal_id: 0, .. })
+        )));
+
+        // Only the amended call is enacted
+        let enactment_block = parent.enactment_block().unwrap();
+        while system.block_number() < enactment_block {
+            system.inc_block_number();
+        }
+        let calls = governance.on_initialize(&mut system, &mut staking, enactment_block);
+        assert_eq!(calls.len(), 1);
+        assert!(matches!(
+            calls[0],
+            (0, RuntimeCall::Staking(StakingCall::SetBalance { amount: 50, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_amendment_ends_before_parent() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+
+        let parent_id = governance
+            .create_proposal(
+                &mut system,
+                &mut staking,
+                alice,
+                Track::Text,
+                "Plant trees".to_string(),
+                Some(20),
+            )
+            .unwrap();
+        let amendment_id = governance
+            .create_amendment(
+                &mut system,
+                &mut staking,
+                alice,
+                parent_id,
+                "Plant oaks".to_string(),
+                Some(5),
+                None,
+            )
+            .unwrap();
+        governance.vote(&mut system, &mut staking, alice, amendment_id, AYE).unwrap();
+
+        // The amended parent is still open, so its new text cannot be proposed again
+        end_voting(&mut system, &governance, amendment_id);
+        let status = governance.finalize_proposal(&mut system, &mut staking, amendment_id);
+        assert_eq!(status, Ok(ProposalStatus::Approved));
+        let parent = governance.get_proposal(parent_id).unwrap();
+        assert_eq!(parent.status(), &ProposalStatus::Active);
+        let result = governance.create_proposal(
+            &mut system,
+            &mut staking,
+            bob,
+            Track::Text,
+            "Plant oaks".to_string(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::DuplicateProposal));
+    }
+
+    #[test]
+    fn test_locks_per_proposal() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let locking_period = <Runtime as GovernanceConfig>::VOTE_LOCKING_PERIOD;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 2000);
+        staking.stake(bob, 1000).unwrap();
+        staking.set_balance(charlie, 1000);
+        staking.stake(charlie, 200).unwrap();
+
+        let mut ids = Vec::new();
+        for (description, bob_vote) in [("Alice wins", AYE), ("Alice loses", NAY)] {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    description.to_string(),
+                    None,
+                )
+                .unwrap();
+            governance.vote(&mut system, &mut staking, alice, id, AYE).unwrap();
+            governance.vote(&mut system, &mut staking, bob, id, bob_vote).unwrap();
+            ids.push(id);
+        }
+
+        // Retracting a vote releases its lock right away
+        governance.vote(&mut system, &mut staking, charlie, ids[0], AYE).unwrap();
+        assert_eq!(staking.get_lock(&charlie, GOVERNANCE_LOCK), 200);
+        governance.remove_vote(&mut system, &mut staking, charlie, ids[0]).unwrap();
+        assert_eq!(staking.get_lock(&charlie, GOVERNANCE_LOCK), 0);
+
+        // Switching a vote to abstain releases its lock as well
+        governance.vote(&mut system, &mut staking, charlie, ids[0], AYE).unwrap();
+        governance.change_vote(&mut system, &mut staking, charlie, ids[0], ABSTAIN).unwrap();
+        assert!(governance.get_lock(&charlie, ids[0]).is_none());
+        assert_eq!(staking.get_lock(&charlie, GOVERNANCE_LOCK), 0);
+
+        for id in &ids {
+            end_voting(&mut system, &governance, *id);
+            governance.finalize_proposal(&mut system, &mut staking, *id).unwrap();
+        }
+        let locked_until = system.block_number() + locking_period;
+
+        // Only the lock from the winning vote outlives its proposal
+        governance.unlock(&mut system, &mut staking, alice).unwrap();
+        assert!(governance.get_lock(&alice, ids[1]).is_none());
+        assert_eq!(governance.get_lock(&alice, ids[0]).unwrap().until, locked_until);
+        assert_eq!(staking.get_lock(&alice, GOVERNANCE_LOCK), 500);
+
+        system.set_block_number(locked_until);
+        governance.unlock(&mut system, &mut staking, alice).unwrap();
+        assert_eq!(staking.get_lock(&alice, GOVERNANCE_LOCK), 0);
+        let result = governance.unlock(&mut system, &mut staking, alice);
+        assert_eq!(result, Err(GovernanceError::NoVotingLock));
+    }
+
+    #[test]
+    fn test_signed_votes() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+
+        let mut ids = Vec::new();
+        for description in ["Signed vote", "Another signed vote"] {
+            let id = governance
+                .create_proposal(
+                    &mut system,
+                    &mut staking,
+                    alice,
+                    Track::Text,
+                    description.to_string(),
+                    None,
+                )
+                .unwrap();
+            ids.push(id);
+        }
+
+        // Alice signs offline and anyone can relay the vote
+        let payload = GovernancePallet::<Runtime>::signed_vote_payload(ids[0], AYE, 0);
+        let signature = sign(alice, &payload);
+        let result =
+            governance.vote_signed(&mut system, &mut staking, bob, ids[0], AYE, &signature);
+        assert_eq!(result, Err(GovernanceError::InvalidSignature));
+        let result =
+            governance.vote_signed(&mut system, &mut staking, alice, ids[0], NAY, &signature);
+        assert_eq!(result, Err(GovernanceError::InvalidSignature));
+        governance.vote_signed(&mut system, &mut staking, alice, ids[0], AYE, &signature).unwrap();
+        assert_eq!(governance.get_proposal(ids[0]).unwrap().tally().ayes, 500);
+        assert_eq!(governance.vote_nonce(&alice), 1);
+
+        // The nonce moves on, so the same signature cannot be replayed
+        let payload = GovernancePallet::<Runtime>::signed_vote_payload(ids[1], AYE, 0);
+        let stale = sign(alice, &payload);
+        let result = governance.vote_signed(&mut system, &mut staking, alice, ids[1], AYE, &stale);
+        assert_eq!(result, Err(GovernanceError::InvalidSignature));
+        let payload = GovernancePallet::<Runtime>::signed_vote_payload(ids[1], AYE, 1);
+        let signature = sign(alice, &payload);
+        governance.vote_signed(&mut system, &mut staking, alice, ids[1], AYE, &signature).unwrap();
+    }
+
+    #[test]
+    fn test_proposal_by_hash() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut governance = GovernancePallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 300).unwrap();
+
+        let description = "Found by hash".to_string();
+        let proposal_hash = GovernancePallet::<Runtime>::proposal_hash(&description, None, None);
+        assert_eq!(governance.proposal_by_hash(&proposal_hash), None);
+
+        let proposal_id = governance
+            .create_proposal(&mut system, &mut staking, alice, Track::Text, description, None)
+            .unwrap();
+        let description_hash = *governance.get_proposal(proposal_id).unwrap().description_hash();
+        assert_eq!(governance.proposal_by_hash(&proposal_hash), Some(proposal_id));
+        assert_eq!(governance.proposal_by_hash(&description_hash), Some(proposal_id));
+
+        // The index outlives the proposal's move to the archive
+        governance.vote(&mut system, &mut staking, alice, proposal_id, AYE).unwrap();
+        end_voting(&mut system, &governance, proposal_id);
+        governance.finalize_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        for _ in 0..=Runtime::ARCHIVE_PERIOD {
+            system.inc_block_number();
+        }
+        governance.archive_proposal(&mut system, &mut staking, proposal_id).unwrap();
+        assert_eq!(governance.proposal_by_hash(&proposal_hash), Some(proposal_id));
+        assert_eq!(governance.proposal_by_hash(&description_hash), Some(proposal_id));
     }
 }
@@ -0,0 +1,107 @@
+use crate::staking::Perbill;
+
+// Method used to work out the reward minted for the validators of each era
+pub trait EraPayout {
+    // Reward minted for an era, given the stake backing its validators and the total issuance
+    // when it ends
+    fn era_payout(total_staked: u128, total_issuance: u128) -> u128;
+}
+
+// Mint the same reward every era, whatever the issuance
+pub struct FixedPayout<const AMOUNT: u128>;
+
+impl<const AMOUNT: u128> EraPayout for FixedPayout<AMOUNT> {
+    fn era_payout(_total_staked: u128, _total_issuance: u128) -> u128 {
+        AMOUNT
+    }
+}
+
+// Inflate the total issuance by a fixed yearly percentage, spread evenly over the eras of
+// a year
+pub struct FixedInflation<const PERCENT: u32, const ERAS_PER_YEAR: u32>;
+
+impl<const PERCENT: u32, const ERAS_PER_YEAR: u32> EraPayout
+    for FixedInflation<PERCENT, ERAS_PER_YEAR>
+{
+    fn era_payout(_total_staked: u128, total_issuance: u128) -> u128 {
+        Perbill::from_percent(PERCENT).mul_floor(total_issuance) / (ERAS_PER_YEAR.max(1) as u128)
+    }
+}
+
+// Ideal staking rate curve, like `pallet_staking_reward_curve`: yearly inflation rises linearly
+// from `MIN_PERCENT` with nothing staked to `MAX_PERCENT` when `IDEAL_PERCENT` of the issuance
+// is staked, then falls linearly back to `MIN_PERCENT` as the rest gets staked. This rewards
+// staking up to the ideal rate while leaving enough of the issuance liquid.
+pub struct StakingRateCurve<
+    const MIN_PERCENT: u32,
+    const MAX_PERCENT: u32,
+    const IDEAL_PERCENT: u32,
+    const ERAS_PER_YEAR: u32,
+>;
+
+impl<
+        const MIN_PERCENT: u32,
+        const MAX_PERCENT: u32,
+        const IDEAL_PERCENT: u32,
+        const ERAS_PER_YEAR: u32,
+    > StakingRateCurve<MIN_PERCENT, MAX_PERCENT, IDEAL_PERCENT, ERAS_PER_YEAR>
+{
+    // Yearly inflation, in parts per billion of the issuance, at a staking rate in parts per
+    // billion
+    fn yearly_inflation(staking_rate: u128) -> u128 {
+        let accuracy = Perbill::from_percent(100).deconstruct() as u128;
+        let min = Perbill::from_percent(MIN_PERCENT).deconstruct() as u128;
+        let max = (Perbill::from_percent(MAX_PERCENT).deconstruct() as u128).max(min);
+        let ideal = Perbill::from_percent(IDEAL_PERCENT).deconstruct() as u128;
+        let rise = max - min;
+        if staking_rate <= ideal {
+            match ideal {
+                0 => max,
+                _ => min + rise * staking_rate / ideal,
+            }
+        } else {
+            let above = (staking_rate - ideal).min(accuracy - ideal);
+            max - rise * above / (accuracy - ideal)
+        }
+    }
+}
+
+impl<
+        const MIN_PERCENT: u32,
+        const MAX_PERCENT: u32,
+        const IDEAL_PERCENT: u32,
+        const ERAS_PER_YEAR: u32,
+    > EraPayout for StakingRateCurve<MIN_PERCENT, MAX_PERCENT, IDEAL_PERCENT, ERAS_PER_YEAR>
+{
+    fn era_payout(total_staked: u128, total_issuance: u128) -> u128 {
+        if total_issuance == 0 {
+            return 0;
+        }
+        let accuracy = Perbill::from_percent(100).deconstruct() as u128;
+        let staked = total_staked.min(total_issuance);
+        let staking_rate = staked.saturating_mul(accuracy) / total_issuance;
+        let inflation = Self::yearly_inflation(staking_rate) as u32;
+        Perbill::from_parts(inflation).mul_floor(total_issuance) / (ERAS_PER_YEAR.max(1) as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_era_payout_models() {
+        // 10% a year over 10 eras mints 1% of the issuance each era
+        assert_eq!(FixedPayout::<100>::era_payout(0, 1_000_000), 100);
+        assert_eq!(FixedInflation::<10, 10>::era_payout(0, 1_000_000), 10_000);
+
+        // The curve peaks at the ideal staking rate and falls off on either side
+        type Curve = StakingRateCurve<2, 10, 50, 10>;
+        assert_eq!(Curve::era_payout(0, 1_000_000), 2_000);
+        assert_eq!(Curve::era_payout(250_000, 1_000_000), 6_000);
+        assert_eq!(Curve::era_payout(500_000, 1_000_000), 10_000);
+        assert_eq!(Curve::era_payout(750_000, 1_000_000), 6_000);
+        assert_eq!(Curve::era_payout(1_000_000, 1_000_000), 2_000);
+        assert_eq!(Curve::era_payout(0, 0), 0);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::staking::StakingConfig;
+use num::traits::{CheckedAdd, CheckedSub, Zero};
+use std::collections::BTreeSet;
+
+// Optional cover against slashing: insured stakers give up `INSURANCE_PREMIUM` of each era
+// reward to the pool, which reimburses `INSURANCE_COVER` of their slashes, up to
+// `INSURANCE_CLAIM_CAP` per slash and as far as its funds go
+pub struct InsurancePool<T: StakingConfig> {
+    pub members: BTreeSet<T::AccountId>,
+    pub funds: T::Balance, // Premiums collected and not yet paid out in claims
+}
+
+impl<T: StakingConfig> InsurancePool<T> {
+    pub fn new() -> Self {
+        Self { members: BTreeSet::new(), funds: T::Balance::zero() }
+    }
+
+    // Whether an account is insured
+    pub fn is_member(&self, who: &T::AccountId) -> bool {
+        self.members.contains(who)
+    }
+
+    // Premium owed on a reward, or nothing if the account is not insured
+    pub fn premium(&self, who: &T::AccountId, reward: u128) -> u128 {
+        match self.is_member(who) {
+            true => T::INSURANCE_PREMIUM.mul_floor(reward),
+            false => 0,
+        }
+    }
+
+    // Add a premium to the pool's funds, returning false if they would overflow
+    pub fn collect(&mut self, premium: T::Balance) -> bool {
+        match self.funds.checked_add(&premium) {
+            Some(funds) => {
+                self.funds = funds;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Take the reimbursement of a member's slash out of the pool's funds
+    pub fn claim(&mut self, who: &T::AccountId, slashed: T::Balance) -> T::Balance {
+        if !self.is_member(who) {
+            return T::Balance::zero();
+        }
+        let cover = T::INSURANCE_COVER.mul_floor(slashed.into());
+        // The cover never exceeds the slashed amount, so it always fits in a balance
+        let mut cover = T::Balance::try_from(cover).unwrap_or(slashed);
+        for limit in [T::INSURANCE_CLAIM_CAP, self.funds] {
+            if cover > limit {
+                cover = limit;
+            }
+        }
+        self.funds = self.funds.checked_sub(&cover).unwrap_or_else(Zero::zero);
+        cover
+    }
+}
+
+impl<T: StakingConfig> Default for InsurancePool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Runtime;
+
+    #[test]
+    fn test_insurance_pool() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut pool = InsurancePool::<Runtime>::new();
+
+        pool.members.insert(alice);
+        assert_eq!(pool.premium(&alice, 200), 10);
+        assert_eq!(pool.premium(&bob, 200), 0);
+
+        // Claims are covered by half, capped per slash and by the funds collected
+        assert!(pool.collect(150));
+        assert_eq!(pool.claim(&bob, 100), 0);
+        assert_eq!(pool.claim(&alice, 100), 50);
+        assert_eq!(pool.claim(&alice, 400), 100);
+        assert_eq!(pool.funds, 0u64);
+        assert_eq!(pool.claim(&alice, 100), 0);
+    }
+}
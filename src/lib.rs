@@ -1,24 +1,827 @@
+pub use committee::CommitteeConfig;
+pub use council::CouncilConfig;
 pub use governance::GovernanceConfig;
+pub use offences::OffencesConfig;
+pub use pools::PoolsConfig;
 pub use staking::StakingConfig;
 pub use system::SystemConfig;
 
+use committee::{CommitteeEvent, CommitteePallet};
+use council::{CouncilEvent, CouncilPallet};
+use election::PhragmenElection;
+use inflation::FixedPayout;
+use governance::{
+    ApprovalCurve, GovernanceCall, GovernanceError, GovernanceEvent, GovernancePallet,
+    LinearTally, Track, TrackInfo,
+};
+use offences::{OffenceKind, OffencesEvent, OffencesPallet};
+use pools::{PoolsEvent, PoolsPallet};
+use reward::ProportionalSplit;
+use staking::{ParameterChange, Perbill, StakingCall, StakingEvent, StakingPallet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use system::{DispatchError, Origin, SystemPallet};
+
+pub mod committee;
+pub mod council;
+pub mod election;
 pub mod governance;
+pub mod inflation;
+pub mod insurance;
+pub mod offences;
+pub mod pools;
+pub mod preimage;
+pub mod reward;
 pub mod staking;
 pub mod system;
 
-pub struct Runtime;
+// Origins calls into the runtime can be made with
+pub type RuntimeOrigin = Origin<Runtime>;
+
+// All calls that can be dispatched through the runtime
+pub enum RuntimeCall {
+    Staking(StakingCall<Runtime>),
+    Governance(GovernanceCall<Runtime>),
+}
+
+// All events that can be deposited into the system pallet
+pub enum RuntimeEvent {
+    Governance(GovernanceEvent<Runtime>),
+    Council(CouncilEvent<Runtime>),
+    Committee(CommitteeEvent<Runtime>),
+    Pools(PoolsEvent<Runtime>),
+    Staking(StakingEvent<Runtime>),
+    Offences(OffencesEvent<Runtime>),
+}
+
+impl From<GovernanceEvent<Runtime>> for RuntimeEvent {
+    fn from(event: GovernanceEvent<Runtime>) -> Self {
+        RuntimeEvent::Governance(event)
+    }
+}
+
+impl From<CouncilEvent<Runtime>> for RuntimeEvent {
+    fn from(event: CouncilEvent<Runtime>) -> Self {
+        RuntimeEvent::Council(event)
+    }
+}
+
+impl From<CommitteeEvent<Runtime>> for RuntimeEvent {
+    fn from(event: CommitteeEvent<Runtime>) -> Self {
+        RuntimeEvent::Committee(event)
+    }
+}
+
+impl From<PoolsEvent<Runtime>> for RuntimeEvent {
+    fn from(event: PoolsEvent<Runtime>) -> Self {
+        RuntimeEvent::Pools(event)
+    }
+}
+
+impl From<StakingEvent<Runtime>> for RuntimeEvent {
+    fn from(event: StakingEvent<Runtime>) -> Self {
+        RuntimeEvent::Staking(event)
+    }
+}
+
+impl From<OffencesEvent<Runtime>> for RuntimeEvent {
+    fn from(event: OffencesEvent<Runtime>) -> Self {
+        RuntimeEvent::Offences(event)
+    }
+}
+
+pub struct Runtime {
+    pub system: SystemPallet<Self>,
+    pub staking: StakingPallet<Self>,
+    pub governance: GovernancePallet<Self>,
+    pub council: CouncilPallet<Self>,
+    pub committee: CommitteePallet<Self>,
+    pub pools: PoolsPallet<Self>,
+    pub offences: OffencesPallet<Self>,
+}
+
+impl Runtime {
+    pub fn new() -> Self {
+        Self {
+            system: SystemPallet::new(),
+            staking: StakingPallet::new(),
+            governance: GovernancePallet::new(),
+            council: CouncilPallet::new(),
+            committee: CommitteePallet::new(),
+            pools: PoolsPallet::new(),
+            offences: OffencesPallet::new(),
+        }
+    }
+
+    // Route a call to the pallet it belongs to
+    pub fn dispatch(&mut self, call: RuntimeCall) -> Result<(), DispatchError> {
+        match call {
+            RuntimeCall::Staking(call) => self.staking.dispatch(call).map_err(Into::into),
+            RuntimeCall::Governance(call) => self
+                .governance
+                .dispatch(&mut self.system, call)
+                .map_err(|message| DispatchError::Module { pallet: "Governance", message }),
+        }
+    }
+
+    // Enact an approved governance proposal by dispatching its call. Proposals are also
+    // enacted automatically by `initialize_block` once their enactment delay is over.
+    pub fn enact_proposal(&mut self, proposal_id: u32) -> Result<(), GovernanceError> {
+        let call = self.governance.enact_proposal(&mut self.system, proposal_id)?;
+        self.dispatch(call).map_err(GovernanceError::DispatchFailed)
+    }
+
+    // Propose a change to a staking parameter on the runtime parameter track
+    pub fn propose_parameter_change(
+        &mut self,
+        creator: u64,
+        change: ParameterChange<Runtime>,
+        description: String,
+    ) -> Result<u32, GovernanceError> {
+        let call = RuntimeCall::Staking(StakingCall::SetParameter(change));
+        self.governance.create_proposal_with_call(
+            &mut self.system,
+            &mut self.staking,
+            creator,
+            Track::RuntimeParameter,
+            description,
+            None,
+            Some(call),
+        )
+    }
+
+    // Run the start-of-block hooks of every pallet, dispatching the calls of proposals whose
+    // enactment block has been reached. A failing call does not stop the others.
+    pub fn initialize_block(&mut self) {
+        let block_number = self.system.block_number();
+        let calls =
+            self.governance.on_initialize(&mut self.system, &mut self.staking, block_number);
+        for (_, call) in calls {
+            let _ = self.dispatch(call);
+        }
+    }
+
+    // Run the end-of-block hooks of every pallet, telling the pallets that follow eras when
+    // one ends or starts, and collect the staking events of the block, then move on to the
+    // next block
+    pub fn finalize_block(&mut self) {
+        let block_number = self.system.block_number();
+        self.governance.on_finalize(&mut self.system, &mut self.staking, block_number);
+        self.staking.on_finalize_with_hooks(block_number, &mut self.offences);
+        self.staking.deposit_events(&mut self.system);
+        self.system.inc_block_number();
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 // Implement specific System configuration for the runtime
 impl SystemConfig for Runtime {
     type AccountId = u64;
+    type BlockNumber = u64;
+    type RuntimeEvent = RuntimeEvent;
+    type Hash = [u8; 32];
+
+    // Stand-in for a cryptographic hash such as blake2: deterministic, but not
+    // collision resistant, which is good enough for this simulation
+    fn hash(data: &[u8]) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        for (round, chunk) in output.chunks_mut(8).enumerate() {
+            let mut hasher = DefaultHasher::new();
+            round.hash(&mut hasher);
+            data.hash(&mut hasher);
+            chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+        }
+        output
+    }
+}
+
+// Sign `message` as `signer` with the stand-in scheme checked by `verify_signature`
+pub fn sign(signer: u64, message: &[u8]) -> [u8; 32] {
+    let mut data = signer.to_le_bytes().to_vec();
+    data.extend_from_slice(message);
+    Runtime::hash(&data)
 }
 
 // Implement specific Staking configuration for the runtime
 impl StakingConfig for Runtime {
     type Balance = u64;
+    const SLASH_DESTINATION: Option<u64> = Some(0); // The governance treasury
+    const BLOCKS_PER_ERA: u64 = 10;
+    const MIN_VALIDATOR_BOND: u64 = 100;
+    const MIN_NOMINATOR_BOND: u64 = 50;
+    const MAX_UNLOCKING_CHUNKS: u32 = 3;
+    const SLASH_DEFER_DURATION: u32 = 2;
+    const PAYOUT_HISTORY_DEPTH: u32 = 10;
+    const AUTHORING_POINTS: u32 = 20;
+    const FAST_UNSTAKE_ERAS: u32 = 2;
+    const REWARD_CLAIM_HORIZON: u32 = 84;
+    const UNCLAIMED_REWARD_DESTINATION: Option<u64> = Some(0); // The governance treasury
+    const INSURANCE_PREMIUM: Perbill = Perbill::from_percent(5);
+    const INSURANCE_COVER: Perbill = Perbill::from_percent(50);
+    const INSURANCE_CLAIM_CAP: u64 = 100;
+    type RuntimeEvent = RuntimeEvent;
+    type ElectionProvider = PhragmenElection;
+    type EraPayout = FixedPayout<100>;
+    type RewardSplitter = ProportionalSplit<0>;
 }
 
 // Implement specific Governance configuration for the runtime
 impl GovernanceConfig for Runtime {
-    // No additional types needed at this level
+    type RuntimeCall = RuntimeCall;
+    type RuntimeEvent = RuntimeEvent;
+    type TallyStrategy = LinearTally;
+    type Signature = [u8; 32];
+    const PROPOSAL_DEPOSIT: u64 = 10;
+    const QUORUM_FAILURE_PENALTY: u64 = 5;
+    const VOTE_LOCKING_PERIOD: u64 = 10;
+    const MAX_ACTIVE_PROPOSALS: u32 = 10;
+    const MAX_PROPOSALS_PER_WINDOW: u32 = 20;
+    const PROPOSAL_RATE_WINDOW: u64 = 10;
+    const ENACTMENT_DELAY: u64 = 5;
+    const VOTE_RETENTION_PERIOD: u64 = 50;
+    const ARCHIVE_PERIOD: u64 = 100;
+    const UNDELEGATION_COOLDOWN: u64 = 5;
+    const TREASURY_ACCOUNT: u64 = 0;
+    const PARTICIPATION_REWARD: u64 = 1;
+    const FAST_TRACK_QUORUM: u128 = 50;
+    const REVEAL_PERIOD: u64 = 10;
+    const MAX_TAGS: u32 = 5;
+    const MAX_TAG_LEN: u32 = 32;
+    const RESUBMISSION_COOLDOWN: u64 = 100;
+    const SECOND_DEPOSIT: u64 = 5;
+
+    // Stand-in for a signature scheme such as sr25519, using the account ID as public key: a
+    // signature is the hash of the signer and message. Anyone could forge one, which is good
+    // enough for this simulation.
+    fn verify_signature(signer: &u64, message: &[u8], signature: &[u8; 32]) -> bool {
+        *signature == sign(*signer, message)
+    }
+
+    // Little-endian account ID
+    fn encode_account(who: &u64) -> Vec<u8> {
+        who.to_le_bytes().to_vec()
+    }
+
+    // Tag byte per variant followed by the little-endian fields
+    fn encode_call(call: &RuntimeCall) -> Vec<u8> {
+        let mut data = Vec::new();
+        match call {
+            RuntimeCall::Staking(StakingCall::Transfer { from, to, amount }) => {
+                data.push(0);
+                data.extend_from_slice(&from.to_le_bytes());
+                data.extend_from_slice(&to.to_le_bytes());
+                data.extend_from_slice(&amount.to_le_bytes());
+            },
+            RuntimeCall::Staking(StakingCall::SetBalance { who, amount }) => {
+                data.push(1);
+                data.extend_from_slice(&who.to_le_bytes());
+                data.extend_from_slice(&amount.to_le_bytes());
+            },
+            RuntimeCall::Staking(StakingCall::SetParameter(change)) => {
+                data.push(2);
+                match change {
+                    ParameterChange::MinStake(amount) => {
+                        data.push(0);
+                        data.extend_from_slice(&amount.to_le_bytes());
+                    },
+                    ParameterChange::MaxValidators(count) => {
+                        data.push(1);
+                        data.extend_from_slice(&count.to_le_bytes());
+                    },
+                    ParameterChange::UnbondingPeriod(period) => {
+                        data.push(2);
+                        data.extend_from_slice(&period.to_le_bytes());
+                    },
+                    ParameterChange::MaxCommission(commission) => {
+                        data.push(3);
+                        data.extend_from_slice(&commission.deconstruct().to_le_bytes());
+                    },
+                    ParameterChange::MaxNominatorsPerValidator(count) => {
+                        data.push(4);
+                        data.extend_from_slice(&count.to_le_bytes());
+                    },
+                    ParameterChange::SlashRewardFraction(fraction) => {
+                        data.push(5);
+                        data.extend_from_slice(&fraction.deconstruct().to_le_bytes());
+                    },
+                    ParameterChange::MinValidators(count) => {
+                        data.push(6);
+                        data.extend_from_slice(&count.to_le_bytes());
+                    },
+                    ParameterChange::MinUptime(uptime) => {
+                        data.push(7);
+                        data.extend_from_slice(&uptime.deconstruct().to_le_bytes());
+                    },
+                    ParameterChange::MinCommission(commission) => {
+                        data.push(8);
+                        data.extend_from_slice(&commission.deconstruct().to_le_bytes());
+                    },
+                }
+            },
+            RuntimeCall::Governance(GovernanceCall::SetProposerAllowlist(members)) => {
+                data.push(3);
+                if let Some(members) = members {
+                    data.extend_from_slice(&(members.len() as u64).to_le_bytes());
+                    for member in members {
+                        data.extend_from_slice(&member.to_le_bytes());
+                    }
+                }
+            },
+            RuntimeCall::Governance(GovernanceCall::AddProposer(who)) => {
+                data.push(4);
+                data.extend_from_slice(&who.to_le_bytes());
+            },
+            RuntimeCall::Governance(GovernanceCall::RemoveProposer(who)) => {
+                data.push(5);
+                data.extend_from_slice(&who.to_le_bytes());
+            },
+            RuntimeCall::Staking(StakingCall::CancelSlash(slash_id)) => {
+                data.push(6);
+                data.extend_from_slice(&slash_id.to_le_bytes());
+            },
+        }
+        data
+    }
+
+    fn track_info(track: Track) -> TrackInfo<u64> {
+        match track {
+            Track::Treasury => TrackInfo {
+                voting_period: 200,
+                quorum: 1_000,
+                approval: ApprovalCurve::Fixed(60),
+                required_seconds: 0,
+            },
+            Track::RuntimeParameter => TrackInfo {
+                voting_period: 300,
+                quorum: 2_000,
+                approval: ApprovalCurve::PositiveTurnoutBias,
+                required_seconds: 2,
+            },
+            Track::Text => TrackInfo {
+                voting_period: 100,
+                quorum: 100,
+                approval: ApprovalCurve::Fixed(50),
+                required_seconds: 0,
+            },
+        }
+    }
+}
+
+// Implement specific Council configuration for the runtime
+impl CouncilConfig for Runtime {
+    const MOTION_DURATION: u64 = 20;
+    const REFERENDUM_APPROVAL: ApprovalCurve = ApprovalCurve::NegativeTurnoutBias;
+    type RuntimeEvent = RuntimeEvent;
+}
+
+// Implement specific Committee configuration for the runtime
+impl CommitteeConfig for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+}
+
+// Implement specific Pools configuration for the runtime
+impl PoolsConfig for Runtime {
+    const MIN_JOIN_BOND: u64 = 10;
+    type RuntimeEvent = RuntimeEvent;
+
+    // Pool accounts sit far above the accounts used in tests
+    fn pool_account(pool_id: u32) -> u64 {
+        1_000_000 + u64::from(pool_id)
+    }
+}
+
+// Implement specific Offences configuration for the runtime
+impl OffencesConfig for Runtime {
+    const REPORT_HISTORY_DEPTH: u32 = 2;
+    type RuntimeEvent = RuntimeEvent;
+
+    fn slash_fraction(kind: OffenceKind) -> Perbill {
+        match kind {
+            OffenceKind::Equivocation => Perbill::from_percent(10),
+            OffenceKind::Unavailability => Perbill::from_percent(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use governance::{AccountVote, Conviction, ProposalStatus, Vote};
+
+    #[test]
+    fn test_enact_proposal_should_work() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let treasury = 100u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        runtime.staking.stake(alice, 500).unwrap();
+        runtime.staking.set_balance(treasury, 1000);
+
+        // Propose paying Bob out of the treasury
+        let call = RuntimeCall::Staking(StakingCall::Transfer {
+            from: treasury,
+            to: bob,
+            amount: 250,
+        });
+        let proposal_id = runtime
+            .governance
+            .create_proposal_with_call(
+                &mut runtime.system,
+                &mut runtime.staking,
+                alice,
+                Track::Text,
+                "Pay Bob for his work".to_string(),
+                None,
+                Some(call),
+            )
+            .unwrap();
+
+        // Nothing can be enacted before approval
+        assert!(runtime.enact_proposal(proposal_id).is_err());
+
+        let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+        runtime
+            .governance
+            .vote(&mut runtime.system, &mut runtime.staking, alice, proposal_id, aye)
+            .unwrap();
+        let end_block = runtime.governance.get_proposal(proposal_id).unwrap().end_block();
+        runtime.system.set_block_number(end_block);
+        runtime
+            .governance
+            .finalize_proposal(&mut runtime.system, &mut runtime.staking, proposal_id)
+            .unwrap();
+
+        // The call can only be enacted once the enactment delay is over
+        let result = runtime.enact_proposal(proposal_id);
+        assert_eq!(result, Err(GovernanceError::EnactmentDelayNotOver));
+        for _ in 0..Runtime::ENACTMENT_DELAY {
+            runtime.system.inc_block_number();
+        }
+
+        // Enactment dispatches the call exactly once
+        runtime.enact_proposal(proposal_id).unwrap();
+        assert_eq!(runtime.staking.get_free_balance(bob), 250);
+        assert_eq!(runtime.staking.get_free_balance(treasury), 750);
+        assert!(matches!(
+            runtime.governance.get_proposal(proposal_id).unwrap().status(),
+            ProposalStatus::Enacted
+        ));
+        assert!(runtime.enact_proposal(proposal_id).is_err());
+    }
+
+    #[test]
+    fn test_veto_blocks_enactment() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let guardian = 50u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        runtime.staking.stake(alice, 500).unwrap();
+        runtime
+            .governance
+            .set_technical_committee(&mut runtime.system, RuntimeOrigin::Root, vec![guardian])
+            .unwrap();
+
+        let call = RuntimeCall::Staking(StakingCall::SetBalance { who: bob, amount: 1_000_000 });
+        let proposal_id = runtime
+            .governance
+            .create_proposal_with_call(
+                &mut runtime.system,
+                &mut runtime.staking,
+                alice,
+                Track::Text,
+                "Mint Bob a fortune".to_string(),
+                None,
+                Some(call),
+            )
+            .unwrap();
+
+        // Only approved proposals can be vetoed
+        let origin = RuntimeOrigin::Signed(guardian);
+        let result = runtime.governance.veto_proposal(&mut runtime.system, origin, proposal_id);
+        assert_eq!(result, Err(GovernanceError::ProposalNotApproved));
+
+        let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+        runtime
+            .governance
+            .vote(&mut runtime.system, &mut runtime.staking, alice, proposal_id, aye)
+            .unwrap();
+        let end_block = runtime.governance.get_proposal(proposal_id).unwrap().end_block();
+        runtime.system.set_block_number(end_block);
+        runtime
+            .governance
+            .finalize_proposal(&mut runtime.system, &mut runtime.staking, proposal_id)
+            .unwrap();
+
+        // Only technical committee members can veto
+        let origin = RuntimeOrigin::Signed(alice);
+        let result = runtime.governance.veto_proposal(&mut runtime.system, origin, proposal_id);
+        assert_eq!(result, Err(GovernanceError::NotTechnicalCommittee));
+
+        let origin = RuntimeOrigin::Signed(guardian);
+        runtime.governance.veto_proposal(&mut runtime.system, origin, proposal_id).unwrap();
+        let proposal = runtime.governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.status(), &ProposalStatus::Vetoed);
+        assert_eq!(proposal.vetoed_by(), Some(&guardian));
+
+        // A vetoed proposal can no longer be enacted
+        let result = runtime.enact_proposal(proposal_id);
+        assert_eq!(result, Err(GovernanceError::ProposalNotApproved));
+        assert_eq!(runtime.staking.get_free_balance(bob), 0);
+    }
+
+    #[test]
+    fn test_enactment_delay() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let guardian = 50u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        runtime.staking.stake(alice, 500).unwrap();
+        runtime
+            .governance
+            .set_technical_committee(&mut runtime.system, RuntimeOrigin::Root, vec![guardian])
+            .unwrap();
+
+        let mut proposal_ids = Vec::new();
+        for who in [bob, charlie] {
+            let call = RuntimeCall::Staking(StakingCall::SetBalance { who, amount: 500 });
+            let proposal_id = runtime
+                .governance
+                .create_proposal_with_call(
+                    &mut runtime.system,
+                    &mut runtime.staking,
+                    alice,
+                    Track::Text,
+                    format!("Give {} some balance", who),
+                    None,
+                    Some(call),
+                )
+                .unwrap();
+            let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+            runtime
+                .governance
+                .vote(&mut runtime.system, &mut runtime.staking, alice, proposal_id, aye)
+                .unwrap();
+            proposal_ids.push(proposal_id);
+        }
+        let end_block = runtime.governance.get_proposal(proposal_ids[0]).unwrap().end_block();
+        runtime.system.set_block_number(end_block);
+        for proposal_id in &proposal_ids {
+            runtime
+                .governance
+                .finalize_proposal(&mut runtime.system, &mut runtime.staking, *proposal_id)
+                .unwrap();
+        }
+
+        // Approval schedules the call instead of dispatching it
+        let enactment_block = end_block + Runtime::ENACTMENT_DELAY;
+        let proposal = runtime.governance.get_proposal(proposal_ids[0]).unwrap();
+        assert_eq!(proposal.enactment_block(), Some(enactment_block));
+        assert!(matches!(
+            runtime.system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::EnactmentScheduled {
+                proposal_id: 1,
+                at,
+            })) if *at == enactment_block
+        ));
+
+        // The technical committee uses the delay to veto the second proposal
+        let origin = RuntimeOrigin::Signed(guardian);
+        runtime.governance.veto_proposal(&mut runtime.system, origin, proposal_ids[1]).unwrap();
+
+        // Nothing happens before the enactment block
+        while runtime.system.block_number() < enactment_block {
+            runtime.initialize_block();
+            runtime.finalize_block();
+        }
+        assert_eq!(runtime.staking.get_free_balance(bob), 0);
+
+        // At the enactment block the first call is dispatched and the vetoed one is skipped
+        runtime.initialize_block();
+        assert_eq!(runtime.staking.get_free_balance(bob), 500);
+        assert_eq!(runtime.staking.get_free_balance(charlie), 0);
+        let status = runtime.governance.get_proposal(proposal_ids[0]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Enacted);
+        let status = runtime.governance.get_proposal(proposal_ids[1]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Vetoed);
+    }
+
+    #[test]
+    fn test_expired_proposals_are_finalized_automatically() {
+        let alice = 1u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        runtime.staking.stake(alice, 500).unwrap();
+
+        let mut proposal_ids = Vec::new();
+        for voting_period in [5, 10] {
+            let proposal_id = runtime
+                .governance
+                .create_proposal(
+                    &mut runtime.system,
+                    &mut runtime.staking,
+                    alice,
+                    Track::Text,
+                    format!("Automatic after {} blocks", voting_period),
+                    Some(voting_period),
+                )
+                .unwrap();
+            let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+            runtime
+                .governance
+                .vote(&mut runtime.system, &mut runtime.staking, alice, proposal_id, aye)
+                .unwrap();
+            proposal_ids.push(proposal_id);
+        }
+
+        // The first proposal is finalized at the end of its last voting block
+        while runtime.system.block_number() <= 5 {
+            runtime.finalize_block();
+        }
+        let status = runtime.governance.get_proposal(proposal_ids[0]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Approved);
+        let status = runtime.governance.get_proposal(proposal_ids[1]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Active);
+        assert!(matches!(
+            runtime.system.events().last(),
+            Some(RuntimeEvent::Governance(GovernanceEvent::ProposalFinalized {
+                proposal_id: 0,
+                status: ProposalStatus::Approved,
+            }))
+        ));
+
+        while runtime.system.block_number() <= 10 {
+            runtime.finalize_block();
+        }
+        let status = runtime.governance.get_proposal(proposal_ids[1]).unwrap().status();
+        assert_eq!(status, &ProposalStatus::Approved);
+    }
+
+    #[test]
+    fn test_era_hooks_prune_offence_reports() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let equivocation = OffenceKind::Equivocation;
+
+        let mut runtime = Runtime::new();
+        for who in [alice, bob] {
+            runtime.staking.set_balance(who, 1000);
+        }
+        runtime.staking.stake(alice, 500).unwrap();
+        runtime.staking.validate(alice).unwrap();
+        runtime.staking.set_keys(alice, vec![1]).unwrap();
+        let blocks_per_era = Runtime::BLOCKS_PER_ERA;
+        while runtime.system.block_number() < blocks_per_era {
+            runtime.finalize_block();
+        }
+        runtime
+            .offences
+            .report_offence(
+                &mut runtime.system,
+                &mut runtime.staking,
+                bob,
+                equivocation,
+                alice,
+                vec![7],
+            )
+            .unwrap();
+
+        // The report outlives its era for the history depth, then the era hook drops it
+        let depth = u64::from(Runtime::REPORT_HISTORY_DEPTH);
+        while runtime.system.block_number() < blocks_per_era * (depth + 1) {
+            runtime.finalize_block();
+        }
+        assert!(runtime.offences.get_report(equivocation, &alice, 1).is_some());
+        while runtime.system.block_number() < blocks_per_era * (depth + 2) {
+            runtime.finalize_block();
+        }
+        assert_eq!(runtime.staking.current_era, 4);
+        assert!(runtime.offences.get_report(equivocation, &alice, 1).is_none());
+    }
+
+    #[test]
+    fn test_parameter_change_proposal() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 5000);
+        runtime.staking.stake(alice, 3000).unwrap();
+        runtime.staking.set_balance(bob, 100);
+        runtime.staking.set_balance(charlie, 100);
+
+        let change = ParameterChange::UnbondingPeriod(50);
+        let proposal_id = runtime
+            .propose_parameter_change(alice, change, "Longer unbonding".to_string())
+            .unwrap();
+        let proposal = runtime.governance.get_proposal(proposal_id).unwrap();
+        assert_eq!(proposal.track(), Track::RuntimeParameter);
+
+        // Parameter changes need two seconds before they can be voted on
+        for seconder in [bob, charlie] {
+            runtime
+                .governance
+                .second(&mut runtime.system, &mut runtime.staking, seconder, proposal_id)
+                .unwrap();
+        }
+
+        let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+        runtime
+            .governance
+            .vote(&mut runtime.system, &mut runtime.staking, alice, proposal_id, aye)
+            .unwrap();
+        let end_block = runtime.governance.get_proposal(proposal_id).unwrap().end_block();
+        runtime.system.set_block_number(end_block);
+        runtime
+            .governance
+            .finalize_proposal(&mut runtime.system, &mut runtime.staking, proposal_id)
+            .unwrap();
+
+        // Enactment writes the new value into the staking parameters
+        assert_eq!(runtime.staking.parameters.unbonding_period, 0);
+        for _ in 0..Runtime::ENACTMENT_DELAY {
+            runtime.system.inc_block_number();
+        }
+        runtime.enact_proposal(proposal_id).unwrap();
+        assert_eq!(runtime.staking.parameters.unbonding_period, 50);
+    }
+
+    #[test]
+    fn test_proposer_allowlist() {
+        let alice = 1u64;
+        let bob = 2u64;
+
+        let mut runtime = Runtime::new();
+        runtime.staking.set_balance(alice, 1000);
+        runtime.staking.stake(alice, 500).unwrap();
+        runtime.staking.set_balance(bob, 1000);
+
+        // Start out permissioned, with only Alice allowed to propose
+        let genesis = GovernanceCall::SetProposerAllowlist(Some(vec![alice]));
+        runtime.dispatch(RuntimeCall::Governance(genesis)).unwrap();
+        let result = runtime.governance.create_proposal(
+            &mut runtime.system,
+            &mut runtime.staking,
+            bob,
+            Track::Text,
+            "Let me in".to_string(),
+            None,
+        );
+        assert_eq!(result, Err(GovernanceError::NotAllowedToPropose));
+
+        // The allowlist is opened up through an approved proposal
+        let call = RuntimeCall::Governance(GovernanceCall::SetProposerAllowlist(None));
+        let proposal_id = runtime
+            .governance
+            .create_proposal_with_call(
+                &mut runtime.system,
+                &mut runtime.staking,
+                alice,
+                Track::Text,
+                "Open governance".to_string(),
+                None,
+                Some(call),
+            )
+            .unwrap();
+        let aye = AccountVote { vote: Vote::Aye, conviction: Conviction::Locked1x };
+        runtime
+            .governance
+            .vote(&mut runtime.system, &mut runtime.staking, alice, proposal_id, aye)
+            .unwrap();
+        let end_block = runtime.governance.get_proposal(proposal_id).unwrap().end_block();
+        runtime.system.set_block_number(end_block);
+        runtime
+            .governance
+            .finalize_proposal(&mut runtime.system, &mut runtime.staking, proposal_id)
+            .unwrap();
+        for _ in 0..Runtime::ENACTMENT_DELAY {
+            runtime.system.inc_block_number();
+        }
+        runtime.enact_proposal(proposal_id).unwrap();
+
+        assert_eq!(runtime.governance.proposer_allowlist(), None);
+        runtime
+            .governance
+            .create_proposal(
+                &mut runtime.system,
+                &mut runtime.staking,
+                bob,
+                Track::Text,
+                "Let me in".to_string(),
+                None,
+            )
+            .unwrap();
+    }
 }
@@ -0,0 +1,205 @@
+use crate::staking::{EraHooks, Perbill, StakingConfig, StakingError, StakingPallet};
+use crate::system::{SystemConfig, SystemPallet};
+use std::collections::HashMap;
+use std::fmt;
+
+pub trait OffencesConfig: StakingConfig + Sized {
+    // Part of the offender's exposed stake slashed for each kind of offence
+    fn slash_fraction(kind: OffenceKind) -> Perbill;
+    // Number of eras the reports of an era's offences are kept for once it has ended
+    const REPORT_HISTORY_DEPTH: u32;
+    // The runtime event type, able to carry offence events into the system pallet
+    type RuntimeEvent: From<OffencesEvent<Self>> + Into<<Self as SystemConfig>::RuntimeEvent>;
+}
+
+// Kinds of validator misbehaviour, like the offences of `pallet_offences`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OffenceKind {
+    Equivocation,   // Signing two conflicting blocks or votes
+    Unavailability, // Going offline instead of authoring blocks
+}
+
+pub struct OffenceReport<T: OffencesConfig> {
+    pub reporter: T::AccountId,
+    pub evidence_hash: T::Hash, // Hash of the evidence submitted with the report
+    pub slash_id: u32,          // Staking slash the report was turned into
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffencesError {
+    EmptyEvidence,
+    SelfReport,
+    DuplicateReport,
+    Staking(StakingError), // An error returned by the staking pallet
+}
+
+impl fmt::Display for OffencesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            OffencesError::EmptyEvidence => "A report must include evidence",
+            OffencesError::SelfReport => "Validators cannot report themselves",
+            OffencesError::DuplicateReport => "This offence has already been reported",
+            OffencesError::Staking(error) => return write!(f, "Staking error: {}", error),
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for OffencesError {}
+
+pub enum OffencesEvent<T: OffencesConfig> {
+    OffenceReported {
+        kind: OffenceKind,
+        offender: T::AccountId,
+        era_index: u32,
+        reporter: T::AccountId,
+        slash_id: u32,
+    },
+}
+
+pub struct OffencesPallet<T: OffencesConfig> {
+    // (kind, offender, era) -> the report of that offence; each is reported once
+    reports: HashMap<(OffenceKind, T::AccountId, u32), OffenceReport<T>>,
+}
+
+impl<T: OffencesConfig> OffencesPallet<T> {
+    pub fn new() -> Self {
+        Self { reports: HashMap::new() }
+    }
+
+    // Report an offence an active validator committed in the current era. The evidence is not
+    // checked, only hashed for the record. The offence becomes a staking slash a share of
+    // which rewards the reporter once applied. Returns the ID of the slash.
+    pub fn report_offence(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        reporter: T::AccountId,
+        kind: OffenceKind,
+        offender: T::AccountId,
+        evidence: Vec<u8>,
+    ) -> Result<u32, OffencesError> {
+        if evidence.is_empty() {
+            return Err(OffencesError::EmptyEvidence);
+        }
+        if reporter == offender {
+            return Err(OffencesError::SelfReport);
+        }
+        let era_index = staking.current_era;
+        let key = (kind, offender.clone(), era_index);
+        if self.reports.contains_key(&key) {
+            return Err(OffencesError::DuplicateReport);
+        }
+
+        let fraction = T::slash_fraction(kind);
+        let slash_id = staking
+            .report_slash(offender.clone(), fraction, vec![reporter.clone()])
+            .map_err(OffencesError::Staking)?;
+        let evidence_hash = T::hash(&evidence);
+        let report = OffenceReport { reporter: reporter.clone(), evidence_hash, slash_id };
+        self.reports.insert(key, report);
+
+        let event =
+            OffencesEvent::OffenceReported { kind, offender, era_index, reporter, slash_id };
+        Self::deposit_event(system, event);
+        Ok(slash_id)
+    }
+
+    // Get the report of an offence, if it was reported
+    pub fn get_report(
+        &self,
+        kind: OffenceKind,
+        offender: &T::AccountId,
+        era_index: u32,
+    ) -> Option<&OffenceReport<T>> {
+        self.reports.get(&(kind, offender.clone(), era_index))
+    }
+
+    // Convert an offences event into the runtime event type and store it in the system pallet
+    fn deposit_event(system: &mut SystemPallet<T>, event: OffencesEvent<T>) {
+        let event: <T as OffencesConfig>::RuntimeEvent = event.into();
+        system.deposit_event(event.into());
+    }
+}
+
+// Offences can only be reported in the era they were committed in, so older reports are only
+// kept as a record until they fall out of the history depth
+impl<T: OffencesConfig> EraHooks<T> for OffencesPallet<T> {
+    fn on_era_end(&mut self, _staking: &mut StakingPallet<T>, era_index: u32) {
+        self.reports
+            .retain(|(_, _, reported_era), _| reported_era + T::REPORT_HISTORY_DEPTH > era_index);
+    }
+}
+
+impl<T: OffencesConfig> Default for OffencesPallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Runtime;
+
+    #[test]
+    fn test_offence_reports() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let treasury = Runtime::SLASH_DESTINATION.unwrap();
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut offences = OffencesPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(alice, 500).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        let equivocation = OffenceKind::Equivocation;
+        let evidence = vec![7];
+        let result = offences.report_offence(
+            &mut system,
+            &mut staking,
+            bob,
+            equivocation,
+            alice,
+            evidence.clone(),
+        );
+        assert_eq!(result, Err(OffencesError::Staking(StakingError::NotActiveValidator)));
+        staking.on_finalize(9);
+
+        // Each offence is reported once per era
+        let slash_id = offences
+            .report_offence(&mut system, &mut staking, bob, equivocation, alice, evidence.clone())
+            .unwrap();
+        let result = offences.report_offence(
+            &mut system,
+            &mut staking,
+            charlie,
+            equivocation,
+            alice,
+            evidence.clone(),
+        );
+        assert_eq!(result, Err(OffencesError::DuplicateReport));
+        let unavailability = OffenceKind::Unavailability;
+        offences
+            .report_offence(&mut system, &mut staking, charlie, unavailability, alice, evidence)
+            .unwrap();
+        let result =
+            offences.report_offence(&mut system, &mut staking, alice, equivocation, alice, vec![]);
+        assert_eq!(result, Err(OffencesError::EmptyEvidence));
+        assert_eq!(offences.get_report(equivocation, &alice, 1).unwrap().slash_id, slash_id);
+        assert_eq!(staking.pending_slashes().len(), 2);
+
+        // Only the larger slash is applied, a tenth of it going to its reporter
+        staking.on_finalize(19);
+        staking.on_finalize(29);
+        assert_eq!(staking.get_staked_balance(alice), 450u64);
+        assert_eq!(staking.get_free_balance(bob), 1005u64);
+        assert_eq!(staking.get_free_balance(charlie), 1000u64);
+        assert_eq!(staking.get_free_balance(treasury), 45u64);
+    }
+}
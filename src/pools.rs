@@ -0,0 +1,541 @@
+use crate::staking::{StakingConfig, StakingError, StakingPallet, UnlockChunk};
+use crate::system::{SystemConfig, SystemPallet};
+use num::traits::{CheckedAdd, Zero};
+use std::collections::HashMap;
+use std::fmt;
+
+pub trait PoolsConfig: StakingConfig + Sized {
+    // Smallest amount an account can create or join a pool with
+    const MIN_JOIN_BOND: Self::Balance;
+    // Account a pool bonds and receives rewards as, derived from its ID
+    fn pool_account(pool_id: u32) -> Self::AccountId;
+    // The runtime event type, able to carry pool events into the system pallet
+    type RuntimeEvent: From<PoolsEvent<Self>> + Into<<Self as SystemConfig>::RuntimeEvent>;
+}
+
+// Scale of the reward counter, so rewards per point keep their precision
+const REWARD_COUNTER_SCALE: u128 = 1_000_000_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolState {
+    Open,    // Anyone can join
+    Blocked, // No one can join, but members can still leave
+}
+
+// Accounts managing a pool, like `pallet_nomination_pools::PoolRoles`
+pub struct PoolRoles<T: PoolsConfig> {
+    pub root: T::AccountId,      // Can change every role and the pool state
+    pub nominator: T::AccountId, // Chooses the validators the pool backs
+    pub bouncer: T::AccountId,   // Can open and block the pool
+}
+
+pub struct Pool<T: PoolsConfig> {
+    roles: PoolRoles<T>,
+    state: PoolState,
+    points: u128, // Total points issued to members, each a share of the pool's stake
+    reward_counter: u128, // Rewards earned per point so far, scaled by REWARD_COUNTER_SCALE
+    recorded_balance: u128, // Free balance of the pool account last accounted for
+}
+
+impl<T: PoolsConfig> Pool<T> {
+    // Get the accounts managing the pool
+    pub fn roles(&self) -> &PoolRoles<T> {
+        &self.roles
+    }
+
+    // Get whether the pool can be joined
+    pub fn state(&self) -> PoolState {
+        self.state
+    }
+
+    // Get the total points issued to members
+    pub fn points(&self) -> u128 {
+        self.points
+    }
+}
+
+pub struct PoolMember<T: PoolsConfig> {
+    pub pool_id: u32,
+    pub points: u128,
+    pub last_reward_counter: u128, // Pool reward counter when the member was last paid
+    pub unbonding: Vec<UnlockChunk<T>>, // Funds the member is unbonding from the pool
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolsError {
+    PoolNotFound,
+    NotMember,
+    AlreadyMember,
+    PoolBlocked,
+    BondTooLow,
+    NotEnoughPoints,
+    NothingToWithdraw,
+    NotRoot,
+    NotNominator,
+    NotBouncer,
+    Overflow,
+    Staking(StakingError), // An error returned by the staking pallet
+}
+
+impl fmt::Display for PoolsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            PoolsError::PoolNotFound => "No pool found with the given ID",
+            PoolsError::NotMember => "Account is not a pool member",
+            PoolsError::AlreadyMember => "Account is already a member of a pool",
+            PoolsError::PoolBlocked => "Pool is not open to new members",
+            PoolsError::BondTooLow => "Amount is below the minimum join bond",
+            PoolsError::NotEnoughPoints => "Member does not have that many points",
+            PoolsError::NothingToWithdraw => "No unbonded funds have matured yet",
+            PoolsError::NotRoot => "Only the pool root can do this",
+            PoolsError::NotNominator => "Only the pool root or nominator can do this",
+            PoolsError::NotBouncer => "Only the pool root or bouncer can do this",
+            PoolsError::Overflow => "Arithmetic overflow",
+            PoolsError::Staking(error) => return write!(f, "Staking error: {}", error),
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for PoolsError {}
+
+pub enum PoolsEvent<T: PoolsConfig> {
+    Created {
+        pool_id: u32,
+        root: T::AccountId,
+    },
+    Joined {
+        pool_id: u32,
+        member: T::AccountId,
+        amount: T::Balance,
+        points: u128,
+    },
+    PaidOut {
+        pool_id: u32,
+        member: T::AccountId,
+        amount: T::Balance,
+    },
+    Unbonded {
+        pool_id: u32,
+        member: T::AccountId,
+        points: u128,
+        amount: T::Balance,
+    },
+    Withdrawn {
+        pool_id: u32,
+        member: T::AccountId,
+        amount: T::Balance,
+    },
+    StateChanged {
+        pool_id: u32,
+        state: PoolState,
+    },
+    RolesUpdated {
+        pool_id: u32,
+    },
+}
+
+pub struct PoolsPallet<T: PoolsConfig> {
+    pools: HashMap<u32, Pool<T>>,
+    members: HashMap<T::AccountId, PoolMember<T>>, // Each account is in at most one pool
+    next_pool_id: u32,
+}
+
+impl<T: PoolsConfig> PoolsPallet<T> {
+    pub fn new() -> Self {
+        Self {
+            pools: HashMap::new(),
+            members: HashMap::new(),
+            next_pool_id: 0,
+        }
+    }
+
+    // Create a pool bonding `amount` from the creator, who becomes its root and first member
+    pub fn create(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        creator: T::AccountId,
+        amount: T::Balance,
+        nominator: T::AccountId,
+        bouncer: T::AccountId,
+    ) -> Result<u32, PoolsError> {
+        if self.members.contains_key(&creator) {
+            return Err(PoolsError::AlreadyMember);
+        }
+        if amount < T::MIN_JOIN_BOND {
+            return Err(PoolsError::BondTooLow);
+        }
+        if amount > staking.get_transferable_balance(creator.clone()) {
+            return Err(PoolsError::Staking(StakingError::InsufficientBalance));
+        }
+
+        let pool_id = self.next_pool_id;
+        let roles = PoolRoles { root: creator.clone(), nominator, bouncer };
+        let pool = Pool {
+            roles,
+            state: PoolState::Open,
+            points: 0,
+            reward_counter: 0,
+            recorded_balance: staking.get_free_balance(T::pool_account(pool_id)).into(),
+        };
+        self.pools.insert(pool_id, pool);
+        self.next_pool_id += 1;
+        Self::deposit_event(system, PoolsEvent::Created { pool_id, root: creator.clone() });
+
+        if let Err(error) = self.join(system, staking, creator, pool_id, amount) {
+            self.pools.remove(&pool_id);
+            self.next_pool_id -= 1;
+            return Err(error);
+        }
+        Ok(pool_id)
+    }
+
+    // Join an open pool, bonding `amount` through it in exchange for points
+    pub fn join(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        pool_id: u32,
+        amount: T::Balance,
+    ) -> Result<(), PoolsError> {
+        if self.members.contains_key(&who) {
+            return Err(PoolsError::AlreadyMember);
+        }
+        if amount < T::MIN_JOIN_BOND {
+            return Err(PoolsError::BondTooLow);
+        }
+        let pool = self.pools.get_mut(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+        if pool.state != PoolState::Open {
+            return Err(PoolsError::PoolBlocked);
+        }
+
+        // Points are issued at the current points-to-stake rate, 1:1 for an empty pool
+        let account = T::pool_account(pool_id);
+        let pool_stake: u128 = staking.get_staked_balance(account.clone()).into();
+        let value: u128 = amount.into();
+        let points = match pool.points {
+            0 => value,
+            _ if pool_stake == 0 => value,
+            total => value.checked_mul(total).ok_or(PoolsError::Overflow)? / pool_stake,
+        };
+
+        staking.transfer(who.clone(), account.clone(), amount).map_err(PoolsError::Staking)?;
+        if let Err(error) = staking.stake(account.clone(), amount) {
+            let _ = staking.transfer(account, who, amount);
+            return Err(PoolsError::Staking(error));
+        }
+
+        Self::update_rewards(pool, staking, pool_id);
+        pool.points = pool.points.saturating_add(points);
+        let member = PoolMember {
+            pool_id,
+            points,
+            last_reward_counter: pool.reward_counter,
+            unbonding: Vec::new(),
+        };
+        self.members.insert(who.clone(), member);
+
+        let event = PoolsEvent::Joined { pool_id, member: who, amount, points };
+        Self::deposit_event(system, event);
+        Ok(())
+    }
+
+    // Account for rewards paid to the pool account since it was last checked
+    fn update_rewards(pool: &mut Pool<T>, staking: &StakingPallet<T>, pool_id: u32) {
+        let balance: u128 = staking.get_free_balance(T::pool_account(pool_id)).into();
+        if balance > pool.recorded_balance && pool.points > 0 {
+            let earned = balance - pool.recorded_balance;
+            let per_point = earned.saturating_mul(REWARD_COUNTER_SCALE) / pool.points;
+            pool.reward_counter = pool.reward_counter.saturating_add(per_point);
+        }
+        pool.recorded_balance = balance;
+    }
+
+    // Get the rewards a member can claim, in proportion to its points
+    pub fn pending_rewards(&self, staking: &StakingPallet<T>, who: &T::AccountId) -> u128 {
+        let member = match self.members.get(who) {
+            Some(member) => member,
+            None => return 0,
+        };
+        let pool = match self.pools.get(&member.pool_id) {
+            Some(pool) => pool,
+            None => return 0,
+        };
+
+        let balance: u128 = staking.get_free_balance(T::pool_account(member.pool_id)).into();
+        let mut reward_counter = pool.reward_counter;
+        if balance > pool.recorded_balance && pool.points > 0 {
+            let earned = balance - pool.recorded_balance;
+            let per_point = earned.saturating_mul(REWARD_COUNTER_SCALE) / pool.points;
+            reward_counter = reward_counter.saturating_add(per_point);
+        }
+        let owed = reward_counter.saturating_sub(member.last_reward_counter);
+        member.points.saturating_mul(owed) / REWARD_COUNTER_SCALE
+    }
+
+    // Pay a member the rewards its points earned since it was last paid
+    pub fn claim_payout(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+    ) -> Result<T::Balance, PoolsError> {
+        let member = self.members.get_mut(&who).ok_or(PoolsError::NotMember)?;
+        let pool_id = member.pool_id;
+        let pool = self.pools.get_mut(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+        Self::update_rewards(pool, staking, pool_id);
+
+        let owed = pool.reward_counter.saturating_sub(member.last_reward_counter);
+        let share = member.points.saturating_mul(owed) / REWARD_COUNTER_SCALE;
+        let amount = T::Balance::try_from(share).map_err(|_| PoolsError::Overflow)?;
+        if !amount.is_zero() {
+            staking
+                .transfer(T::pool_account(pool_id), who.clone(), amount)
+                .map_err(PoolsError::Staking)?;
+            Self::update_rewards(pool, staking, pool_id);
+        }
+        member.last_reward_counter = pool.reward_counter;
+
+        Self::deposit_event(system, PoolsEvent::PaidOut { pool_id, member: who, amount });
+        Ok(amount)
+    }
+
+    // Redeem `points` for their share of the pool's stake, which starts unbonding at
+    // `current_block`. Pending rewards are paid out first.
+    pub fn unbond(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        points: u128,
+        current_block: T::BlockNumber,
+    ) -> Result<T::Balance, PoolsError> {
+        let member = self.members.get(&who).ok_or(PoolsError::NotMember)?;
+        if points == 0 || points > member.points {
+            return Err(PoolsError::NotEnoughPoints);
+        }
+        self.claim_payout(system, staking, who.clone())?;
+
+        let member = self.members.get_mut(&who).ok_or(PoolsError::NotMember)?;
+        let pool_id = member.pool_id;
+        let pool = self.pools.get_mut(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+        let account = T::pool_account(pool_id);
+        let pool_stake: u128 = staking.get_staked_balance(account.clone()).into();
+        let value = points.checked_mul(pool_stake).ok_or(PoolsError::Overflow)? / pool.points;
+        let amount = T::Balance::try_from(value).map_err(|_| PoolsError::Overflow)?;
+
+        staking.unbond(account, amount, current_block).map_err(PoolsError::Staking)?;
+        let unlock_at = current_block
+            .checked_add(&staking.parameters.unbonding_period)
+            .ok_or(PoolsError::Overflow)?;
+        member.points -= points;
+        member.unbonding.push(UnlockChunk { value: amount, unlock_at });
+        pool.points -= points;
+
+        let event = PoolsEvent::Unbonded { pool_id, member: who, points, amount };
+        Self::deposit_event(system, event);
+        Ok(amount)
+    }
+
+    // Pay a member the funds it unbonded that have matured by `current_block`. A member
+    // with no points and nothing left unbonding leaves the pool.
+    pub fn withdraw_unbonded(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        current_block: T::BlockNumber,
+    ) -> Result<T::Balance, PoolsError> {
+        let member = self.members.get_mut(&who).ok_or(PoolsError::NotMember)?;
+        let pool_id = member.pool_id;
+        let pool = self.pools.get_mut(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+
+        let mut amount = T::Balance::zero();
+        for chunk in member.unbonding.iter().filter(|chunk| chunk.unlock_at <= current_block) {
+            amount = amount.checked_add(&chunk.value).ok_or(PoolsError::Overflow)?;
+        }
+        if amount.is_zero() {
+            return Err(PoolsError::NothingToWithdraw);
+        }
+
+        // Withdrawn stake is not a reward, so it is accounted for before being paid out
+        let account = T::pool_account(pool_id);
+        Self::update_rewards(pool, staking, pool_id);
+        let _ = staking.withdraw_unbonded(account.clone(), current_block);
+        staking.transfer(account, who.clone(), amount).map_err(PoolsError::Staking)?;
+        pool.recorded_balance = staking.get_free_balance(T::pool_account(pool_id)).into();
+
+        member.unbonding.retain(|chunk| chunk.unlock_at > current_block);
+        if member.points == 0 && member.unbonding.is_empty() {
+            self.members.remove(&who);
+        }
+
+        Self::deposit_event(system, PoolsEvent::Withdrawn { pool_id, member: who, amount });
+        Ok(amount)
+    }
+
+    // Choose the validators the pool's stake backs. Requires the root or the nominator.
+    pub fn nominate(
+        &mut self,
+        staking: &mut StakingPallet<T>,
+        who: T::AccountId,
+        pool_id: u32,
+        targets: Vec<T::AccountId>,
+    ) -> Result<(), PoolsError> {
+        let pool = self.pools.get(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+        if who != pool.roles.root && who != pool.roles.nominator {
+            return Err(PoolsError::NotNominator);
+        }
+
+        staking.nominate(T::pool_account(pool_id), targets).map_err(PoolsError::Staking)
+    }
+
+    // Open or block the pool to new members. Requires the root or the bouncer.
+    pub fn set_state(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        who: T::AccountId,
+        pool_id: u32,
+        state: PoolState,
+    ) -> Result<(), PoolsError> {
+        let pool = self.pools.get_mut(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+        if who != pool.roles.root && who != pool.roles.bouncer {
+            return Err(PoolsError::NotBouncer);
+        }
+
+        pool.state = state;
+        Self::deposit_event(system, PoolsEvent::StateChanged { pool_id, state });
+        Ok(())
+    }
+
+    // Replace the accounts managing the pool. Requires the root.
+    pub fn update_roles(
+        &mut self,
+        system: &mut SystemPallet<T>,
+        who: T::AccountId,
+        pool_id: u32,
+        roles: PoolRoles<T>,
+    ) -> Result<(), PoolsError> {
+        let pool = self.pools.get_mut(&pool_id).ok_or(PoolsError::PoolNotFound)?;
+        if who != pool.roles.root {
+            return Err(PoolsError::NotRoot);
+        }
+
+        pool.roles = roles;
+        Self::deposit_event(system, PoolsEvent::RolesUpdated { pool_id });
+        Ok(())
+    }
+
+    // Get pool details
+    pub fn get_pool(&self, pool_id: u32) -> Option<&Pool<T>> {
+        self.pools.get(&pool_id)
+    }
+
+    // Get the pool membership of an account
+    pub fn get_member(&self, who: &T::AccountId) -> Option<&PoolMember<T>> {
+        self.members.get(who)
+    }
+
+    // Convert a pools event into the runtime event type and store it in the system pallet
+    fn deposit_event(system: &mut SystemPallet<T>, event: PoolsEvent<T>) {
+        let event: <T as PoolsConfig>::RuntimeEvent = event.into();
+        system.deposit_event(event.into());
+    }
+}
+
+impl<T: PoolsConfig> Default for PoolsPallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking::ParameterChange;
+    use crate::Runtime;
+
+    #[test]
+    fn test_nomination_pools() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut pools = PoolsPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(dave, 500).unwrap();
+        staking.validate(dave).unwrap();
+        staking.set_keys(dave, dave.to_le_bytes().to_vec()).unwrap();
+        staking.set_parameter(ParameterChange::UnbondingPeriod(10));
+
+        // The pool bonds its members' funds as one account
+        let result = pools.create(&mut system, &mut staking, alice, 5, bob, charlie);
+        assert_eq!(result, Err(PoolsError::BondTooLow));
+        let pool_id = pools.create(&mut system, &mut staking, alice, 100, bob, charlie).unwrap();
+        pools.join(&mut system, &mut staking, bob, pool_id, 20).unwrap();
+        let account = Runtime::pool_account(pool_id);
+        assert_eq!(staking.get_staked_balance(account), 120u64);
+        assert_eq!(pools.get_member(&bob).unwrap().points, 20);
+
+        // Only the root or nominator chooses validators
+        let result = pools.nominate(&mut staking, charlie, pool_id, vec![dave]);
+        assert_eq!(result, Err(PoolsError::NotNominator));
+        pools.join(&mut system, &mut staking, charlie, pool_id, 20).unwrap();
+        pools.nominate(&mut staking, bob, pool_id, vec![dave]).unwrap();
+
+        // Only the root or bouncer can block the pool
+        let result = pools.set_state(&mut system, bob, pool_id, PoolState::Blocked);
+        assert_eq!(result, Err(PoolsError::NotBouncer));
+        pools.set_state(&mut system, charlie, pool_id, PoolState::Blocked).unwrap();
+        let result = pools.join(&mut system, &mut staking, 5u64, pool_id, 10);
+        assert_eq!(result, Err(PoolsError::PoolBlocked));
+
+        // Rewards paid to the pool are shared by points
+        staking.transfer(dave, account, 70).unwrap();
+        assert_eq!(pools.pending_rewards(&staking, &alice), 50);
+        assert_eq!(pools.claim_payout(&mut system, &mut staking, bob), Ok(10));
+        assert_eq!(pools.claim_payout(&mut system, &mut staking, bob), Ok(0));
+        assert_eq!(staking.get_free_balance(bob), 990u64);
+
+        // Unbonding pays pending rewards and releases the stake after the unbonding period
+        assert_eq!(pools.unbond(&mut system, &mut staking, alice, 50, 0), Ok(50));
+        assert_eq!(staking.get_free_balance(alice), 950u64);
+        assert_eq!(pools.unbond(&mut system, &mut staking, bob, 20, 0), Ok(20));
+        let result = pools.withdraw_unbonded(&mut system, &mut staking, bob, 9);
+        assert_eq!(result, Err(PoolsError::NothingToWithdraw));
+        assert_eq!(pools.withdraw_unbonded(&mut system, &mut staking, bob, 10), Ok(20));
+        assert_eq!(staking.get_free_balance(bob), 1010u64);
+        assert!(pools.get_member(&bob).is_none());
+        assert_eq!(pools.get_member(&alice).unwrap().points, 50);
+
+        // Withdrawn stake is not mistaken for a reward
+        assert_eq!(pools.pending_rewards(&staking, &charlie), 10);
+    }
+
+    #[test]
+    fn test_unbond_empty_pool() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+
+        let mut system = SystemPallet::<Runtime>::new();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut pools = PoolsPallet::<Runtime>::new();
+        staking.set_balance(alice, 1000);
+
+        // Once the only member has unbonded everything, unbonding nothing is refused
+        let pool_id = pools.create(&mut system, &mut staking, alice, 100, bob, charlie).unwrap();
+        assert_eq!(pools.unbond(&mut system, &mut staking, alice, 100, 0), Ok(100));
+        assert_eq!(pools.get_pool(pool_id).unwrap().points, 0);
+        let result = pools.unbond(&mut system, &mut staking, alice, 0, 0);
+        assert_eq!(result, Err(PoolsError::NotEnoughPoints));
+    }
+}
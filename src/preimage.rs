@@ -0,0 +1,58 @@
+use crate::system::SystemConfig;
+use std::collections::HashMap;
+
+// Off-chain data (e.g. proposal descriptions) kept by hash, so pallets only need to store
+// the hash in their own state
+pub struct PreimageStore<T: SystemConfig> {
+    preimages: HashMap<T::Hash, Vec<u8>>,
+}
+
+impl<T: SystemConfig> PreimageStore<T> {
+    pub fn new() -> Self {
+        Self {
+            preimages: HashMap::new(),
+        }
+    }
+
+    // Store the data and return its hash
+    pub fn note_preimage(&mut self, data: Vec<u8>) -> T::Hash {
+        let hash = T::hash(&data);
+        self.preimages.insert(hash.clone(), data);
+        hash
+    }
+
+    // Remove the data for a hash, returning whether it was stored
+    pub fn reap_preimage(&mut self, hash: &T::Hash) -> bool {
+        self.preimages.remove(hash).is_some()
+    }
+
+    // Get the data for a hash, if it is still stored
+    pub fn get_preimage(&self, hash: &T::Hash) -> Option<&[u8]> {
+        self.preimages.get(hash).map(|data| data.as_slice())
+    }
+}
+
+impl<T: SystemConfig> Default for PreimageStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Runtime;
+
+    #[test]
+    fn test_preimage_store() {
+        let mut store = PreimageStore::<Runtime>::new();
+
+        let hash = store.note_preimage(b"Fund the community".to_vec());
+        assert_eq!(hash, Runtime::hash(b"Fund the community"));
+        assert_eq!(store.get_preimage(&hash), Some(&b"Fund the community"[..]));
+
+        assert!(store.reap_preimage(&hash));
+        assert_eq!(store.get_preimage(&hash), None);
+        assert!(!store.reap_preimage(&hash));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::staking::Perbill;
+
+// Method used to split each era's reward between the treasury, the validators and the stakers
+// backing them, so a runtime can tune its economics without changing the staking pallet
+pub trait RewardSplitter {
+    // Part of an era's reward kept back for the treasury before any validator is paid
+    fn treasury_cut(era_reward: u128) -> u128;
+    // Part of a validator's reward it takes as commission, at its rate capped by governance
+    fn commission(validator_reward: u128, commission: Perbill) -> u128;
+    // Split what is left of a validator's reward after commission between its own stake and
+    // the stake of each of its nominators, returning (own share, nominator shares)
+    fn split(shared: u128, own: u128, others: &[u128]) -> (u128, Vec<u128>);
+}
+
+// Take `TREASURY_PERCENT` of each era's reward for the treasury, charge each validator's
+// commission in full and share the rest in proportion to stake
+pub struct ProportionalSplit<const TREASURY_PERCENT: u32>;
+
+impl<const TREASURY_PERCENT: u32> RewardSplitter for ProportionalSplit<TREASURY_PERCENT> {
+    fn treasury_cut(era_reward: u128) -> u128 {
+        Perbill::from_percent(TREASURY_PERCENT).mul_floor(era_reward)
+    }
+
+    fn commission(validator_reward: u128, commission: Perbill) -> u128 {
+        commission.mul_floor(validator_reward)
+    }
+
+    fn split(shared: u128, own: u128, others: &[u128]) -> (u128, Vec<u128>) {
+        let total = others.iter().fold(own, |total, value| total.saturating_add(*value));
+        let share = |value: u128| match total {
+            0 => 0,
+            _ => shared.saturating_mul(value) / total,
+        };
+        (share(own), others.iter().map(|value| share(*value)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pays validators their commission alone, leaving the rest to their nominators
+    struct NominatorsFirst;
+
+    impl RewardSplitter for NominatorsFirst {
+        fn treasury_cut(_era_reward: u128) -> u128 {
+            0
+        }
+
+        fn commission(validator_reward: u128, commission: Perbill) -> u128 {
+            commission.mul_floor(validator_reward)
+        }
+
+        fn split(shared: u128, own: u128, others: &[u128]) -> (u128, Vec<u128>) {
+            match others.is_empty() {
+                true => ProportionalSplit::<0>::split(shared, own, others),
+                false => {
+                    let (_, shares) = ProportionalSplit::<0>::split(shared, 0, others);
+                    (0, shares)
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_reward_splitters() {
+        type Split = ProportionalSplit<10>;
+        assert_eq!(Split::treasury_cut(1000), 100);
+        assert_eq!(Split::commission(900, Perbill::from_percent(10)), 90);
+        assert_eq!(Split::split(810, 100, &[100, 50, 20]), (300, vec![300, 150, 60]));
+        assert_eq!(Split::split(810, 0, &[]), (0, vec![]));
+
+        // A runtime can keep the validator's own stake out of the shared reward
+        assert_eq!(NominatorsFirst::split(300, 100, &[100, 50]), (0, vec![200, 100]));
+        assert_eq!(NominatorsFirst::split(300, 100, &[]), (300, vec![]));
+    }
+}
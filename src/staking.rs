@@ -1,34 +1,806 @@
-use crate::system::SystemConfig;
-use num::traits::{CheckedAdd, CheckedSub, Zero};
-use std::collections::HashMap;
+use crate::election::ElectionProvider;
+use crate::inflation::EraPayout;
+use crate::insurance::InsurancePool;
+use crate::reward::RewardSplitter;
+use crate::system::{DispatchError, Origin, SystemConfig, SystemPallet};
+use num::traits::{CheckedAdd, CheckedSub, One, Zero};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 
-pub trait StakingConfig: SystemConfig {
+pub trait StakingConfig: SystemConfig + Sized {
     // Define the Balance type with ability to perform checked arithmetic operations
-    type Balance: Zero + CheckedSub + CheckedAdd + Copy;
+    type Balance: Zero + CheckedSub + CheckedAdd + Copy + PartialOrd + Into<u128> + TryFrom<u128>;
+    // Account slashed funds are paid to, e.g. the treasury; they are burned if None
+    const SLASH_DESTINATION: Option<Self::AccountId>;
+    // Number of blocks in an era, the period validator rewards are paid for
+    const BLOCKS_PER_ERA: Self::BlockNumber;
+    // Smallest stake an account can validate with
+    const MIN_VALIDATOR_BOND: Self::Balance;
+    // Smallest stake an account can nominate with
+    const MIN_NOMINATOR_BOND: Self::Balance;
+    // Maximum number of unbonding chunks an account can have at once
+    const MAX_UNLOCKING_CHUNKS: u32;
+    // Number of eras a reported slash waits before it is applied, so governance can cancel it
+    const SLASH_DEFER_DURATION: u32;
+    // Number of eras each staker's payout records are kept for
+    const PAYOUT_HISTORY_DEPTH: u32;
+    // Era points an active validator earns for each block it authors
+    const AUTHORING_POINTS: u32;
+    // Number of recent eras an account must not have been exposed in to fast-unstake
+    const FAST_UNSTAKE_ERAS: u32;
+    // Number of eras an era's reward can be claimed for after it ends, like
+    // `pallet_staking::HistoryDepth`. Older era data is pruned.
+    const REWARD_CLAIM_HORIZON: u32;
+    // Account unclaimed era rewards and the treasury's cut of each era's reward are paid to;
+    // they are never minted if None
+    const UNCLAIMED_REWARD_DESTINATION: Option<Self::AccountId>;
+    // Share of an insured staker's era rewards paid into the slash insurance pool
+    const INSURANCE_PREMIUM: Perbill;
+    // Share of an insured staker's slash the insurance pool reimburses
+    const INSURANCE_COVER: Perbill;
+    // Most the insurance pool reimburses for a single slash
+    const INSURANCE_CLAIM_CAP: Self::Balance;
+    // The runtime event type, able to carry staking events into the system pallet
+    type RuntimeEvent: From<StakingEvent<Self>> + Into<<Self as SystemConfig>::RuntimeEvent>;
+    // Method used to elect the validators of each era
+    type ElectionProvider: ElectionProvider<Self>;
+    // Inflation model working out the reward minted for the validators of each era
+    type EraPayout: EraPayout;
+    // Method used to split each era's reward between the treasury, validators and nominators
+    type RewardSplitter: RewardSplitter;
+}
+
+// Name of a lock on an account's balance, like `frame_support::traits::LockIdentifier`
+pub type LockIdentifier = [u8; 8];
+
+// Lock staking keeps on each account's bonded stake
+pub const STAKING_LOCK: LockIdentifier = *b"staking ";
+
+// A fraction in parts per billion, like `sp_arithmetic::Perbill`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Perbill(u32);
+
+impl Perbill {
+    const ACCURACY: u32 = 1_000_000_000;
+
+    // Build a fraction from parts per billion, capped at one
+    pub fn from_parts(parts: u32) -> Self {
+        Self(parts.min(Self::ACCURACY))
+    }
+
+    // Build the fraction `numerator / denominator`, rounding down and capped at one. A zero
+    // denominator gives one.
+    pub fn from_rational(numerator: u128, denominator: u128) -> Self {
+        match denominator {
+            0 => Self(Self::ACCURACY),
+            _ => {
+                let parts = numerator.min(denominator).saturating_mul(Self::ACCURACY.into());
+                Self((parts / denominator) as u32)
+            },
+        }
+    }
+
+    // Build a fraction from a whole percentage, capped at 100%
+    pub const fn from_percent(percent: u32) -> Self {
+        let percent = if percent < 100 { percent } else { 100 };
+        Self(percent * (Self::ACCURACY / 100))
+    }
+
+    // Get the fraction in parts per billion
+    pub fn deconstruct(self) -> u32 {
+        self.0
+    }
+
+    // Multiply `value` by the fraction, rounding down, without overflowing
+    pub fn mul_floor(self, value: u128) -> u128 {
+        let accuracy = u128::from(Self::ACCURACY);
+        let parts = u128::from(self.0);
+        value / accuracy * parts + value % accuracy * parts / accuracy
+    }
+}
+
+// Stake backing a validator in an era, like `pallet_staking::Exposure`
+pub struct Exposure<T: StakingConfig> {
+    pub validator: T::AccountId,
+    pub own: T::Balance, // The validator's own stake
+    pub others: Vec<IndividualExposure<T>>, // Stake of the nominators backing it
+    pub commission: Perbill, // Cut of the validator's reward it takes before sharing it
+}
+
+impl<T: StakingConfig> Exposure<T> {
+    // Total stake backing the validator
+    pub fn total(&self) -> u128 {
+        self.others
+            .iter()
+            .fold(self.own.into(), |total: u128, other| total.saturating_add(other.value.into()))
+    }
+}
+
+// Part of a nominator's stake backing one validator
+pub struct IndividualExposure<T: StakingConfig> {
+    pub who: T::AccountId,
+    pub value: T::Balance,
+}
+
+// Where a staker's era rewards are paid, like `pallet_staking::RewardDestination`
+pub enum RewardDestination<T: StakingConfig> {
+    Staked,                  // Added to the staker's stake, compounding it
+    Stash,                   // Added to the staker's free balance
+    Account(T::AccountId),   // Added to the free balance of another account
+}
+
+// Record of a slash applied to a validator and the nominators backing it
+pub struct SlashEvent<T: StakingConfig> {
+    pub validator: T::AccountId,
+    pub fraction: Perbill,
+    pub amount: T::Balance, // Stake actually removed from the validator
+    pub others: Vec<IndividualExposure<T>>, // Stake actually removed from each nominator
+    pub beneficiary: Option<T::AccountId>, // Account the slashed funds went to, if not burned
+}
+
+// A slash reported but not applied yet, like `pallet_staking::UnappliedSlash`
+pub struct UnappliedSlash<T: StakingConfig> {
+    pub validator: T::AccountId,
+    pub fraction: Perbill,
+    pub own: T::Balance, // The validator's own stake exposed when the slash was reported
+    pub others: Vec<IndividualExposure<T>>, // Stake of each nominator exposed at the time
+    pub apply_era: u32, // Era at whose start the slash is applied
+    pub reporters: Vec<T::AccountId>, // Accounts rewarded from the slash for reporting it
+    pub span_index: u32, // Slashing span of the validator the offence was committed in
+}
+
+// Eras a validator was active in and how many of them it sent a heartbeat in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UptimeRecord {
+    pub active_eras: u32,
+    pub online_eras: u32,
+}
+
+impl UptimeRecord {
+    // Share of its active eras the validator was online in
+    pub fn score(&self) -> Perbill {
+        Perbill::from_rational(self.online_eras.into(), self.active_eras.into())
+    }
+}
+
+// Summary of the staking system, from the running totals the pallet keeps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakingStats {
+    pub total_bonded: u128,          // Active stake of every bonded account
+    pub validator_count: u32,        // Accounts validating, chilled or not
+    pub nominator_count: u32,        // Accounts nominating
+    pub average_commission: Perbill, // Mean commission of the validators
+    pub staking_rate: Perbill,       // Share of the total issuance that is bonded
+}
+
+// Eras between two chills of a validator, like `pallet_staking::slashing::SlashingSpans`.
+// Offences within a span are not punished more than once: only the part of each slash above
+// the largest fraction already applied in the span is slashed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashingSpan {
+    pub index: u32,            // Number of spans the validator had before this one
+    pub start_era: u32,        // Era the span started in
+    pub max_fraction: Perbill, // Largest fraction slashed in the span so far
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakingError {
+    InsufficientBalance,
+    InsufficientReservedBalance,
+    InsufficientStake,
+    LockedBalance,
+    BelowMinimumStake,
+    InsufficientValidatorBond,
+    InsufficientNominatorBond,
+    NotBonded,
+    NotController,
+    AlreadyPaired,
+    EmptySessionKeys,
+    AlreadyValidating,
+    NotValidating,
+    AlreadyChilled,
+    NotActiveValidator,
+    DuplicateHeartbeat,
+    ValidatorCannotNominate,
+    NoTargets,
+    DuplicateTarget,
+    TargetNotValidator,
+    TargetBlocked,
+    NotAllowlisted,
+    NotNominated,
+    AlreadyInsured,
+    NotInsured,
+    CommissionTooHigh,
+    CommissionTooLow,
+    InvalidValidatorCount,
+    TooManyUnlockingChunks,
+    NoUnlockedChunks,
+    AlreadyQueued,
+    EraNotEnded,
+    EraExpired,
+    AlreadyPaid,
+    SlashNotFound,
+    SlashPending,
+    BadOrigin,
+    Overflow,
+}
+
+impl StakingError {
+    // Get the description of the error
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StakingError::InsufficientBalance => "Not enough free balance",
+            StakingError::InsufficientReservedBalance => "Not enough reserved balance",
+            StakingError::InsufficientStake => "Not enough staked balance",
+            StakingError::LockedBalance => "Balance is locked",
+            StakingError::BelowMinimumStake => "Staked balance would be below the minimum stake",
+            StakingError::InsufficientValidatorBond => "Stake is below the minimum validator bond",
+            StakingError::InsufficientNominatorBond => "Stake is below the minimum nominator bond",
+            StakingError::NotBonded => "Account has no stake",
+            StakingError::NotController => "Stash is controlled by another account",
+            StakingError::AlreadyPaired => "Account is already a stash or controller",
+            StakingError::EmptySessionKeys => "Session keys cannot be empty",
+            StakingError::AlreadyValidating => "Account is already validating",
+            StakingError::NotValidating => "Account is not validating",
+            StakingError::AlreadyChilled => "Validator is already chilled",
+            StakingError::NotActiveValidator => "Validator is not in the active set",
+            StakingError::DuplicateHeartbeat => "Validator already sent a heartbeat this era",
+            StakingError::ValidatorCannotNominate => "Validators cannot nominate",
+            StakingError::NoTargets => "No nomination targets",
+            StakingError::DuplicateTarget => "Nomination target is listed more than once",
+            StakingError::TargetNotValidator => "Nomination target is not a validator",
+            StakingError::TargetBlocked => "Nomination target does not accept new nominations",
+            StakingError::NotAllowlisted => "Only allowlisted accounts can validate",
+            StakingError::NotNominated => "Validator is not among the account's nominations",
+            StakingError::AlreadyInsured => "Account is already insured",
+            StakingError::NotInsured => "Account is not insured",
+            StakingError::CommissionTooHigh => "Commission is above the maximum",
+            StakingError::CommissionTooLow => "Commission is below the minimum",
+            StakingError::InvalidValidatorCount => {
+                "Validator count must not be below the minimum validator count"
+            },
+            StakingError::TooManyUnlockingChunks => "Too many unbonding chunks",
+            StakingError::NoUnlockedChunks => "No unbonding funds",
+            StakingError::AlreadyQueued => "Account is already queued to fast-unstake",
+            StakingError::EraNotEnded => "Era has not ended yet",
+            StakingError::EraExpired => "Era rewards can no longer be claimed",
+            StakingError::AlreadyPaid => "Era has already been paid out",
+            StakingError::SlashNotFound => "No pending slash with the given ID",
+            StakingError::SlashPending => "Funds cannot leave the stake while a slash is pending",
+            StakingError::BadOrigin => "Origin is not allowed to perform this operation",
+            StakingError::Overflow => "Arithmetic overflow",
+        }
+    }
+}
+
+impl fmt::Display for StakingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::error::Error for StakingError {}
+
+impl From<StakingError> for DispatchError {
+    fn from(error: StakingError) -> Self {
+        match error {
+            StakingError::BadOrigin => DispatchError::BadOrigin,
+            error => DispatchError::Module { pallet: "Staking", message: error.as_str() },
+        }
+    }
+}
+
+pub enum StakingEvent<T: StakingConfig> {
+    Bonded {
+        stash: T::AccountId,
+        amount: T::Balance,
+    },
+    Unbonded {
+        stash: T::AccountId,
+        amount: T::Balance,
+    },
+    Rewarded {
+        who: T::AccountId, // Account the reward was paid to
+        amount: T::Balance,
+    },
+    Slashed {
+        staker: T::AccountId,
+        amount: T::Balance,
+    },
+    ValidatorChilled {
+        validator: T::AccountId,
+    },
+    NominatorChilled {
+        nominator: T::AccountId, // Dropped its nominations for falling below the minimum bond
+    },
+    EraPaid {
+        era_index: u32,
+        total: T::Balance,
+    },
+    EraRewardExpired {
+        era_index: u32,
+        amount: T::Balance, // Unclaimed reward paid to `UNCLAIMED_REWARD_DESTINATION`
+    },
+    ValidatorOffline {
+        validator: T::AccountId,
+        uptime: Perbill, // Uptime score that fell below `min_uptime`
+    },
+    Renominated {
+        nominator: T::AccountId,
+        from: T::AccountId,
+        to: T::AccountId,
+    },
+    Reimbursed {
+        who: T::AccountId,
+        amount: T::Balance, // Part of a slash paid back from the insurance pool
+    },
+}
+
+// Calls into the staking pallet that can be dispatched by the runtime (e.g. from governance)
+pub enum StakingCall<T: StakingConfig> {
+    Transfer {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    SetBalance {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    SetParameter(ParameterChange<T>),
+    CancelSlash(u32),
+}
+
+// A change to one of the staking parameters, e.g. enacted by a governance proposal
+pub enum ParameterChange<T: StakingConfig> {
+    MinStake(T::Balance),
+    MaxValidators(u32),
+    UnbondingPeriod(T::BlockNumber),
+    MaxCommission(Perbill),
+    MaxNominatorsPerValidator(u32),
+    SlashRewardFraction(Perbill),
+    MinValidators(u32),
+    MinUptime(Perbill),
+    MinCommission(Perbill),
+}
+
+// Staking parameters that can be adjusted at runtime
+pub struct StakingParameters<T: StakingConfig> {
+    // Smallest staked balance an account can hold, other than zero
+    pub min_stake: T::Balance,
+    // Maximum number of validators that can be active at once
+    pub max_validators: u32,
+    // Fewest validators an election must choose; with fewer candidates the previous
+    // validators are kept
+    pub min_validators: u32,
+    // Number of blocks unstaked tokens take to become free
+    pub unbonding_period: T::BlockNumber,
+    // Highest commission validators can set or be paid
+    pub max_commission: Perbill,
+    // Lowest commission validators can set; `apply_min_commission` raises older ones to it
+    pub min_commission: Perbill,
+    // Maximum number of nominators counted toward a validator's exposure, the largest first.
+    // The others are oversubscribed and earn nothing from it.
+    pub max_nominators_per_validator: u32,
+    // Uptime score below which validators are chilled at the end of an era; zero disables it
+    pub min_uptime: Perbill,
+    // Part of each slash shared by the accounts that reported the offence
+    pub slash_reward_fraction: Perbill,
+}
+
+// Unstaked tokens waiting out the unbonding period, like `pallet_staking::UnlockChunk`
+pub struct UnlockChunk<T: StakingConfig> {
+    pub value: T::Balance,
+    pub unlock_at: T::BlockNumber, // First block at which the chunk can be withdrawn
+}
+
+// Bonded state of an account, like `pallet_staking::StakingLedger`
+pub struct StakingLedger<T: StakingConfig> {
+    pub stash: T::AccountId,
+    pub active: T::Balance, // Stake counting toward elections, rewards and votes
+    pub total: T::Balance,  // Active stake and the chunks still unbonding
+    pub unlocking: Vec<UnlockChunk<T>>, // Chunks still unbonding, oldest first
+}
+
+impl<T: StakingConfig> StakingLedger<T> {
+    pub fn new(stash: T::AccountId) -> Self {
+        Self { stash, active: T::Balance::zero(), total: T::Balance::zero(), unlocking: Vec::new() }
+    }
+
+    // Total value of the chunks still unbonding
+    pub fn total_unlocking(&self) -> T::Balance {
+        self.unlocking.iter().fold(T::Balance::zero(), |total, chunk| {
+            total.checked_add(&chunk.value).unwrap_or(total)
+        })
+    }
+
+    // Whether nothing is bonded or unbonding any more
+    pub fn is_empty(&self) -> bool {
+        self.total.is_zero() && self.unlocking.is_empty()
+    }
+
+    // Add to the active stake
+    pub fn bond(&mut self, amount: T::Balance) -> Result<(), StakingError> {
+        let active = self.active.checked_add(&amount).ok_or(StakingError::Overflow)?;
+        let total = self.total.checked_add(&amount).ok_or(StakingError::Overflow)?;
+        self.active = active;
+        self.total = total;
+        Ok(())
+    }
+
+    // Remove from the active stake straight away, without unbonding it
+    pub fn release(&mut self, amount: T::Balance) -> Result<(), StakingError> {
+        self.active = self.active.checked_sub(&amount).ok_or(StakingError::InsufficientStake)?;
+        self.total = self.total.checked_sub(&amount).unwrap_or_else(Zero::zero);
+        Ok(())
+    }
+
+    // Move active stake into a chunk unlocking at `unlock_at`, topping up the chunk unlocking
+    // at the same block if there is one. Fails if `max_chunks` are already unbonding.
+    pub fn unbond(
+        &mut self,
+        amount: T::Balance,
+        unlock_at: T::BlockNumber,
+        max_chunks: u32,
+    ) -> Result<(), StakingError> {
+        let active = self.active.checked_sub(&amount).ok_or(StakingError::InsufficientStake)?;
+        let chunks = self.unlocking.len();
+        match self.unlocking.iter_mut().find(|chunk| chunk.unlock_at == unlock_at) {
+            Some(chunk) => {
+                chunk.value = chunk.value.checked_add(&amount).ok_or(StakingError::Overflow)?
+            },
+            None if chunks >= max_chunks as usize => {
+                return Err(StakingError::TooManyUnlockingChunks);
+            },
+            None => self.unlocking.push(UnlockChunk { value: amount, unlock_at }),
+        }
+        self.active = active;
+        Ok(())
+    }
+
+    // Move up to `amount` of the unbonding chunks back into the active stake, taking from the
+    // most recent chunks first. Returns the amount rebonded.
+    pub fn rebond(&mut self, amount: T::Balance) -> T::Balance {
+        let mut remaining = amount;
+        while let Some(chunk) = self.unlocking.last_mut() {
+            if chunk.value > remaining {
+                chunk.value = chunk.value - remaining;
+                remaining = T::Balance::zero();
+                break;
+            }
+            remaining = remaining - chunk.value;
+            self.unlocking.pop();
+        }
+        let rebonded = amount - remaining;
+        // Rebonded funds were already counted in the total, so the active stake cannot overflow
+        self.active = self.active.checked_add(&rebonded).unwrap_or(self.total);
+        rebonded
+    }
+
+    // Value of the chunks unlocked by `current_block`
+    pub fn unlocked(&self, current_block: T::BlockNumber) -> Result<T::Balance, StakingError> {
+        let mut unlocked = T::Balance::zero();
+        for chunk in self.unlocking.iter().filter(|chunk| chunk.unlock_at <= current_block) {
+            unlocked = unlocked.checked_add(&chunk.value).ok_or(StakingError::Overflow)?;
+        }
+        Ok(unlocked)
+    }
+
+    // Remove the chunks unlocked by `current_block` from the ledger, returning their value
+    pub fn consolidate_unlocked(&mut self, current_block: T::BlockNumber) -> T::Balance {
+        let unlocked = self.unlocked(current_block).unwrap_or(self.total);
+        self.unlocking.retain(|chunk| chunk.unlock_at > current_block);
+        self.total = self.total.checked_sub(&unlocked).unwrap_or_else(Zero::zero);
+        unlocked
+    }
+
+    // Remove up to `amount` from the active stake, then from the chunks still unbonding, the
+    // most recent first, so unbonding after an offence does not escape its slash (withdrawing
+    // waits for pending slashes). Returns the amount removed.
+    pub fn slash(&mut self, amount: T::Balance) -> T::Balance {
+        let from_active = if amount < self.active { amount } else { self.active };
+        self.active = self.active - from_active;
+        let mut remaining = amount - from_active;
+        while !remaining.is_zero() {
+            let chunk = match self.unlocking.last_mut() {
+                Some(chunk) => chunk,
+                None => break,
+            };
+            let from_chunk = if remaining < chunk.value { remaining } else { chunk.value };
+            chunk.value = chunk.value - from_chunk;
+            remaining = remaining - from_chunk;
+            if chunk.value.is_zero() {
+                self.unlocking.pop();
+            }
+        }
+        let slashed = amount - remaining;
+        self.total = self.total.checked_sub(&slashed).unwrap_or_else(Zero::zero);
+        slashed
+    }
 }
 
 pub struct StakingPallet<T: StakingConfig> {
     // Track free balances for each account
-    pub free_balances: HashMap<T::AccountId, T::Balance>,
-    // Track staked balances for each account
-    pub staked_balances: HashMap<T::AccountId, T::Balance>,
+    pub free_balances: BTreeMap<T::AccountId, T::Balance>,
+    // Track reserved balances (e.g. governance deposits) for each account
+    pub reserved_balances: BTreeMap<T::AccountId, T::Balance>,
+    // account -> named locks on balance that cannot be unstaked or transferred, e.g. the bond
+    // and governance voting locks. Locks overlap: the largest covers the staked balance first
+    // and any remainder of the free balance.
+    pub locks: BTreeMap<T::AccountId, BTreeMap<LockIdentifier, T::Balance>>,
+    // Accounts that offer to validate, in the order they started
+    pub validators: Vec<T::AccountId>,
+    // The same accounts, sorted for fast lookups
+    validator_index: BTreeSet<T::AccountId>,
+    // validator -> opaque consensus keys it authors blocks with, needed to be elected
+    pub session_keys: BTreeMap<T::AccountId, Vec<u8>>,
+    // Validators that stepped out of elections and payouts without unbonding
+    pub chilled: BTreeSet<T::AccountId>,
+    // Validators elected for the current era, by decreasing backing stake
+    pub active_set: Vec<T::AccountId>,
+    // validator -> commission it takes off the top of its rewards
+    pub commissions: BTreeMap<T::AccountId, Perbill>,
+    // Validators refusing new nominations; those that already nominate them are kept
+    pub blocked: BTreeSet<T::AccountId>,
+    // While `permissioned` is set, only accounts in `validator_allowlist` can validate
+    pub permissioned: bool,
+    pub validator_allowlist: BTreeSet<T::AccountId>,
+    // stash -> account signing its staking operations, if not the stash itself
+    pub controllers: BTreeMap<T::AccountId, T::AccountId>,
+    // staker -> where its rewards are paid, if not to its free balance
+    pub payees: BTreeMap<T::AccountId, RewardDestination<T>>,
+    // nominator -> validators it backs with its stake, split evenly between them
+    pub nominations: BTreeMap<T::AccountId, Vec<T::AccountId>>,
+    // Index of the era in progress, and the block it started at
+    pub current_era: u32,
+    pub era_start: T::BlockNumber,
+    // era -> stake backing each active validator when it was elected, used to split the era's
+    // reward and to slash misbehaving validators
+    pub eras_stakers: BTreeMap<u32, Vec<Exposure<T>>>,
+    // era -> validators elected for it that chilled before it ended, forfeiting its reward
+    pub eras_chilled: BTreeMap<u32, BTreeSet<T::AccountId>>,
+    // Active validators that sent a heartbeat in the current era
+    pub heartbeats: BTreeSet<T::AccountId>,
+    // validator -> its uptime over the eras it was active in
+    pub uptime: BTreeMap<T::AccountId, UptimeRecord>,
+    // era -> points each active validator earned for its activity, used to split the reward
+    pub eras_reward_points: BTreeMap<u32, BTreeMap<T::AccountId, u32>>,
+    // era -> reward minted for its validators when it ended, waiting to be paid out
+    pub eras_validator_reward: BTreeMap<u32, T::Balance>,
+    // Eras whose reward has already been paid out
+    pub paid_eras: BTreeSet<u32>,
+    // staker -> (era, reward earned) for the eras still within `PAYOUT_HISTORY_DEPTH`
+    pub payout_history: BTreeMap<T::AccountId, Vec<(u32, T::Balance)>>,
+    // Track slashes applied so far, oldest first
+    pub slashes: Vec<SlashEvent<T>>,
+    // slash ID -> slash waiting for its era to be applied
+    pub pending_slashes: BTreeMap<u32, UnappliedSlash<T>>,
+    // validator -> its slashing spans, oldest first; the last one is in progress
+    pub slashing_spans: BTreeMap<T::AccountId, Vec<SlashingSpan>>,
+    pub next_slash_id: u32,
+    // Track the active and unbonding stake of each bonded account
+    pub ledgers: BTreeMap<T::AccountId, StakingLedger<T>>,
+    // Accounts waiting to unstake without unbonding, checked at the start of the next era
+    pub fast_unstake_queue: Vec<T::AccountId>,
+    // Nominations to move at the start of the next era: (nominator, from, to), oldest first
+    pub pending_renominations: Vec<(T::AccountId, T::AccountId, T::AccountId)>,
+    // Premiums collected from insured stakers to reimburse part of their slashes
+    pub insurance: InsurancePool<T>,
+    // Parameters governance can adjust at runtime
+    pub parameters: StakingParameters<T>,
+    // Running totals behind `staking_overview`, kept up to date as balances change
+    total_bonded: u128,
+    issuance: u128,
+    commission_total: u128, // Sum of the validators' commissions, in parts per billion
+    // Events not yet moved to the system pallet, oldest first
+    events: Vec<StakingEvent<T>>,
 }
 
 impl<T: StakingConfig> StakingPallet<T> {
     pub fn new() -> Self {
         Self {
-            free_balances: HashMap::new(),
-            staked_balances: HashMap::new(),
+            free_balances: BTreeMap::new(),
+            reserved_balances: BTreeMap::new(),
+            locks: BTreeMap::new(),
+            validators: Vec::new(),
+            validator_index: BTreeSet::new(),
+            session_keys: BTreeMap::new(),
+            chilled: BTreeSet::new(),
+            active_set: Vec::new(),
+            commissions: BTreeMap::new(),
+            blocked: BTreeSet::new(),
+            permissioned: false,
+            validator_allowlist: BTreeSet::new(),
+            controllers: BTreeMap::new(),
+            payees: BTreeMap::new(),
+            nominations: BTreeMap::new(),
+            current_era: 0,
+            era_start: T::BlockNumber::zero(),
+            eras_stakers: BTreeMap::new(),
+            eras_chilled: BTreeMap::new(),
+            heartbeats: BTreeSet::new(),
+            uptime: BTreeMap::new(),
+            eras_reward_points: BTreeMap::new(),
+            eras_validator_reward: BTreeMap::new(),
+            paid_eras: BTreeSet::new(),
+            payout_history: BTreeMap::new(),
+            slashes: Vec::new(),
+            pending_slashes: BTreeMap::new(),
+            slashing_spans: BTreeMap::new(),
+            next_slash_id: 0,
+            ledgers: BTreeMap::new(),
+            fast_unstake_queue: Vec::new(),
+            pending_renominations: Vec::new(),
+            insurance: InsurancePool::new(),
+            total_bonded: 0,
+            issuance: 0,
+            commission_total: 0,
+            parameters: StakingParameters {
+                min_stake: T::Balance::zero(),
+                max_validators: u32::MAX,
+                min_validators: 0,
+                unbonding_period: T::BlockNumber::zero(),
+                max_commission: Perbill::from_percent(100),
+                min_commission: Perbill::default(),
+                max_nominators_per_validator: u32::MAX,
+                slash_reward_fraction: Perbill::from_percent(10),
+                min_uptime: Perbill::default(),
+            },
+            events: Vec::new(),
         }
     }
 
     // Set free balance for an account
     pub fn set_balance(&mut self, who: T::AccountId, amount: T::Balance) {
+        let free_balance = self.get_free_balance(who.clone());
+        self.issuance =
+            self.issuance.saturating_sub(free_balance.into()).saturating_add(amount.into());
         self.free_balances.insert(who, amount);
     }
 
+    // Transfer free tokens between accounts
+    pub fn transfer(
+        &mut self,
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> Result<(), StakingError> {
+        let from_balance = self.get_free_balance(from.clone());
+        let new_from_balance = from_balance
+            .checked_sub(&amount)
+            .ok_or(StakingError::InsufficientBalance)?;
+        if amount > self.get_transferable_balance(from.clone()) {
+            return Err(StakingError::LockedBalance);
+        }
+        self.free_balances.insert(from.clone(), new_from_balance);
+
+        let to_balance = self.get_free_balance(to.clone());
+        if let Some(new_to_balance) = to_balance.checked_add(&amount) {
+            self.free_balances.insert(to, new_to_balance);
+            Ok(())
+        } else {
+            self.free_balances.insert(from, from_balance);
+            Err(StakingError::Overflow)
+        }
+    }
+
+    // Execute a dispatched staking call
+    pub fn dispatch(&mut self, call: StakingCall<T>) -> Result<(), StakingError> {
+        match call {
+            StakingCall::Transfer { from, to, amount } => self.transfer(from, to, amount),
+            StakingCall::SetBalance { who, amount } => {
+                self.set_balance(who, amount);
+                Ok(())
+            },
+            StakingCall::SetParameter(change) => {
+                self.set_parameter(change);
+                Ok(())
+            },
+            // Dispatched calls come from enacted proposals
+            StakingCall::CancelSlash(slash_id) => self.cancel_slash(Origin::Root, slash_id),
+        }
+    }
+
+    // Write a new value into the staking parameters
+    pub fn set_parameter(&mut self, change: ParameterChange<T>) {
+        match change {
+            ParameterChange::MinStake(min_stake) => self.parameters.min_stake = min_stake,
+            ParameterChange::MaxValidators(max_validators) => {
+                self.parameters.max_validators = max_validators
+            },
+            ParameterChange::UnbondingPeriod(unbonding_period) => {
+                self.parameters.unbonding_period = unbonding_period
+            },
+            ParameterChange::MaxCommission(max_commission) => {
+                self.parameters.max_commission = max_commission
+            },
+            ParameterChange::MaxNominatorsPerValidator(max_nominators) => {
+                self.parameters.max_nominators_per_validator = max_nominators
+            },
+            ParameterChange::SlashRewardFraction(fraction) => {
+                self.parameters.slash_reward_fraction = fraction
+            },
+            ParameterChange::MinValidators(min_validators) => {
+                self.parameters.min_validators = min_validators
+            },
+            ParameterChange::MinUptime(min_uptime) => self.parameters.min_uptime = min_uptime,
+            ParameterChange::MinCommission(min_commission) => {
+                self.parameters.min_commission = min_commission
+            },
+        }
+    }
+
+    // Set the number of validators elected from the next era on. Only root or the council can
+    // change it, and never below the minimum.
+    pub fn set_validator_count(&mut self, origin: Origin<T>, new: u32) -> Result<(), StakingError> {
+        if !matches!(origin, Origin::Root | Origin::Council) {
+            return Err(StakingError::BadOrigin);
+        }
+        if new < self.parameters.min_validators {
+            return Err(StakingError::InvalidValidatorCount);
+        }
+        self.set_parameter(ParameterChange::MaxValidators(new));
+        Ok(())
+    }
+
+    // Set the fewest validators elected from the next era on. Only root or the council can
+    // change it, and never above the validator count.
+    pub fn set_minimum_validator_count(
+        &mut self,
+        origin: Origin<T>,
+        new: u32,
+    ) -> Result<(), StakingError> {
+        if !matches!(origin, Origin::Root | Origin::Council) {
+            return Err(StakingError::BadOrigin);
+        }
+        if new > self.parameters.max_validators {
+            return Err(StakingError::InvalidValidatorCount);
+        }
+        self.set_parameter(ParameterChange::MinValidators(new));
+        Ok(())
+    }
+
+    // Turn permissioned mode on or off, e.g. for a proof-of-authority test network. While it is
+    // on, only allowlisted accounts can validate or stand for election. Only root or the
+    // council can change it.
+    pub fn set_permissioned(
+        &mut self,
+        origin: Origin<T>,
+        permissioned: bool,
+    ) -> Result<(), StakingError> {
+        if !matches!(origin, Origin::Root | Origin::Council) {
+            return Err(StakingError::BadOrigin);
+        }
+        self.permissioned = permissioned;
+        Ok(())
+    }
+
+    // Add an account to the validator allowlist or remove it. Only root or the council can
+    // change it; a validator removed while permissioned mode is on stops being elected.
+    pub fn set_allowlisted(
+        &mut self,
+        origin: Origin<T>,
+        who: T::AccountId,
+        allowed: bool,
+    ) -> Result<(), StakingError> {
+        if !matches!(origin, Origin::Root | Origin::Council) {
+            return Err(StakingError::BadOrigin);
+        }
+        match allowed {
+            true => self.validator_allowlist.insert(who),
+            false => self.validator_allowlist.remove(&who),
+        };
+        Ok(())
+    }
+
+    // Whether an account may validate under the current mode
+    fn is_allowlisted(&self, who: &T::AccountId) -> bool {
+        !self.permissioned || self.validator_allowlist.contains(who)
+    }
+
     // Stake tokens (move from free to staked)
-    pub fn stake(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), &'static str> {
+    pub fn stake(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), StakingError> {
         let free_balance = self.get_free_balance(who.clone());
         
         if let Some(new_free_balance) = free_balance.checked_sub(&amount) {
@@ -36,100 +808,3103 @@ impl<T: StakingConfig> StakingPallet<T> {
             
             let staked_balance = self.get_staked_balance(who.clone());
             if let Some(new_staked_balance) = staked_balance.checked_add(&amount) {
-                self.staked_balances.insert(who, new_staked_balance);
+                if new_staked_balance < self.parameters.min_stake {
+                    self.free_balances.insert(who, free_balance);
+                    return Err(StakingError::BelowMinimumStake);
+                }
+                if let Err(error) = self.ledger_mut(&who).bond(amount) {
+                    self.free_balances.insert(who, free_balance);
+                    return Err(error);
+                }
+                self.total_bonded = self.total_bonded.saturating_add(amount.into());
+                self.update_bond_lock(&who);
+                self.events.push(StakingEvent::Bonded { stash: who, amount });
                 Ok(())
             } else {
                 self.free_balances.insert(who, free_balance);
-                Err("Staked balance would overflow")
+                Err(StakingError::Overflow)
             }
         } else {
-            Err("Not enough free balance to stake")
+            Err(StakingError::InsufficientBalance)
+        }
+    }
+
+    // Add free balance to an existing bond, like `pallet_staking::bond_extra`. The extra stake
+    // counts straight away and backs the account's validator or nominations from the next
+    // election.
+    pub fn bond_extra(
+        &mut self,
+        who: T::AccountId,
+        additional: T::Balance,
+    ) -> Result<(), StakingError> {
+        if self.get_staked_balance(who.clone()).is_zero() {
+            return Err(StakingError::NotBonded);
         }
+        self.stake(who, additional)
     }
 
-    // Unstake tokens (move from staked to free)
-    pub fn unstake(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), &'static str> {
+    // Move bonded stake straight back to the free balance, skipping the unbonding period. Only
+    // fast unstaking may do this; every other exit goes through `unbond`.
+    fn release_stake(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), StakingError> {
         let staked_balance = self.get_staked_balance(who.clone());
         
         if let Some(new_staked_balance) = staked_balance.checked_sub(&amount) {
-            self.staked_balances.insert(who.clone(), new_staked_balance);
-            
+            if new_staked_balance < self.locked_by_others(&who) {
+                return Err(StakingError::LockedBalance);
+            }
+
+            if !new_staked_balance.is_zero() && new_staked_balance < self.parameters.min_stake {
+                return Err(StakingError::BelowMinimumStake);
+            }
+            self.ensure_role_bond(&who, new_staked_balance)?;
+
             let free_balance = self.get_free_balance(who.clone());
             if let Some(new_free_balance) = free_balance.checked_add(&amount) {
-                self.free_balances.insert(who, new_free_balance);
+                self.ledger_mut(&who).release(amount)?;
+                self.prune_ledger(&who);
+                self.total_bonded = self.total_bonded.saturating_sub(amount.into());
+                self.update_bond_lock(&who);
+                self.free_balances.insert(who.clone(), new_free_balance);
+                self.events.push(StakingEvent::Unbonded { stash: who, amount });
                 Ok(())
             } else {
-                self.staked_balances.insert(who, staked_balance);
-                Err("Free balance would overflow")
+                Err(StakingError::Overflow)
             }
         } else {
-            Err("Not enough staked balance to unstake")
+            Err(StakingError::InsufficientStake)
         }
     }
 
-    // Get free balance for an account
-    pub fn get_free_balance(&self, who: T::AccountId) -> T::Balance {
-        *self.free_balances.get(&who).unwrap_or(&T::Balance::zero())
+    // Start unbonding staked tokens at `current_block`. They stop counting as stake straight
+    // away but only become free through `withdraw_unbonded` once the unbonding period is over.
+    // Each call adds a chunk, up to `MAX_UNLOCKING_CHUNKS`; chunks maturing at the same block
+    // are merged.
+    pub fn unbond(
+        &mut self,
+        who: T::AccountId,
+        amount: T::Balance,
+        current_block: T::BlockNumber,
+    ) -> Result<(), StakingError> {
+        let staked_balance = self.get_staked_balance(who.clone());
+        let new_staked_balance = staked_balance
+            .checked_sub(&amount)
+            .ok_or(StakingError::InsufficientStake)?;
+        if new_staked_balance < self.locked_by_others(&who) {
+            return Err(StakingError::LockedBalance);
+        }
+        if !new_staked_balance.is_zero() && new_staked_balance < self.parameters.min_stake {
+            return Err(StakingError::BelowMinimumStake);
+        }
+        self.ensure_role_bond(&who, new_staked_balance)?;
+        let unlock_at = current_block
+            .checked_add(&self.parameters.unbonding_period)
+            .ok_or(StakingError::Overflow)?;
+
+        self.ledger_mut(&who).unbond(amount, unlock_at, T::MAX_UNLOCKING_CHUNKS)?;
+        self.total_bonded = self.total_bonded.saturating_sub(amount.into());
+        self.update_bond_lock(&who);
+        self.events.push(StakingEvent::Unbonded { stash: who, amount });
+        Ok(())
     }
 
-    // Get staked balance for an account
-    pub fn get_staked_balance(&self, who: T::AccountId) -> T::Balance {
-        *self.staked_balances.get(&who).unwrap_or(&T::Balance::zero())
+    // Move up to `amount` of unbonding tokens back into the stake without waiting for them to
+    // unlock, taking from the most recent chunks first. Returns the amount rebonded.
+    pub fn rebond(
+        &mut self,
+        who: T::AccountId,
+        amount: T::Balance,
+    ) -> Result<T::Balance, StakingError> {
+        let ledger = self
+            .ledgers
+            .get_mut(&who)
+            .filter(|ledger| !ledger.unlocking.is_empty())
+            .ok_or(StakingError::NoUnlockedChunks)?;
+        let rebonded = ledger.rebond(amount);
+        self.total_bonded = self.total_bonded.saturating_add(rebonded.into());
+        self.update_bond_lock(&who);
+        self.events.push(StakingEvent::Bonded { stash: who, amount: rebonded });
+        Ok(rebonded)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::Runtime;
+    // Queue an account to unstake its whole stake without waiting for the unbonding period,
+    // dropping its nominations. At the start of the next era it is unstaked at once if it
+    // was not exposed in any of the last `FAST_UNSTAKE_ERAS` eras, and otherwise removed from
+    // the queue, keeping its stake bonded. Validators must stop validating first.
+    pub fn register_fast_unstake(&mut self, who: T::AccountId) -> Result<(), StakingError> {
+        if self.get_staked_balance(who.clone()).is_zero() {
+            return Err(StakingError::NotBonded);
+        }
+        if self.is_validator(&who) {
+            return Err(StakingError::AlreadyValidating);
+        }
+        if self.fast_unstake_queue.contains(&who) {
+            return Err(StakingError::AlreadyQueued);
+        }
 
-    #[test]
-    fn test_staking_should_work() {
-        let alice = 1u64;
-        let mut staking = StakingPallet::<Runtime>::new();
+        self.nominations.remove(&who);
+        self.fast_unstake_queue.push(who);
+        Ok(())
+    }
 
-        // Set initial balance
-        staking.set_balance(alice, 1000);
+    // Unstake the queued accounts that were not exposed in the recent eras, including the one
+    // just elected. Accounts that were, or that started nominating again, are dropped.
+    fn process_fast_unstake(&mut self) {
+        let first_era = self.current_era.saturating_sub(T::FAST_UNSTAKE_ERAS);
+        for who in std::mem::take(&mut self.fast_unstake_queue) {
+            if self.nominations.contains_key(&who) || self.is_validator(&who) {
+                continue;
+            }
+            let mut eras = self.eras_stakers.range(first_era..=self.current_era);
+            let exposed = eras.any(|(_, stakers)| {
+                stakers.iter().any(|exposure| {
+                    let nominated = exposure.others.iter().any(|other| other.who == who);
+                    exposure.validator == who || nominated
+                })
+            });
+            if exposed || self.has_pending_slash(&who) {
+                continue;
+            }
+            let staked_balance = self.get_staked_balance(who.clone());
+            // Locked stake stays bonded; the account can register again once it is unlocked
+            let _ = self.release_stake(who, staked_balance);
+        }
+    }
 
-        // Check free balance
-        assert_eq!(staking.get_free_balance(alice), 1000u64);
-        assert_eq!(staking.get_staked_balance(alice), 0u64);
+    // Move every unbonding chunk that has matured by `current_block` to the free balance,
+    // returning the amount withdrawn. Chunks still unbonding are kept, and nothing can be
+    // withdrawn while a slash against the account's stake is pending.
+    pub fn withdraw_unbonded(
+        &mut self,
+        who: T::AccountId,
+        current_block: T::BlockNumber,
+    ) -> Result<T::Balance, StakingError> {
+        if self.has_pending_slash(&who) {
+            return Err(StakingError::SlashPending);
+        }
+        let ledger = self
+            .ledgers
+            .get_mut(&who)
+            .filter(|ledger| !ledger.unlocking.is_empty())
+            .ok_or(StakingError::NoUnlockedChunks)?;
+        let free_balance = self.free_balances.get(&who).copied().unwrap_or_else(Zero::zero);
+        let new_free_balance = free_balance
+            .checked_add(&ledger.unlocked(current_block)?)
+            .ok_or(StakingError::Overflow)?;
 
-        // Stake tokens
-        let result = staking.stake(alice, 400);
-        assert!(result.is_ok());
+        let withdrawn = ledger.consolidate_unlocked(current_block);
+        self.free_balances.insert(who.clone(), new_free_balance);
+        self.prune_ledger(&who);
+        Ok(withdrawn)
+    }
 
-        // Check balances after staking
-        assert_eq!(staking.get_free_balance(alice), 600u64);
-        assert_eq!(staking.get_staked_balance(alice), 400u64);
+    // Report misbehaviour of an active validator, slashing `fraction` of its bonded stake
+    // along with the same fraction of the stake each nominator backed it with when it was
+    // elected. The slash is applied `SLASH_DEFER_DURATION` eras later unless governance
+    // cancels it first. Returns the ID of the slash.
+    pub fn slash(
+        &mut self,
+        validator: T::AccountId,
+        fraction: Perbill,
+    ) -> Result<u32, StakingError> {
+        self.report_slash(validator, fraction, Vec::new())
+    }
 
-        // Unstake tokens
-        let result = staking.unstake(alice, 100);
-        assert!(result.is_ok());
+    // Like `slash`, but `slash_reward_fraction` of the slashed funds is shared evenly by the
+    // reporters when the slash is applied
+    pub fn report_slash(
+        &mut self,
+        validator: T::AccountId,
+        fraction: Perbill,
+        reporters: Vec<T::AccountId>,
+    ) -> Result<u32, StakingError> {
+        let exposure = self
+            .eras_stakers
+            .get(&self.current_era)
+            .and_then(|stakers| stakers.iter().find(|exposure| exposure.validator == validator))
+            .ok_or(StakingError::NotActiveValidator)?;
+        let own = exposure.own;
+        let others = exposure
+            .others
+            .iter()
+            .map(|other| IndividualExposure { who: other.who.clone(), value: other.value })
+            .collect();
+        let apply_era = self
+            .current_era
+            .checked_add(T::SLASH_DEFER_DURATION)
+            .ok_or(StakingError::Overflow)?;
 
-        // Check balances after unstaking
-        assert_eq!(staking.get_free_balance(alice), 700u64);
-        assert_eq!(staking.get_staked_balance(alice), 300u64);
+        let current_era = self.current_era;
+        let spans = self.slashing_spans.entry(validator.clone()).or_insert_with(|| {
+            let max_fraction = Perbill::default();
+            vec![SlashingSpan { index: 0, start_era: current_era, max_fraction }]
+        });
+        let span_index = spans.last().map_or(0, |span| span.index);
+
+        let slash_id = self.next_slash_id;
+        self.next_slash_id += 1;
+        let slash =
+            UnappliedSlash { validator, fraction, own, others, apply_era, reporters, span_index };
+        match T::SLASH_DEFER_DURATION {
+            0 => self.apply_slash(slash),
+            _ => {
+                self.pending_slashes.insert(slash_id, slash);
+            },
+        }
+        Ok(slash_id)
     }
 
-    #[test]
-    fn test_staking_errors() {
-        let bob = 2u64;
-        let mut staking = StakingPallet::<Runtime>::new();
+    // Drop a pending slash before it is applied. Requires root or the council.
+    pub fn cancel_slash(&mut self, origin: Origin<T>, slash_id: u32) -> Result<(), StakingError> {
+        if !matches!(origin, Origin::Root | Origin::Council) {
+            return Err(StakingError::BadOrigin);
+        }
+        self.pending_slashes.remove(&slash_id).ok_or(StakingError::SlashNotFound)?;
+        Ok(())
+    }
 
-        // Set initial balance
-        staking.set_balance(bob, 500);
+    // Whether a pending slash will take from an account's stake, as a validator or nominator
+    fn has_pending_slash(&self, who: &T::AccountId) -> bool {
+        self.pending_slashes.values().any(|slash| {
+            slash.validator == *who || slash.others.iter().any(|other| other.who == *who)
+        })
+    }
 
-        // Try to stake more than available
-        let result = staking.stake(bob, 600);
-        assert!(result.is_err());
+    // Get the slashes waiting to be applied, by ID
+    pub fn pending_slashes(&self) -> &BTreeMap<u32, UnappliedSlash<T>> {
+        &self.pending_slashes
+    }
 
-        // Stake valid amount
-        let result = staking.stake(bob, 300);
-        assert!(result.is_ok());
+    // Get a validator's slashing spans, oldest first
+    pub fn slashing_spans(&self, validator: &T::AccountId) -> &[SlashingSpan] {
+        self.slashing_spans.get(validator).map_or(&[][..], Vec::as_slice)
+    }
 
-        // Try to unstake more than staked
-        let result = staking.unstake(bob, 400);
-        assert!(result.is_err());
+    // Start a new slashing span for a validator that was slashed before, so later offences are
+    // punished again
+    fn end_span(&mut self, validator: &T::AccountId) {
+        let current_era = self.current_era;
+        if let Some(spans) = self.slashing_spans.get_mut(validator) {
+            let index = spans.last().map_or(0, |span| span.index + 1);
+            spans.push(SlashingSpan {
+                index,
+                start_era: current_era,
+                max_fraction: Perbill::default(),
+            });
+        }
+    }
+
+    // Remove the part of a slash's fraction above what was already slashed in its span from
+    // the stake it exposed, capped at what each account still has staked. The reporters' reward
+    // is paid out of the funds and the rest goes to
+    // `SLASH_DESTINATION` or is burned.
+    fn apply_slash(&mut self, slash: UnappliedSlash<T>) {
+        let span_index = slash.span_index;
+        let UnappliedSlash { validator, fraction, own, others: exposed, reporters, .. } = slash;
+        let span = self
+            .slashing_spans
+            .get_mut(&validator)
+            .and_then(|spans| spans.iter_mut().find(|span| span.index == span_index));
+        let fraction = match span {
+            Some(span) => {
+                let excess = fraction.deconstruct().saturating_sub(span.max_fraction.deconstruct());
+                span.max_fraction = span.max_fraction.max(fraction);
+                Perbill::from_parts(excess)
+            },
+            None => fraction,
+        };
+        if fraction == Perbill::default() {
+            return;
+        }
+
+        let amount = self.slash_stake(validator.clone(), fraction, own);
+        let mut total = amount;
+        let mut others = Vec::new();
+        for exposure in exposed {
+            let slashed = self.slash_stake(exposure.who.clone(), fraction, exposure.value);
+            total = total.checked_add(&slashed).unwrap_or(total);
+            others.push(IndividualExposure { who: exposure.who, value: slashed });
+        }
+
+        let mut remaining = total;
+        if !reporters.is_empty() {
+            let reward = self.parameters.slash_reward_fraction.mul_floor(total.into());
+            let share = reward / reporters.len() as u128;
+            // A share never exceeds the slashed total, so it always fits in a balance
+            let share = T::Balance::try_from(share).unwrap_or_else(|_| Zero::zero());
+            for reporter in reporters {
+                let free_balance = self.get_free_balance(reporter.clone());
+                if let Some(new_free_balance) = free_balance.checked_add(&share) {
+                    self.free_balances.insert(reporter, new_free_balance);
+                    self.issuance = self.issuance.saturating_add(share.into());
+                    remaining = remaining.checked_sub(&share).unwrap_or_else(Zero::zero);
+                }
+            }
+        }
+
+        let beneficiary = T::SLASH_DESTINATION;
+        if let Some(destination) = &beneficiary {
+            let free_balance = self.get_free_balance(destination.clone());
+            if let Some(new_free_balance) = free_balance.checked_add(&remaining) {
+                self.free_balances.insert(destination.clone(), new_free_balance);
+                self.issuance = self.issuance.saturating_add(remaining.into());
+            }
+        }
+
+        self.slashes.push(SlashEvent { validator, fraction, amount, others, beneficiary });
+    }
+
+    // Remove `fraction` of `exposed`, the stake the era's snapshot recorded, from an account's
+    // bonded and unbonding funds, returning the amount removed
+    fn slash_stake(
+        &mut self,
+        who: T::AccountId,
+        fraction: Perbill,
+        exposed: T::Balance,
+    ) -> T::Balance {
+        // The result never exceeds the exposed stake, so it always fits in a balance
+        let amount = T::Balance::try_from(fraction.mul_floor(exposed.into())).unwrap_or(exposed);
+        let (amount, active) = match self.ledgers.get_mut(&who) {
+            Some(ledger) => {
+                let active = ledger.active;
+                let amount = ledger.slash(amount);
+                (amount, active.checked_sub(&ledger.active).unwrap_or_else(Zero::zero))
+            },
+            None => (T::Balance::zero(), T::Balance::zero()),
+        };
+        self.prune_ledger(&who);
+        // Slashed funds are burned; the parts paid out are minted again by `apply_slash`
+        self.total_bonded = self.total_bonded.saturating_sub(active.into());
+        self.update_bond_lock(&who);
+        self.issuance = self.issuance.saturating_sub(amount.into());
+        self.events.push(StakingEvent::Slashed { staker: who.clone(), amount });
+
+        // The reimbursement moves funds out of the pool, so the issuance is unchanged
+        let cover = self.insurance.claim(&who, amount);
+        if !cover.is_zero() {
+            let free_balance = self.get_free_balance(who.clone());
+            if let Some(new_free_balance) = free_balance.checked_add(&cover) {
+                self.free_balances.insert(who.clone(), new_free_balance);
+                self.events.push(StakingEvent::Reimbursed { who, amount: cover });
+            }
+        }
+        amount
+    }
+
+    // Insure the account's stake against slashes, paying a premium out of each era reward
+    pub fn join_insurance(&mut self, signer: T::AccountId) -> Result<(), StakingError> {
+        let who = self.stash_of(&signer)?;
+        if self.get_staked_balance(who.clone()).is_zero() {
+            return Err(StakingError::NotBonded);
+        }
+        if !self.insurance.members.insert(who) {
+            return Err(StakingError::AlreadyInsured);
+        }
+        Ok(())
+    }
+
+    // Stop insuring the account's stake. Premiums already paid stay in the pool.
+    pub fn leave_insurance(&mut self, signer: T::AccountId) -> Result<(), StakingError> {
+        let who = self.stash_of(&signer)?;
+        if !self.insurance.members.remove(&who) {
+            return Err(StakingError::NotInsured);
+        }
+        Ok(())
+    }
+
+    // Let `controller` sign the staking operations of a bonded stash (nominating, validating,
+    // chilling and managing its validator preferences, keys and payee), so the stash itself
+    // can stay in cold storage. Setting the stash as its own controller removes the controller.
+    pub fn set_controller(
+        &mut self,
+        stash: T::AccountId,
+        controller: T::AccountId,
+    ) -> Result<(), StakingError> {
+        if self.get_staked_balance(stash.clone()).is_zero() {
+            return Err(StakingError::NotBonded);
+        }
+        if controller == stash {
+            self.controllers.remove(&stash);
+            return Ok(());
+        }
+        let paired = self.controllers.iter().any(|(other, controlled_by)| {
+            *other == controller || (*controlled_by == controller && *other != stash)
+        });
+        if paired || self.ledgers.contains_key(&controller) {
+            return Err(StakingError::AlreadyPaired);
+        }
+
+        self.controllers.insert(stash, controller);
+        Ok(())
+    }
+
+    // Get the account signing a stash's staking operations
+    pub fn controller_of(&self, stash: &T::AccountId) -> T::AccountId {
+        self.controllers.get(stash).cloned().unwrap_or_else(|| stash.clone())
+    }
+
+    // Find the stash a signer acts for: the stash it controls, or its own account if it has no
+    // controller of its own
+    fn stash_of(&self, signer: &T::AccountId) -> Result<T::AccountId, StakingError> {
+        let controlled = self.controllers.iter().find(|(_, controller)| *controller == signer);
+        if let Some((stash, _)) = controlled {
+            return Ok(stash.clone());
+        }
+        if self.controllers.contains_key(signer) {
+            return Err(StakingError::NotController);
+        }
+        Ok(signer.clone())
+    }
+
+    // Back validators with the nominator's stake, replacing any previous nominations. The
+    // stake is split evenly between the targets, which must all be validators.
+    pub fn nominate(
+        &mut self,
+        signer: T::AccountId,
+        targets: Vec<T::AccountId>,
+    ) -> Result<(), StakingError> {
+        let nominator = self.stash_of(&signer)?;
+        if self.get_staked_balance(nominator.clone()) < T::MIN_NOMINATOR_BOND {
+            return Err(StakingError::InsufficientNominatorBond);
+        }
+        if self.is_validator(&nominator) {
+            return Err(StakingError::ValidatorCannotNominate);
+        }
+        if targets.is_empty() {
+            return Err(StakingError::NoTargets);
+        }
+        let current = self.nominations.get(&nominator).map_or(&[][..], Vec::as_slice);
+        for (index, target) in targets.iter().enumerate() {
+            if !self.is_validator(target) {
+                return Err(StakingError::TargetNotValidator);
+            }
+            if self.blocked.contains(target) && !current.contains(target) {
+                return Err(StakingError::TargetBlocked);
+            }
+            if targets[..index].contains(target) {
+                return Err(StakingError::DuplicateTarget);
+            }
+        }
+
+        self.nominations.insert(nominator, targets);
+        Ok(())
+    }
+
+    // Move a nomination from one validator to another at the start of the next era, before the
+    // election. The stake stays bonded the whole time, so no unbonding period applies.
+    pub fn renominate(
+        &mut self,
+        signer: T::AccountId,
+        from: T::AccountId,
+        to: T::AccountId,
+    ) -> Result<(), StakingError> {
+        let nominator = self.stash_of(&signer)?;
+        let current = self.nominations.get(&nominator).map_or(&[][..], Vec::as_slice);
+        if !current.contains(&from) {
+            return Err(StakingError::NotNominated);
+        }
+        if current.contains(&to) {
+            return Err(StakingError::DuplicateTarget);
+        }
+        if !self.is_validator(&to) {
+            return Err(StakingError::TargetNotValidator);
+        }
+        if self.blocked.contains(&to) {
+            return Err(StakingError::TargetBlocked);
+        }
+        let mut queued = self.pending_renominations.iter();
+        if queued.any(|(who, queued_from, _)| *who == nominator && *queued_from == from) {
+            return Err(StakingError::AlreadyQueued);
+        }
+
+        self.pending_renominations.push((nominator, from, to));
+        Ok(())
+    }
+
+    // Apply the queued nomination moves that are still valid: the nominator must still back
+    // `from`, and `to` must still be an unblocked validator it does not already back
+    fn apply_renominations(&mut self) {
+        for (nominator, from, to) in std::mem::take(&mut self.pending_renominations) {
+            let allowed = self.is_validator(&to) && !self.blocked.contains(&to);
+            let targets = match self.nominations.get_mut(&nominator) {
+                Some(targets) if allowed && !targets.contains(&to) => targets,
+                _ => continue,
+            };
+            if let Some(target) = targets.iter_mut().find(|target| **target == from) {
+                *target = to.clone();
+                self.events.push(StakingEvent::Renominated { nominator, from, to });
+            }
+        }
+    }
+
+    // Get the stake currently backing a validator: its own and the shares of its largest
+    // `max_nominators_per_validator` nominators. Nominators with equal shares at the cut-off
+    // are kept in no particular order.
+    pub fn exposure_of(&self, validator: &T::AccountId) -> Exposure<T> {
+        let own = self.get_staked_balance(validator.clone());
+        let mut others: Vec<IndividualExposure<T>> = Vec::new();
+        for (nominator, targets) in &self.nominations {
+            if !targets.contains(validator) {
+                continue;
+            }
+            let backed = targets.iter().filter(|target| self.is_candidate(target)).count();
+            let stake: u128 = self.get_staked_balance(nominator.clone()).into();
+            let share = stake / (backed.max(1) as u128);
+            // A share never exceeds the nominator's stake, so it always fits in a balance
+            if let Ok(value) = T::Balance::try_from(share) {
+                if !value.is_zero() {
+                    others.push(IndividualExposure { who: nominator.clone(), value });
+                }
+            }
+        }
+        self.clip_nominators(&mut others);
+        let commission = self.commissions.get(validator).copied().unwrap_or_default();
+        Exposure { validator: validator.clone(), own, others, commission }
+    }
+
+    // Keep the largest `max_nominators_per_validator` shares of a validator's exposure
+    pub fn clip_nominators(&self, others: &mut Vec<IndividualExposure<T>>) {
+        others.sort_by_key(|other| Reverse(other.value.into()));
+        others.truncate(self.parameters.max_nominators_per_validator as usize);
+    }
+
+    // Get the validators elected this era that the nominator backs but whose exposure leaves
+    // it out, so it earns nothing from them
+    pub fn oversubscribed_targets(&self, nominator: &T::AccountId) -> Vec<T::AccountId> {
+        let targets = match self.nominations.get(nominator) {
+            Some(targets) => targets,
+            None => return Vec::new(),
+        };
+        let stakers = self.eras_stakers.get(&self.current_era).map_or(&[][..], Vec::as_slice);
+        stakers
+            .iter()
+            .filter(|exposure| targets.contains(&exposure.validator))
+            .filter(|exposure| exposure.others.iter().all(|other| other.who != *nominator))
+            .map(|exposure| exposure.validator.clone())
+            .collect()
+    }
+
+    // Set the commission a validator takes off the top of its era rewards before the rest is
+    // shared with its nominators. It cannot exceed the `max_commission` parameter.
+    pub fn set_commission(
+        &mut self,
+        signer: T::AccountId,
+        commission: Perbill,
+    ) -> Result<(), StakingError> {
+        let validator = self.stash_of(&signer)?;
+        if !self.is_validator(&validator) {
+            return Err(StakingError::NotValidating);
+        }
+        if commission > self.parameters.max_commission {
+            return Err(StakingError::CommissionTooHigh);
+        }
+        if commission < self.parameters.min_commission {
+            return Err(StakingError::CommissionTooLow);
+        }
+
+        let previous = self.commissions.insert(validator, commission).unwrap_or_default();
+        self.commission_total = self
+            .commission_total
+            .saturating_sub(previous.deconstruct().into())
+            .saturating_add(commission.deconstruct().into());
+        Ok(())
+    }
+
+    // Maintenance call after `min_commission` is raised: bring every validator whose commission
+    // is below it up to the minimum. Anyone can trigger it. Returns the validators raised.
+    pub fn apply_min_commission(&mut self) -> Vec<T::AccountId> {
+        let min_commission = self.parameters.min_commission;
+        let mut raised = Vec::new();
+        for validator in &self.validators {
+            let commission = self.commissions.get(validator).copied().unwrap_or_default();
+            if commission < min_commission {
+                self.commissions.insert(validator.clone(), min_commission);
+                let raise = min_commission.deconstruct() - commission.deconstruct();
+                self.commission_total = self.commission_total.saturating_add(raise.into());
+                raised.push(validator.clone());
+            }
+        }
+        raised
+    }
+
+    // Set whether a validator refuses new nominations. Its existing nominators are kept, but
+    // can be removed with `kick`.
+    pub fn set_blocked(
+        &mut self,
+        signer: T::AccountId,
+        blocked: bool,
+    ) -> Result<(), StakingError> {
+        let validator = self.stash_of(&signer)?;
+        if !self.is_validator(&validator) {
+            return Err(StakingError::NotValidating);
+        }
+
+        match blocked {
+            true => self.blocked.insert(validator),
+            false => self.blocked.remove(&validator),
+        };
+        Ok(())
+    }
+
+    // Remove a validator from the targets of the given nominators. Nominators left without
+    // targets stop nominating; accounts not nominating the validator are ignored.
+    pub fn kick(
+        &mut self,
+        signer: T::AccountId,
+        nominators: Vec<T::AccountId>,
+    ) -> Result<(), StakingError> {
+        let validator = self.stash_of(&signer)?;
+        if !self.is_validator(&validator) {
+            return Err(StakingError::NotValidating);
+        }
+
+        for nominator in nominators {
+            if let Some(targets) = self.nominations.get_mut(&nominator) {
+                targets.retain(|target| *target != validator);
+                if targets.is_empty() {
+                    self.nominations.remove(&nominator);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Ensure a validator or nominator keeps at least the minimum bond of its role
+    fn ensure_role_bond(
+        &self,
+        who: &T::AccountId,
+        staked_balance: T::Balance,
+    ) -> Result<(), StakingError> {
+        if self.is_validator(who) && staked_balance < T::MIN_VALIDATOR_BOND {
+            return Err(StakingError::InsufficientValidatorBond);
+        }
+        if self.nominations.contains_key(who) && staked_balance < T::MIN_NOMINATOR_BOND {
+            return Err(StakingError::InsufficientNominatorBond);
+        }
+        Ok(())
+    }
+
+    // Chill validators and nominators whose stake fell below the minimum bond of their role,
+    // e.g. after a slash, returning the accounts chilled. Nominators are chilled by dropping
+    // their nominations.
+    pub fn kick_below_minimum(&mut self) -> Vec<T::AccountId> {
+        let mut kicked = Vec::new();
+        for validator in &self.validators {
+            let below = self.get_staked_balance(validator.clone()) < T::MIN_VALIDATOR_BOND;
+            if below && !self.chilled.contains(validator) {
+                kicked.push(validator.clone());
+            }
+        }
+        for validator in kicked.clone() {
+            let _ = self.chill_stash(validator);
+        }
+
+        let nominators: Vec<T::AccountId> = self
+            .nominations
+            .keys()
+            .filter(|who| self.get_staked_balance((*who).clone()) < T::MIN_NOMINATOR_BOND)
+            .cloned()
+            .collect();
+        for nominator in nominators {
+            self.nominations.remove(&nominator);
+            self.events.push(StakingEvent::NominatorChilled { nominator: nominator.clone() });
+            kicked.push(nominator);
+        }
+        kicked
+    }
+
+    // Offer to validate with the account's stake, dropping any nominations it made. A chilled
+    // validator resumes validating.
+    pub fn validate(&mut self, signer: T::AccountId) -> Result<(), StakingError> {
+        let who = self.stash_of(&signer)?;
+        if self.get_staked_balance(who.clone()) < T::MIN_VALIDATOR_BOND {
+            return Err(StakingError::InsufficientValidatorBond);
+        }
+        if !self.is_allowlisted(&who) {
+            return Err(StakingError::NotAllowlisted);
+        }
+        if self.chilled.remove(&who) {
+            return Ok(());
+        }
+        if self.is_validator(&who) {
+            return Err(StakingError::AlreadyValidating);
+        }
+
+        self.nominations.remove(&who);
+        let commission = self.commissions.get(&who).copied().unwrap_or_default();
+        self.commission_total =
+            self.commission_total.saturating_add(commission.deconstruct().into());
+        self.validator_index.insert(who.clone());
+        self.validators.push(who);
+        Ok(())
+    }
+
+    // Register the consensus keys a validator authors blocks with, replacing any set before.
+    // Validators without keys are not elected.
+    pub fn set_keys(&mut self, signer: T::AccountId, keys: Vec<u8>) -> Result<(), StakingError> {
+        let who = self.stash_of(&signer)?;
+        if self.get_staked_balance(who.clone()).is_zero() {
+            return Err(StakingError::NotBonded);
+        }
+        if keys.is_empty() {
+            return Err(StakingError::EmptySessionKeys);
+        }
+
+        self.session_keys.insert(who, keys);
+        Ok(())
+    }
+
+    // Get the consensus keys registered by a validator
+    pub fn session_keys(&self, who: &T::AccountId) -> Option<&[u8]> {
+        self.session_keys.get(who).map(Vec::as_slice)
+    }
+
+    // Step out of elections and reward payouts, keeping the stake bonded, until `validate` is
+    // called again. Nominations of the validator are kept but back nobody in the meantime.
+    pub fn chill(&mut self, signer: T::AccountId) -> Result<(), StakingError> {
+        let validator = self.stash_of(&signer)?;
+        self.chill_stash(validator)
+    }
+
+    // Chill a validator's stash, whoever controls it
+    fn chill_stash(&mut self, validator: T::AccountId) -> Result<(), StakingError> {
+        if !self.is_validator(&validator) {
+            return Err(StakingError::NotValidating);
+        }
+        if !self.chilled.insert(validator.clone()) {
+            return Err(StakingError::AlreadyChilled);
+        }
+        self.end_span(&validator);
+        if self.era_exposure(self.current_era, &validator).is_some() {
+            self.eras_chilled.entry(self.current_era).or_default().insert(validator.clone());
+        }
+        self.events.push(StakingEvent::ValidatorChilled { validator });
+        Ok(())
+    }
+
+    // Get the validators standing for election: not chilled, allowlisted in permissioned mode
+    // and with session keys, in the order they started validating
+    pub fn candidates(&self) -> Vec<T::AccountId> {
+        self.validators
+            .iter()
+            .filter(|validator| self.is_candidate(validator))
+            .filter(|validator| self.session_keys.contains_key(validator))
+            .cloned()
+            .collect()
+    }
+
+    // Whether an account offers to validate, chilled or not
+    pub fn is_validator(&self, who: &T::AccountId) -> bool {
+        self.validator_index.contains(who)
+    }
+
+    // Whether a validator currently stands for election
+    fn is_candidate(&self, who: &T::AccountId) -> bool {
+        self.is_validator(who) && !self.chilled.contains(who) && self.is_allowlisted(who)
+    }
+
+    // End-of-block hook: close the current era once `BLOCKS_PER_ERA` blocks have passed,
+    // chilling validators with too little uptime, expire the eras past the claim horizon,
+    // apply the slashes due in the next one, prune old payout records and elect its validators
+    pub fn on_finalize(&mut self, block_number: T::BlockNumber) {
+        self.on_finalize_with_hooks(block_number, &mut ());
+    }
+
+    // Same as `on_finalize`, telling `hooks` when an era ends and when the next one starts
+    pub fn on_finalize_with_hooks(
+        &mut self,
+        block_number: T::BlockNumber,
+        hooks: &mut impl EraHooks<T>,
+    ) {
+        let era_end = match self.era_start.checked_add(&T::BLOCKS_PER_ERA) {
+            Some(era_end) => era_end,
+            None => return,
+        };
+        let next_block = match block_number.checked_add(&T::BlockNumber::one()) {
+            Some(next_block) => next_block,
+            None => return,
+        };
+        if next_block < era_end {
+            return;
+        }
+
+        self.end_era();
+        hooks.on_era_end(self, self.current_era);
+        self.track_uptime();
+        self.current_era += 1;
+        self.era_start = next_block;
+        self.expire_eras();
+        let current_era = self.current_era;
+        let due: Vec<u32> = self
+            .pending_slashes
+            .iter()
+            .filter(|(_, slash)| slash.apply_era <= current_era)
+            .map(|(slash_id, _)| *slash_id)
+            .collect();
+        for slash_id in due {
+            if let Some(slash) = self.pending_slashes.remove(&slash_id) {
+                self.apply_slash(slash);
+            }
+        }
+        self.prune_payout_history();
+        self.apply_renominations();
+        self.elect_validators();
+        self.process_fast_unstake();
+        hooks.on_era_start(self, self.current_era);
+    }
+
+    // Mint the reward of the era that just ended with the configured inflation model, based on
+    // the stake backing its validators and the total issuance. Rewards that do not fit in a
+    // balance are not minted.
+    fn end_era(&mut self) {
+        let total_staked = self
+            .eras_stakers
+            .get(&self.current_era)
+            .map_or(0u128, |stakers| {
+                stakers.iter().fold(0, |total, exposure| total.saturating_add(exposure.total()))
+            });
+        let reward = T::EraPayout::era_payout(total_staked, self.total_issuance());
+        if let Ok(reward) = T::Balance::try_from(reward) {
+            self.eras_validator_reward.insert(self.current_era, reward);
+        }
+    }
+
+    // Heartbeat of an active validator, like `pallet_im_online::heartbeat`: record that it is
+    // online in the current era. One heartbeat per era counts toward its uptime.
+    pub fn im_online(&mut self, validator: T::AccountId) -> Result<(), StakingError> {
+        if !self.active_set.contains(&validator) {
+            return Err(StakingError::NotActiveValidator);
+        }
+        if !self.heartbeats.insert(validator) {
+            return Err(StakingError::DuplicateHeartbeat);
+        }
+        Ok(())
+    }
+
+    // Get a validator's uptime over the eras it was active in
+    pub fn uptime_of(&self, validator: &T::AccountId) -> UptimeRecord {
+        self.uptime.get(validator).copied().unwrap_or_default()
+    }
+
+    // Update the uptime of the validators active in the era that just ended and chill those
+    // whose score fell below `min_uptime`
+    fn track_uptime(&mut self) {
+        let heartbeats = std::mem::take(&mut self.heartbeats);
+        let mut offline = Vec::new();
+        for validator in &self.active_set {
+            let record = self.uptime.entry(validator.clone()).or_default();
+            record.active_eras = record.active_eras.saturating_add(1);
+            if heartbeats.contains(validator) {
+                record.online_eras = record.online_eras.saturating_add(1);
+            }
+            let uptime = record.score();
+            if uptime < self.parameters.min_uptime {
+                offline.push((validator.clone(), uptime));
+            }
+        }
+        for (validator, uptime) in offline {
+            if self.chill_stash(validator.clone()).is_ok() {
+                self.events.push(StakingEvent::ValidatorOffline { validator, uptime });
+            }
+        }
+    }
+
+    // Drop the data of eras past `REWARD_CLAIM_HORIZON`, paying the rewards nobody claimed for
+    // them to `UNCLAIMED_REWARD_DESTINATION`
+    fn expire_eras(&mut self) {
+        let oldest = self.current_era.saturating_sub(T::REWARD_CLAIM_HORIZON);
+        let kept = self.eras_validator_reward.split_off(&oldest);
+        let expired = std::mem::replace(&mut self.eras_validator_reward, kept);
+        for (era_index, amount) in expired {
+            let stakers = self.eras_stakers.get(&era_index);
+            if self.paid_eras.contains(&era_index) || stakers.is_none_or(Vec::is_empty) {
+                continue;
+            }
+            if let Some(destination) = T::UNCLAIMED_REWARD_DESTINATION {
+                let free_balance = self.get_free_balance(destination.clone());
+                if let Some(new_free_balance) = free_balance.checked_add(&amount) {
+                    self.free_balances.insert(destination, new_free_balance);
+                    self.issuance = self.issuance.saturating_add(amount.into());
+                    self.events.push(StakingEvent::EraRewardExpired { era_index, amount });
+                }
+            }
+        }
+        self.eras_stakers = self.eras_stakers.split_off(&oldest);
+        self.eras_chilled = self.eras_chilled.split_off(&oldest);
+        self.eras_reward_points = self.eras_reward_points.split_off(&oldest);
+        self.paid_eras.retain(|era_index| *era_index >= oldest);
+    }
+
+    // Get the total issuance: every free, staked, reserved and unbonding balance, and the
+    // funds of the insurance pool
+    pub fn total_issuance(&self) -> u128 {
+        self.issuance
+    }
+
+    // Get a summary of the staking system from the running totals, without scanning accounts
+    pub fn staking_overview(&self) -> StakingStats {
+        let average_commission = match self.validators.len() as u128 {
+            0 => Perbill::default(),
+            count => Perbill::from_parts((self.commission_total / count) as u32),
+        };
+        let staking_rate = match self.issuance {
+            0 => Perbill::default(),
+            issuance => Perbill::from_rational(self.total_bonded, issuance),
+        };
+        StakingStats {
+            total_bonded: self.total_bonded,
+            validator_count: self.validators.len() as u32,
+            nominator_count: self.nominations.len() as u32,
+            average_commission,
+            staking_rate,
+        }
+    }
+
+    // Elect the validators of the new era into the active set with the configured election
+    // provider, recording their exposures
+    fn elect_validators(&mut self) {
+        let mut stakers = T::ElectionProvider::elect(self);
+        // Too few winners: keep the previous validators, with the stake now backing them
+        let previous = &self.active_set;
+        if stakers.len() < self.parameters.min_validators as usize && !previous.is_empty() {
+            stakers = previous.iter().map(|validator| self.exposure_of(validator)).collect();
+        }
+
+        self.active_set = stakers.iter().map(|exposure| exposure.validator.clone()).collect();
+        self.eras_stakers.insert(self.current_era, stakers);
+    }
+
+    // Choose where the account's era rewards are paid
+    pub fn set_payee(
+        &mut self,
+        signer: T::AccountId,
+        destination: RewardDestination<T>,
+    ) -> Result<(), StakingError> {
+        let who = self.stash_of(&signer)?;
+        if self.get_staked_balance(who.clone()).is_zero() {
+            return Err(StakingError::NotBonded);
+        }
+
+        match destination {
+            RewardDestination::Stash => self.payees.remove(&who),
+            destination => self.payees.insert(who, destination),
+        };
+        Ok(())
+    }
+
+    // Opt in or out of re-bonding era rewards automatically. Enabling sets the payee to
+    // `Staked`; disabling pays rewards to the free balance again.
+    pub fn set_auto_compound(
+        &mut self,
+        who: T::AccountId,
+        enabled: bool,
+    ) -> Result<(), StakingError> {
+        let destination = match enabled {
+            true => RewardDestination::Staked,
+            false => RewardDestination::Stash,
+        };
+        self.set_payee(who, destination)
+    }
+
+    // Get whether the account's era rewards are re-bonded automatically
+    pub fn is_auto_compounding(&self, who: &T::AccountId) -> bool {
+        matches!(self.payees.get(who), Some(RewardDestination::Staked))
+    }
+
+    // Get the exposure snapshot of a validator elected in an era, which rewards and slashes
+    // for that era are based on
+    pub fn era_exposure(&self, era_index: u32, validator: &T::AccountId) -> Option<&Exposure<T>> {
+        self.eras_stakers
+            .get(&era_index)
+            .and_then(|stakers| stakers.iter().find(|exposure| exposure.validator == *validator))
+    }
+
+    // Get the validators elected for the current era
+    pub fn active_validators(&self) -> &[T::AccountId] {
+        &self.active_set
+    }
+
+    // Block production hook: award `AUTHORING_POINTS` to the active validator that authored a
+    // block of the current era
+    pub fn note_author(&mut self, validator: T::AccountId) -> Result<(), StakingError> {
+        self.reward_by_ids(vec![(validator, T::AUTHORING_POINTS)])
+    }
+
+    // Award era points to active validators for their participation in the current era (e.g.
+    // producing blocks or attesting to them). Fails without awarding any if one is not active.
+    pub fn reward_by_ids(&mut self, rewards: Vec<(T::AccountId, u32)>) -> Result<(), StakingError> {
+        if rewards.iter().any(|(validator, _)| !self.active_set.contains(validator)) {
+            return Err(StakingError::NotActiveValidator);
+        }
+        let points = self.eras_reward_points.entry(self.current_era).or_default();
+        for (validator, earned) in rewards {
+            let total = points.entry(validator).or_insert(0);
+            *total = total.saturating_add(earned);
+        }
+        Ok(())
+    }
+
+    // Get the era points a validator earned in an era
+    pub fn era_points(&self, era_index: u32, validator: &T::AccountId) -> u32 {
+        self.eras_reward_points
+            .get(&era_index)
+            .and_then(|points| points.get(validator))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // Pay out the reward minted for an ended era to its active validators in proportion to the era
+    // points they earned, or to the stake backing them if none earned any. Each validator takes
+    // its commission, capped at the current maximum, and shares the rest with its nominators
+    // by stake. Each reward goes to the staker's payee.
+    // Returns the total paid. Anyone can trigger it, but each era is paid only once.
+    pub fn payout_era(&mut self, era_index: u32) -> Result<T::Balance, StakingError> {
+        if era_index >= self.current_era {
+            return Err(StakingError::EraNotEnded);
+        }
+        if era_index < self.current_era.saturating_sub(T::REWARD_CLAIM_HORIZON) {
+            return Err(StakingError::EraExpired);
+        }
+        if self.paid_eras.contains(&era_index) {
+            return Err(StakingError::AlreadyPaid);
+        }
+        let stakers = self.eras_stakers.get(&era_index).map_or(&[][..], Vec::as_slice);
+
+        let era_stake = stakers
+            .iter()
+            .fold(0u128, |total, exposure| total.saturating_add(exposure.total()));
+        let points = self.eras_reward_points.get(&era_index);
+        let era_points = points.map_or(0, |points| {
+            points.values().fold(0u128, |total, earned| total.saturating_add(*earned as u128))
+        });
+        let era_reward: u128 = self
+            .eras_validator_reward
+            .get(&era_index)
+            .map_or(0, |reward| (*reward).into());
+        let treasury_cut = T::RewardSplitter::treasury_cut(era_reward).min(era_reward);
+        let reward = era_reward - treasury_cut;
+        let max_commission = self.parameters.max_commission;
+        let mut rewards: Vec<(T::AccountId, u128)> = Vec::new();
+        let mut add_reward = |who: T::AccountId, amount: u128| {
+            match rewards.iter_mut().find(|(account, _)| *account == who) {
+                Some((_, total)) => *total = total.saturating_add(amount),
+                None => rewards.push((who, amount)),
+            }
+        };
+        // Validators that chilled during the era forfeit their part of the reward
+        let chilled = self.eras_chilled.get(&era_index);
+        let paid = stakers
+            .iter()
+            .filter(|exposure| chilled.is_none_or(|set| !set.contains(&exposure.validator)));
+        for exposure in paid {
+            let exposure_stake = exposure.total();
+            let validator_reward = match era_points {
+                0 => reward.saturating_mul(exposure_stake) / era_stake,
+                _ => {
+                    let earned = points.and_then(|points| points.get(&exposure.validator));
+                    reward.saturating_mul(earned.copied().unwrap_or(0) as u128) / era_points
+                }
+            };
+            let rate = exposure.commission.min(max_commission);
+            let commission = T::RewardSplitter::commission(validator_reward, rate);
+            let commission = commission.min(validator_reward);
+            add_reward(exposure.validator.clone(), commission);
+
+            // Shares beyond what is left after commission are never paid
+            let mut shared = validator_reward - commission;
+            let values: Vec<u128> =
+                exposure.others.iter().map(|other| other.value.into()).collect();
+            let (own, others) = T::RewardSplitter::split(shared, exposure.own.into(), &values);
+            let own = (exposure.validator.clone(), own);
+            let others = exposure.others.iter().map(|other| other.who.clone()).zip(others);
+            for (who, share) in std::iter::once(own).chain(others) {
+                let share = share.min(shared);
+                shared -= share;
+                add_reward(who, share);
+            }
+        }
+
+        // Group the rewards by the balance they are paid into: (account, staked, amount)
+        // Insured stakers pay their premium out of their share before it is credited
+        let mut premiums = 0u128;
+        for (who, share) in rewards.iter_mut() {
+            let premium = self.insurance.premium(who, *share).min(*share);
+            *share -= premium;
+            premiums = premiums.saturating_add(premium);
+        }
+
+        let mut credits: Vec<(T::AccountId, bool, u128)> = Vec::new();
+        if let Some(treasury) = T::UNCLAIMED_REWARD_DESTINATION.filter(|_| treasury_cut > 0) {
+            credits.push((treasury, false, treasury_cut));
+        }
+        for (who, share) in &rewards {
+            let (account, staked) = match self.payees.get(who) {
+                Some(RewardDestination::Staked) => (who.clone(), true),
+                Some(RewardDestination::Account(payee)) => (payee.clone(), false),
+                Some(RewardDestination::Stash) | None => (who.clone(), false),
+            };
+            let share = *share;
+            let existing = credits
+                .iter_mut()
+                .find(|(to, is_staked, _)| *to == account && *is_staked == staked);
+            match existing {
+                Some((_, _, total)) => *total = total.saturating_add(share),
+                None => credits.push((account, staked, share)),
+            }
+        }
+
+        // Check every payout before applying any, so a failure pays nobody
+        let mut payouts = Vec::new();
+        let mut total_paid = T::Balance::zero();
+        for (account, staked, share) in credits {
+            let amount = T::Balance::try_from(share).map_err(|_| StakingError::Overflow)?;
+            // Compounded rewards add to the ledger's total as well, which is the larger
+            let balance = match staked {
+                true => self.ledger(&account).map_or(T::Balance::zero(), |ledger| ledger.total),
+                false => self.get_free_balance(account.clone()),
+            };
+            let new_balance = balance.checked_add(&amount).ok_or(StakingError::Overflow)?;
+            total_paid = total_paid.checked_add(&amount).ok_or(StakingError::Overflow)?;
+            payouts.push((account, staked, amount, new_balance));
+        }
+
+        let premiums = T::Balance::try_from(premiums).map_err(|_| StakingError::Overflow)?;
+        self.insurance.funds.checked_add(&premiums).ok_or(StakingError::Overflow)?;
+
+        self.insurance.collect(premiums);
+        self.issuance = self.issuance.saturating_add(premiums.into());
+        for (account, staked, amount, new_balance) in payouts {
+            match staked {
+                true => {
+                    if self.ledger_mut(&account).bond(amount).is_err() {
+                        continue;
+                    }
+                    self.total_bonded = self.total_bonded.saturating_add(amount.into());
+                    self.update_bond_lock(&account);
+                },
+                false => {
+                    self.free_balances.insert(account.clone(), new_balance);
+                },
+            }
+            self.issuance = self.issuance.saturating_add(amount.into());
+            self.events.push(StakingEvent::Rewarded { who: account, amount });
+        }
+        // Record what each staker earned, wherever it was paid
+        for (who, share) in rewards {
+            if let Ok(amount) = T::Balance::try_from(share) {
+                self.payout_history.entry(who).or_default().push((era_index, amount));
+            }
+        }
+        self.paid_eras.insert(era_index);
+        self.events.push(StakingEvent::EraPaid { era_index, total: total_paid });
+        Ok(total_paid)
+    }
+
+    // Get the rewards a staker earned in the eras still within `PAYOUT_HISTORY_DEPTH`, as
+    // (era, amount) in the order they were paid out
+    pub fn payout_history(&self, who: &T::AccountId) -> Vec<(u32, T::Balance)> {
+        self.payout_history.get(who).cloned().unwrap_or_default()
+    }
+
+    // Drop the payout records of eras older than `PAYOUT_HISTORY_DEPTH`
+    fn prune_payout_history(&mut self) {
+        let oldest = self.current_era.saturating_sub(T::PAYOUT_HISTORY_DEPTH);
+        for records in self.payout_history.values_mut() {
+            records.retain(|(era_index, _)| *era_index >= oldest);
+        }
+        self.payout_history.retain(|_, records| !records.is_empty());
+    }
+
+    // Get the events not yet moved to the system pallet
+    pub fn pending_events(&self) -> &[StakingEvent<T>] {
+        &self.events
+    }
+
+    // Move the buffered events into the system pallet, converted to the runtime event type.
+    // Staking calls do not take the system pallet, so the runtime does this every block.
+    pub fn deposit_events(&mut self, system: &mut SystemPallet<T>) {
+        for event in self.events.drain(..) {
+            let event: <T as StakingConfig>::RuntimeEvent = event.into();
+            system.deposit_event(event.into());
+        }
+    }
+
+    // Get the total balance an account is still unbonding
+    pub fn get_unbonding_balance(&self, who: T::AccountId) -> T::Balance {
+        self.ledgers.get(&who).map_or(T::Balance::zero(), StakingLedger::total_unlocking)
+    }
+
+    // Get the ledger of an account, if it has funds bonded or unbonding
+    pub fn ledger(&self, who: &T::AccountId) -> Option<&StakingLedger<T>> {
+        self.ledgers.get(who)
+    }
+
+    // Get the ledger of an account, creating an empty one if it has none
+    fn ledger_mut(&mut self, who: &T::AccountId) -> &mut StakingLedger<T> {
+        self.ledgers.entry(who.clone()).or_insert_with(|| StakingLedger::new(who.clone()))
+    }
+
+    // Drop the ledger of an account once nothing is bonded or unbonding
+    fn prune_ledger(&mut self, who: &T::AccountId) {
+        if self.ledgers.get(who).is_some_and(StakingLedger::is_empty) {
+            self.ledgers.remove(who);
+        }
+    }
+
+    // Get free balance for an account
+    pub fn get_free_balance(&self, who: T::AccountId) -> T::Balance {
+        *self.free_balances.get(&who).unwrap_or(&T::Balance::zero())
+    }
+
+    // Get staked balance for an account
+    pub fn get_staked_balance(&self, who: T::AccountId) -> T::Balance {
+        self.ledgers.get(&who).map_or(T::Balance::zero(), |ledger| ledger.active)
+    }
+
+    // Reserve tokens (move from free to reserved)
+    pub fn reserve(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), StakingError> {
+        let free_balance = self.get_free_balance(who.clone());
+        let new_free_balance = free_balance
+            .checked_sub(&amount)
+            .ok_or(StakingError::InsufficientBalance)?;
+        let new_reserved_balance = self
+            .get_reserved_balance(who.clone())
+            .checked_add(&amount)
+            .ok_or(StakingError::Overflow)?;
+
+        self.free_balances.insert(who.clone(), new_free_balance);
+        self.reserved_balances.insert(who, new_reserved_balance);
+        Ok(())
+    }
+
+    // Unreserve tokens (move from reserved back to free)
+    pub fn unreserve(&mut self, who: T::AccountId, amount: T::Balance) -> Result<(), StakingError> {
+        let reserved_balance = self.get_reserved_balance(who.clone());
+        let new_reserved_balance = reserved_balance
+            .checked_sub(&amount)
+            .ok_or(StakingError::InsufficientReservedBalance)?;
+        let new_free_balance = self
+            .get_free_balance(who.clone())
+            .checked_add(&amount)
+            .ok_or(StakingError::Overflow)?;
+
+        self.reserved_balances.insert(who.clone(), new_reserved_balance);
+        self.free_balances.insert(who, new_free_balance);
+        Ok(())
+    }
+
+    // Slash reserved tokens, returning the amount actually removed
+    pub fn slash_reserved(&mut self, who: T::AccountId, amount: T::Balance) -> T::Balance {
+        let reserved_balance = self.get_reserved_balance(who.clone());
+        let slashed = if amount < reserved_balance { amount } else { reserved_balance };
+
+        if let Some(new_reserved_balance) = reserved_balance.checked_sub(&slashed) {
+            self.reserved_balances.insert(who, new_reserved_balance);
+        }
+        slashed
+    }
+
+    // Lock part of an account's balance under `id` so it cannot be unstaked or transferred,
+    // replacing any lock with the same name, like `LockableCurrency::set_lock`. A zero amount
+    // removes the lock.
+    pub fn set_lock(&mut self, id: LockIdentifier, who: T::AccountId, amount: T::Balance) {
+        if amount.is_zero() {
+            return self.remove_lock(id, who);
+        }
+        self.locks.entry(who).or_default().insert(id, amount);
+    }
+
+    // Raise the lock named `id` to at least `amount`, never lowering it, like
+    // `LockableCurrency::extend_lock`
+    pub fn extend_lock(&mut self, id: LockIdentifier, who: T::AccountId, amount: T::Balance) {
+        if amount > self.get_lock(&who, id) {
+            self.set_lock(id, who, amount);
+        }
+    }
+
+    // Release the lock named `id` on an account's balance
+    pub fn remove_lock(&mut self, id: LockIdentifier, who: T::AccountId) {
+        if let Some(account_locks) = self.locks.get_mut(&who) {
+            account_locks.remove(&id);
+            if account_locks.is_empty() {
+                self.locks.remove(&who);
+            }
+        }
+    }
+
+    // Get the amount locked under `id` for an account
+    pub fn get_lock(&self, who: &T::AccountId, id: LockIdentifier) -> T::Balance {
+        self.locks
+            .get(who)
+            .and_then(|account_locks| account_locks.get(&id))
+            .copied()
+            .unwrap_or_else(Zero::zero)
+    }
+
+    // Get locked balance for an account. Locks overlap, so it is the largest of them, not
+    // their sum.
+    pub fn get_locked_balance(&self, who: T::AccountId) -> T::Balance {
+        self.largest_lock(&who, None)
+    }
+
+    // Largest lock other pallets hold on an account, which its stake must keep covering
+    fn locked_by_others(&self, who: &T::AccountId) -> T::Balance {
+        self.largest_lock(who, Some(STAKING_LOCK))
+    }
+
+    // Largest lock on an account, leaving out the one named `except`
+    fn largest_lock(&self, who: &T::AccountId, except: Option<LockIdentifier>) -> T::Balance {
+        let account_locks = self.locks.get(who).into_iter().flatten();
+        account_locks
+            .filter(|(id, _)| Some(**id) != except)
+            .fold(T::Balance::zero(), |largest, (_, amount)| match *amount > largest {
+                true => *amount,
+                false => largest,
+            })
+    }
+
+    // Keep the staking lock equal to the account's bonded stake
+    fn update_bond_lock(&mut self, who: &T::AccountId) {
+        let staked_balance = self.get_staked_balance(who.clone());
+        self.set_lock(STAKING_LOCK, who.clone(), staked_balance);
+    }
+
+    // Get the part of the free balance not covered by a lock. The staking lock only covers the
+    // bonded stake, so the free balance is locked for whatever larger locks need beyond it.
+    pub fn get_transferable_balance(&self, who: T::AccountId) -> T::Balance {
+        let free_balance = self.get_free_balance(who.clone());
+        let staked_balance = self.get_staked_balance(who.clone());
+        match self.get_locked_balance(who).checked_sub(&staked_balance) {
+            Some(locked_free) => free_balance.checked_sub(&locked_free).unwrap_or_else(Zero::zero),
+            None => free_balance,
+        }
+    }
+
+    // Get reserved balance for an account
+    pub fn get_reserved_balance(&self, who: T::AccountId) -> T::Balance {
+        *self.reserved_balances.get(&who).unwrap_or(&T::Balance::zero())
+    }
+}
+
+impl<T: StakingConfig> Default for StakingPallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Era boundary hooks for other pallets, like `pallet_session::SessionManager`, so they can act
+// on eras (e.g. topping up a treasury or taking snapshots) without staking knowing about them
+pub trait EraHooks<T: StakingConfig> {
+    // Called once an era has ended and its reward has been minted
+    fn on_era_end(&mut self, _staking: &mut StakingPallet<T>, _era_index: u32) {}
+    // Called once an era has started and its validators have been elected
+    fn on_era_start(&mut self, _staking: &mut StakingPallet<T>, _era_index: u32) {}
+}
+
+// No hooks
+impl<T: StakingConfig> EraHooks<T> for () {}
+
+// Run both hooks, the first one first
+impl<T: StakingConfig, A: EraHooks<T>, B: EraHooks<T>> EraHooks<T> for (A, B) {
+    fn on_era_end(&mut self, staking: &mut StakingPallet<T>, era_index: u32) {
+        self.0.on_era_end(staking, era_index);
+        self.1.on_era_end(staking, era_index);
+    }
+
+    fn on_era_start(&mut self, staking: &mut StakingPallet<T>, era_index: u32) {
+        self.0.on_era_start(staking, era_index);
+        self.1.on_era_start(staking, era_index);
+    }
+}
+
+// Read access to bonded stake for pallets that weigh by it, like `sp_staking::StakingInterface`,
+// so they do not depend on how staking stores it
+pub trait StakeProvider<T: StakingConfig> {
+    // Stake an account has bonded
+    fn bonded_of(&self, who: &T::AccountId) -> T::Balance;
+    // Stake bonded by all accounts together
+    fn total_bonded(&self) -> u128;
+    // Stake bonded by each account, to remember it as of a given block
+    fn bonded_snapshot(&self) -> BTreeMap<T::AccountId, T::Balance>;
+}
+
+impl<T: StakingConfig> StakeProvider<T> for StakingPallet<T> {
+    fn bonded_of(&self, who: &T::AccountId) -> T::Balance {
+        self.get_staked_balance(who.clone())
+    }
+
+    fn total_bonded(&self) -> u128 {
+        self.total_bonded
+    }
+
+    fn bonded_snapshot(&self) -> BTreeMap<T::AccountId, T::Balance> {
+        self.ledgers.iter().map(|(stash, ledger)| (stash.clone(), ledger.active)).collect()
+    }
+}
+
+// Role a staker starts in at genesis, like `pallet_staking::StakerStatus`
+pub enum StakerStatus<T: StakingConfig> {
+    Idle,                         // Bonded without validating or nominating
+    Validator(Vec<u8>),           // Validating with the given session keys
+    Nominator(Vec<T::AccountId>), // Nominating the given validators
+}
+
+// Initial state of the staking pallet, like `pallet_staking::GenesisConfig`
+pub struct GenesisConfig<T: StakingConfig> {
+    // Free balance each account starts with
+    pub balances: Vec<(T::AccountId, T::Balance)>,
+    // Accounts endowed with and bonding the given stake, in the role they start in
+    pub stakers: Vec<(T::AccountId, T::Balance, StakerStatus<T>)>,
+}
+
+impl<T: StakingConfig> GenesisConfig<T> {
+    // Build the staking pallet with the stakers bonded and the validators of the first era
+    // elected. Validators are set up before nominators, so nominators can back any of them.
+    pub fn build(self) -> Result<StakingPallet<T>, StakingError> {
+        let mut staking = StakingPallet::<T>::new();
+        for (who, balance) in self.balances {
+            staking.set_balance(who, balance);
+        }
+
+        let mut nominators = Vec::new();
+        for (stash, bonded, status) in self.stakers {
+            let free_balance = staking
+                .get_free_balance(stash.clone())
+                .checked_add(&bonded)
+                .ok_or(StakingError::Overflow)?;
+            staking.set_balance(stash.clone(), free_balance);
+            staking.stake(stash.clone(), bonded)?;
+            match status {
+                StakerStatus::Idle => {},
+                StakerStatus::Validator(keys) => {
+                    staking.validate(stash.clone())?;
+                    staking.set_keys(stash, keys)?;
+                },
+                StakerStatus::Nominator(targets) => nominators.push((stash, targets)),
+            }
+        }
+        for (nominator, targets) in nominators {
+            staking.nominate(nominator, targets)?;
+        }
+
+        staking.elect_validators();
+        // Genesis bonds are not reported as events
+        staking.events.clear();
+        Ok(staking)
+    }
+}
+
+impl<T: StakingConfig> Default for GenesisConfig<T> {
+    fn default() -> Self {
+        Self { balances: Vec::new(), stakers: Vec::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Runtime, RuntimeEvent};
+
+    #[test]
+    fn test_staking_should_work() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        // Set initial balance
+        staking.set_balance(alice, 1000);
+
+        // Check free balance
+        assert_eq!(staking.get_free_balance(alice), 1000u64);
+        assert_eq!(staking.get_staked_balance(alice), 0u64);
+
+        // Stake tokens
+        let result = staking.stake(alice, 400);
+        assert!(result.is_ok());
+
+        // Check balances after staking
+        assert_eq!(staking.get_free_balance(alice), 600u64);
+        assert_eq!(staking.get_staked_balance(alice), 400u64);
+
+        // Unbond tokens, which stay out of the free balance until the unbonding period is over
+        let result = staking.unbond(alice, 100, 0);
+        assert!(result.is_ok());
+        assert_eq!(staking.get_free_balance(alice), 600u64);
+        assert_eq!(staking.get_staked_balance(alice), 300u64);
+
+        // Check balances after withdrawing
+        let unbonding_period = staking.parameters.unbonding_period;
+        staking.withdraw_unbonded(alice, unbonding_period).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 700u64);
+    }
+
+    #[test]
+    fn test_staking_errors() {
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        // Set initial balance
+        staking.set_balance(bob, 500);
+
+        // Try to stake more than available
+        let result = staking.stake(bob, 600);
+        assert!(result.is_err());
+
+        // Stake valid amount
+        let result = staking.stake(bob, 300);
+        assert!(result.is_ok());
+
+        // Try to unstake more than staked
+        let result = staking.unbond(bob, 400, 0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_reserve_should_work() {
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(charlie, 100);
+
+        // Cannot reserve more than the free balance
+        assert!(staking.reserve(charlie, 150).is_err());
+
+        // Reserve and release part of the balance
+        staking.reserve(charlie, 60).unwrap();
+        assert_eq!(staking.get_free_balance(charlie), 40u64);
+        assert_eq!(staking.get_reserved_balance(charlie), 60u64);
+
+        staking.unreserve(charlie, 20).unwrap();
+        assert_eq!(staking.get_free_balance(charlie), 60u64);
+        assert_eq!(staking.get_reserved_balance(charlie), 40u64);
+
+        // Slashing is capped at what is actually reserved
+        assert_eq!(staking.slash_reserved(charlie, 100), 40u64);
+        assert_eq!(staking.get_reserved_balance(charlie), 0u64);
+        assert_eq!(staking.get_free_balance(charlie), 60u64);
+    }
+
+    #[test]
+    fn test_locked_stake_cannot_be_unstaked() {
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(dave, 1000);
+        staking.stake(dave, 500).unwrap();
+        staking.set_lock(*b"testlock", dave, 300);
+
+        // Only the unlocked part of the stake can be withdrawn
+        assert_eq!(staking.unbond(dave, 300, 0), Err(StakingError::LockedBalance));
+        staking.unbond(dave, 200, 0).unwrap();
+        assert_eq!(staking.get_staked_balance(dave), 300u64);
+
+        staking.remove_lock(*b"testlock", dave);
+        staking.unbond(dave, 300, 0).unwrap();
+        assert_eq!(staking.get_staked_balance(dave), 0u64);
+    }
+
+    #[test]
+    fn test_locked_balance_cannot_be_transferred() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 200).unwrap();
+        staking.set_lock(*b"testlock", alice, 500);
+
+        // The lock covers the whole stake and 300 of the free balance
+        assert_eq!(staking.get_transferable_balance(alice), 500u64);
+        assert_eq!(staking.transfer(alice, bob, 501), Err(StakingError::LockedBalance));
+        staking.transfer(alice, bob, 500).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 300u64);
+
+        staking.remove_lock(*b"testlock", alice);
+        assert_eq!(staking.get_transferable_balance(alice), 300u64);
+    }
+
+    #[test]
+    fn test_bonded_balance_cannot_be_transferred() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        // Bonded funds leave the free balance, so they cannot be transferred
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 600).unwrap();
+        assert_eq!(staking.get_transferable_balance(alice), 400u64);
+        let result = staking.transfer(alice, bob, 500);
+        assert_eq!(result, Err(StakingError::InsufficientBalance));
+
+        // Extending a lock only ever raises it
+        staking.extend_lock(*b"testlock", alice, 700);
+        staking.extend_lock(*b"testlock", alice, 200);
+        assert_eq!(staking.get_locked_balance(alice), 700u64);
+        assert_eq!(staking.get_transferable_balance(alice), 300u64);
+    }
+
+    #[test]
+    fn test_bond_lock() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.set_parameter(ParameterChange::UnbondingPeriod(10));
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 0u64);
+
+        // Bonding acquires the staking lock and every change to the bond moves it
+        staking.stake(alice, 500).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 500u64);
+        staking.unbond(alice, 200, 0).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 300u64);
+        staking.rebond(alice, 50).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 350u64);
+        staking.unbond(alice, 100, 0).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 250u64);
+
+        // Unbonding the whole stake releases the lock
+        staking.unbond(alice, 250, 0).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 0u64);
+        assert!(!staking.locks.contains_key(&alice));
+    }
+
+    #[test]
+    fn test_overlapping_locks() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let vesting = *b"vesting ";
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        // The bond is a lock of its own, kept in step with the stake
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 400).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 400u64);
+
+        // Locks overlap, so only the largest one counts
+        staking.set_lock(crate::governance::GOVERNANCE_LOCK, alice, 600);
+        staking.set_lock(vesting, alice, 300);
+        assert_eq!(staking.get_locked_balance(alice), 600u64);
+        assert_eq!(staking.get_transferable_balance(alice), 400u64);
+        assert_eq!(staking.transfer(alice, bob, 401), Err(StakingError::LockedBalance));
+        staking.remove_lock(crate::governance::GOVERNANCE_LOCK, alice);
+        assert_eq!(staking.get_transferable_balance(alice), 600u64);
+
+        // The stake has to keep covering the other locks
+        assert_eq!(staking.unbond(alice, 200, 0), Err(StakingError::LockedBalance));
+        staking.unbond(alice, 100, 0).unwrap();
+        assert_eq!(staking.get_lock(&alice, STAKING_LOCK), 300u64);
+        staking.set_lock(vesting, alice, 0);
+        staking.unbond(alice, 300, 0).unwrap();
+        assert!(staking.locks.is_empty());
+    }
+
+    #[test]
+    fn test_staking_error_into_dispatch_error() {
+        let error: DispatchError = StakingError::NoUnlockedChunks.into();
+        let expected = DispatchError::Module { pallet: "Staking", message: "No unbonding funds" };
+        assert_eq!(error, expected);
+        assert_eq!(error.to_string(), "Staking error: No unbonding funds");
+        assert_eq!(DispatchError::from(StakingError::BadOrigin), DispatchError::BadOrigin);
+    }
+
+    #[test]
+    fn test_transfer_should_work() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 100);
+        assert!(staking.transfer(alice, bob, 150).is_err());
+
+        staking.transfer(alice, bob, 40).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 60u64);
+        assert_eq!(staking.get_free_balance(bob), 40u64);
+
+        // Calls dispatched by the runtime go through the same checks
+        staking.dispatch(StakingCall::Transfer { from: bob, to: alice, amount: 40 }).unwrap();
+        staking.dispatch(StakingCall::SetBalance { who: bob, amount: 5 }).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 100u64);
+        assert_eq!(staking.get_free_balance(bob), 5u64);
+    }
+
+    #[test]
+    fn test_parameter_changes() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.dispatch(StakingCall::SetParameter(ParameterChange::MinStake(100))).unwrap();
+        staking.set_parameter(ParameterChange::MaxValidators(10));
+        staking.set_parameter(ParameterChange::UnbondingPeriod(28));
+        assert_eq!(staking.parameters.min_stake, 100u64);
+        assert_eq!(staking.parameters.max_validators, 10);
+        assert_eq!(staking.parameters.unbonding_period, 28u64);
+
+        // The minimum stake applies to the resulting staked balance
+        assert!(staking.stake(alice, 50).is_err());
+        assert_eq!(staking.get_free_balance(alice), 1000u64);
+        staking.stake(alice, 150).unwrap();
+        assert!(staking.unbond(alice, 100, 0).is_err());
+
+        // Unstaking everything is always allowed
+        staking.unbond(alice, 150, 0).unwrap();
+        assert_eq!(staking.get_staked_balance(alice), 0u64);
+    }
+
+    #[test]
+    fn test_unbonding() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_parameter(ParameterChange::UnbondingPeriod(10));
+
+        // Unbonded tokens leave the stake straight away but are not free yet
+        assert_eq!(staking.unbond(alice, 600, 0), Err(StakingError::InsufficientStake));
+        staking.unbond(alice, 200, 0).unwrap();
+        staking.unbond(alice, 100, 5).unwrap();
+        assert_eq!(staking.get_staked_balance(alice), 200u64);
+        assert_eq!(staking.get_unbonding_balance(alice), 300u64);
+        assert_eq!(staking.get_free_balance(alice), 500u64);
+
+        // Only the chunks that matured are withdrawn
+        assert_eq!(staking.withdraw_unbonded(alice, 9), Ok(0));
+        assert_eq!(staking.withdraw_unbonded(alice, 10), Ok(200));
+        assert_eq!(staking.get_free_balance(alice), 700u64);
+        assert_eq!(staking.get_unbonding_balance(alice), 100u64);
+
+        assert_eq!(staking.withdraw_unbonded(alice, 15), Ok(100));
+        assert_eq!(staking.get_free_balance(alice), 800u64);
+        assert_eq!(staking.withdraw_unbonded(alice, 20), Err(StakingError::NoUnlockedChunks));
+    }
+
+    #[test]
+    fn test_rebond() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_parameter(ParameterChange::UnbondingPeriod(10));
+        assert_eq!(staking.rebond(alice, 100), Err(StakingError::NoUnlockedChunks));
+        staking.unbond(alice, 100, 0).unwrap();
+        staking.unbond(alice, 100, 5).unwrap();
+
+        // The latest chunk is rebonded first
+        assert_eq!(staking.rebond(alice, 150), Ok(150));
+        assert_eq!(staking.get_staked_balance(alice), 450u64);
+        let ledger = staking.ledger(&alice).unwrap();
+        assert_eq!(ledger.unlocking.len(), 1);
+        assert_eq!(ledger.unlocking[0].value, 50u64);
+        assert_eq!(ledger.unlocking[0].unlock_at, 10u64);
+
+        // Rebonding more than is unbonding takes back what is left
+        assert_eq!(staking.rebond(alice, 100), Ok(50));
+        assert_eq!(staking.get_staked_balance(alice), 500u64);
+        assert!(staking.ledger(&alice).unwrap().unlocking.is_empty());
+        assert_eq!(staking.get_free_balance(alice), 500u64);
+    }
+
+    #[test]
+    fn test_genesis_config() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+
+        let genesis = GenesisConfig::<Runtime> {
+            balances: vec![(alice, 1000), (dave, 1000)],
+            stakers: vec![
+                (charlie, 200, StakerStatus::Nominator(vec![alice, bob])),
+                (alice, 500, StakerStatus::Validator(vec![1])),
+                (bob, 300, StakerStatus::Validator(vec![2])),
+                (dave, 100, StakerStatus::Idle),
+            ],
+        };
+        let staking = genesis.build().unwrap();
+
+        // Stakers are endowed with their bond on top of any free balance
+        assert_eq!(staking.get_staked_balance(alice), 500u64);
+        assert_eq!(staking.get_free_balance(alice), 1000u64);
+        assert_eq!(staking.get_free_balance(charlie), 0u64);
+        assert_eq!(staking.get_free_balance(dave), 1000u64);
+        assert_eq!(staking.nominations[&charlie], vec![alice, bob]);
+        assert!(staking.pending_events().is_empty());
+
+        // The first era starts with its validators elected
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+        assert_eq!(staking.session_keys(&bob), Some(&[2u8][..]));
+
+        let genesis = GenesisConfig::<Runtime> {
+            balances: Vec::new(),
+            stakers: vec![(charlie, 200, StakerStatus::Nominator(vec![alice]))],
+        };
+        assert_eq!(genesis.build().err(), Some(StakingError::TargetNotValidator));
+    }
+
+    #[test]
+    fn test_unclaimed_rewards_expire() {
+        let alice = 1u64;
+        let treasury = Runtime::UNCLAIMED_REWARD_DESTINATION.unwrap();
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        staking.payout_era(1).unwrap();
+
+        // Era 2 is claimable until the horizon has passed, then forfeited to the treasury
+        for _ in 1..Runtime::REWARD_CLAIM_HORIZON {
+            run_to_next_era(&mut staking);
+        }
+        assert_eq!(staking.current_era, Runtime::REWARD_CLAIM_HORIZON + 2);
+        assert_eq!(staking.get_free_balance(treasury), 0u64);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(2), Err(StakingError::EraExpired));
+        assert_eq!(staking.get_free_balance(treasury), 100u64);
+        assert_eq!(staking.get_free_balance(alice), 1000u64);
+
+        // Only the eras within the horizon are kept
+        let oldest = staking.current_era - Runtime::REWARD_CLAIM_HORIZON;
+        assert_eq!(staking.eras_stakers.keys().next(), Some(&oldest));
+        assert_eq!(staking.eras_validator_reward.keys().next(), Some(&oldest));
+        assert!(staking.paid_eras.is_empty());
+    }
+
+    #[test]
+    fn test_controller_accounts() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.set_balance(charlie, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.stake(charlie, 100).unwrap();
+        assert_eq!(staking.set_controller(bob, dave), Err(StakingError::NotBonded));
+        staking.set_controller(alice, bob).unwrap();
+        assert_eq!(staking.set_controller(charlie, bob), Err(StakingError::AlreadyPaired));
+        assert_eq!(staking.set_controller(charlie, alice), Err(StakingError::AlreadyPaired));
+
+        // Bob signs for Alice's stash, which can no longer sign for itself
+        staking.validate(bob).unwrap();
+        staking.set_keys(bob, vec![1]).unwrap();
+        staking.set_commission(bob, Perbill::from_percent(5)).unwrap();
+        assert_eq!(staking.chill(alice), Err(StakingError::NotController));
+        assert_eq!(staking.validators, vec![alice]);
+        assert_eq!(staking.commissions[&alice], Perbill::from_percent(5));
+        assert_eq!(staking.controller_of(&alice), bob);
+        staking.chill(bob).unwrap();
+        assert!(staking.chilled.contains(&alice));
+
+        // Nominators without a controller sign for themselves
+        staking.nominate(charlie, vec![alice]).unwrap();
+        staking.set_controller(alice, alice).unwrap();
+        staking.validate(alice).unwrap();
+        assert_eq!(staking.controller_of(&alice), alice);
+    }
+
+    #[test]
+    fn test_validator_uptime() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        for who in [alice, bob] {
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        staking.set_parameter(ParameterChange::MinUptime(Perbill::from_percent(50)));
+        assert_eq!(staking.im_online(alice), Err(StakingError::NotActiveValidator));
+        run_to_next_era(&mut staking);
+
+        // Bob misses half his heartbeats and stays; Alice stays online
+        staking.im_online(alice).unwrap();
+        assert_eq!(staking.im_online(alice), Err(StakingError::DuplicateHeartbeat));
+        staking.im_online(bob).unwrap();
+        run_to_next_era(&mut staking);
+        staking.im_online(alice).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.uptime_of(&bob), UptimeRecord { active_eras: 2, online_eras: 1 });
+        assert!(!staking.chilled.contains(&bob));
+        assert_eq!(staking.uptime_of(&charlie), UptimeRecord::default());
+
+        // Falling below the threshold chills him at the end of the era
+        staking.im_online(alice).unwrap();
+        run_to_next_era(&mut staking);
+        assert!(staking.chilled.contains(&bob));
+        assert_eq!(staking.active_validators(), &[alice]);
+        let uptime = Perbill::from_rational(1, 3);
+        assert!(staking.pending_events().iter().any(|event| matches!(
+            event,
+            StakingEvent::ValidatorOffline { validator, uptime: score }
+                if *validator == bob && *score == uptime
+        )));
+        assert_eq!(staking.uptime_of(&alice).score(), Perbill::from_percent(100));
+    }
+
+    #[test]
+    fn test_min_commission() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+            staking.validate(who).unwrap();
+        }
+        staking.set_commission(alice, Perbill::from_percent(2)).unwrap();
+        staking.set_commission(bob, Perbill::from_percent(10)).unwrap();
+        staking.set_parameter(ParameterChange::MinCommission(Perbill::from_percent(5)));
+
+        // New commissions must respect the floor
+        let result = staking.set_commission(bob, Perbill::from_percent(4));
+        assert_eq!(result, Err(StakingError::CommissionTooLow));
+
+        // Existing ones below it, including the default of none, are raised to it
+        assert_eq!(staking.apply_min_commission(), vec![alice, charlie]);
+        assert_eq!(staking.commissions[&alice], Perbill::from_percent(5));
+        assert_eq!(staking.commissions[&bob], Perbill::from_percent(10));
+        assert_eq!(staking.commissions[&charlie], Perbill::from_percent(5));
+        assert!(staking.apply_min_commission().is_empty());
+    }
+
+    #[test]
+    fn test_era_exposure_snapshots() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(alice, 100).unwrap();
+        staking.stake(bob, 300).unwrap();
+        staking.stake(charlie, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        staking.nominate(bob, vec![alice]).unwrap();
+        staking.set_parameter(ParameterChange::UnbondingPeriod(100));
+        run_to_next_era(&mut staking);
+        let exposure = staking.era_exposure(1, &alice).unwrap();
+        assert_eq!((exposure.own, exposure.others[0].value), (100u64, 300u64));
+
+        // Bonding after the election earns nothing from the era
+        staking.nominate(charlie, vec![alice]).unwrap();
+        staking.slash(alice, Perbill::from_percent(50)).unwrap();
+        run_to_next_era(&mut staking);
+        staking.payout_era(1).unwrap();
+        assert_eq!(staking.get_free_balance(bob), 775u64);
+        assert_eq!(staking.get_free_balance(charlie), 900u64);
+
+        // Unbonding before the slash is applied does not escape it
+        staking.unbond(bob, 250, 20).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(alice), 50u64);
+        assert_eq!(staking.get_staked_balance(bob), 0u64);
+        assert_eq!(staking.get_unbonding_balance(bob), 150u64);
+        assert_eq!(staking.get_staked_balance(charlie), 100u64);
+    }
+
+    #[test]
+    fn test_staking_ledger() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_parameter(ParameterChange::UnbondingPeriod(10));
+        staking.unbond(alice, 200, 0).unwrap();
+
+        // Unbonding funds leave the active stake but stay in the total
+        let ledger = staking.ledger(&alice).unwrap();
+        assert_eq!(ledger.stash, alice);
+        assert_eq!(ledger.active, 300u64);
+        assert_eq!(ledger.total, 500u64);
+
+        // Withdrawing consolidates the unlocked chunks out of the total
+        staking.withdraw_unbonded(alice, 10).unwrap();
+        let ledger = staking.ledger(&alice).unwrap();
+        assert_eq!((ledger.active, ledger.total), (300u64, 300u64));
+
+        // A ledger is dropped once nothing is bonded or unbonding
+        staking.unbond(alice, 300, 10).unwrap();
+        staking.withdraw_unbonded(alice, 20).unwrap();
+        assert!(staking.ledger(&alice).is_none());
+        assert_eq!(staking.get_free_balance(alice), 1000u64);
+    }
+
+    #[test]
+    fn test_blocked_validator() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        staking.validate(alice).unwrap();
+        staking.validate(bob).unwrap();
+        staking.nominate(charlie, vec![alice, bob]).unwrap();
+        assert_eq!(staking.set_blocked(dave, true), Err(StakingError::NotValidating));
+        staking.set_blocked(alice, true).unwrap();
+
+        // Existing nominators can keep Alice, new ones cannot add her
+        assert_eq!(staking.nominate(dave, vec![alice]), Err(StakingError::TargetBlocked));
+        staking.nominate(charlie, vec![bob, alice]).unwrap();
+        staking.nominate(dave, vec![bob]).unwrap();
+
+        // Kicked nominators lose Alice, and stop nominating if she was their only target
+        staking.kick(alice, vec![charlie]).unwrap();
+        assert_eq!(staking.nominations[&charlie], vec![bob]);
+        staking.kick(bob, vec![charlie, dave]).unwrap();
+        assert!(staking.nominations.is_empty());
+        staking.set_blocked(alice, false).unwrap();
+        staking.nominate(dave, vec![alice]).unwrap();
+    }
+
+    #[test]
+    fn test_stake_provider() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.set_balance(bob, 1000);
+        staking.stake(alice, 300).unwrap();
+        staking.stake(bob, 200).unwrap();
+        staking.unbond(bob, 50, 0).unwrap();
+
+        // Unbonding funds no longer count as bonded
+        assert_eq!(staking.bonded_of(&alice), 300u64);
+        assert_eq!(staking.bonded_of(&bob), 150u64);
+        assert_eq!(staking.bonded_of(&charlie), 0u64);
+        assert_eq!(staking.total_bonded(), 450);
+        assert_eq!(staking.bonded_snapshot().get(&bob), Some(&150u64));
+    }
+
+    #[test]
+    fn test_fast_unstake() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        staking.nominate(bob, vec![alice]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.register_fast_unstake(alice), Err(StakingError::AlreadyValidating));
+
+        // Charlie never backed anyone and is unstaked at the start of the next era
+        staking.register_fast_unstake(charlie).unwrap();
+        assert_eq!(staking.register_fast_unstake(charlie), Err(StakingError::AlreadyQueued));
+        staking.register_fast_unstake(bob).unwrap();
+        assert!(!staking.nominations.contains_key(&bob));
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(charlie), 0u64);
+        assert_eq!(staking.get_free_balance(charlie), 1000u64);
+
+        // Bob was exposed last era, so he stays bonded and leaves the queue
+        assert_eq!(staking.get_staked_balance(bob), 100u64);
+        assert!(staking.fast_unstake_queue.is_empty());
+        for _ in 0..Runtime::FAST_UNSTAKE_ERAS {
+            run_to_next_era(&mut staking);
+        }
+        staking.register_fast_unstake(bob).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_free_balance(bob), 1000u64);
+    }
+
+    #[test]
+    fn test_renominate() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        for who in [alice, bob] {
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        staking.nominate(charlie, vec![alice]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.renominate(charlie, bob, alice), Err(StakingError::NotNominated));
+        assert_eq!(staking.renominate(charlie, alice, dave), Err(StakingError::TargetNotValidator));
+        assert_eq!(staking.renominate(charlie, alice, alice), Err(StakingError::DuplicateTarget));
+
+        // The move waits for the next era, and the stake never leaves the bond
+        staking.renominate(charlie, alice, bob).unwrap();
+        assert_eq!(staking.renominate(charlie, alice, bob), Err(StakingError::AlreadyQueued));
+        assert_eq!(staking.nominations[&charlie], vec![alice]);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.nominations[&charlie], vec![bob]);
+        assert_eq!(staking.get_staked_balance(charlie), 100u64);
+        assert_eq!(staking.get_unbonding_balance(charlie), 0u64);
+        let exposure = staking.era_exposure(staking.current_era, &bob).unwrap();
+        assert_eq!((exposure.own, exposure.others[0].who), (100u64, charlie));
+
+        // Moves to validators that have since been blocked are dropped
+        staking.renominate(charlie, bob, alice).unwrap();
+        staking.set_blocked(alice, true).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.nominations[&charlie], vec![bob]);
+        assert!(staking.pending_renominations.is_empty());
+    }
+
+    #[test]
+    fn test_staking_overview() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+        }
+        for (who, stake) in [(alice, 400), (bob, 200), (charlie, 300), (dave, 100)] {
+            staking.stake(who, stake).unwrap();
+        }
+        for who in [alice, bob] {
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        staking.set_commission(alice, Perbill::from_percent(10)).unwrap();
+        staking.set_commission(bob, Perbill::from_percent(20)).unwrap();
+        staking.set_commission(bob, Perbill::from_percent(30)).unwrap();
+        staking.nominate(charlie, vec![alice, bob]).unwrap();
+        let stats = staking.staking_overview();
+        assert_eq!(stats.total_bonded, 1000);
+        assert_eq!((stats.validator_count, stats.nominator_count), (2, 1));
+        assert_eq!(stats.average_commission, Perbill::from_percent(20));
+        assert_eq!(stats.staking_rate, Perbill::from_percent(25));
+
+        // The totals follow unbonding, rewards and slashes
+        staking.set_payee(charlie, RewardDestination::Staked).unwrap();
+        staking.unbond(dave, 100, 0).unwrap();
+        staking.rebond(dave, 50).unwrap();
+        run_to_next_era(&mut staking);
+        staking.report_slash(alice, Perbill::from_percent(10), vec![dave]).unwrap();
+        run_to_next_era(&mut staking);
+        staking.payout_era(1).unwrap();
+        for _ in 0..Runtime::SLASH_DEFER_DURATION {
+            run_to_next_era(&mut staking);
+        }
+        assert_eq!(staking.slashes.len(), 1);
+        assert_eq!(staking.get_unbonding_balance(dave), 50u64);
+        let ledgers = staking.ledgers.values();
+        let bonded: u128 = ledgers.clone().map(|ledger| u128::from(ledger.active)).sum();
+        let balances = staking.free_balances.values().chain(staking.reserved_balances.values());
+        let issuance = balances.copied().chain(ledgers.map(|ledger| ledger.total));
+        assert_eq!(staking.staking_overview().total_bonded, bonded);
+        assert_eq!(staking.total_issuance(), issuance.map(u128::from).sum::<u128>());
+    }
+
+    // Finalize blocks until the current era ends and the next validators are elected
+    fn run_to_next_era(staking: &mut StakingPallet<Runtime>) {
+        let era = staking.current_era;
+        let mut block = staking.era_start;
+        while staking.current_era == era {
+            staking.on_finalize(block);
+            block += 1;
+        }
+    }
+
+    #[test]
+    fn test_slashing() {
+        let alice = 1u64;
+        let treasury = Runtime::SLASH_DESTINATION.unwrap();
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        let result = staking.slash(alice, Perbill::from_percent(10));
+        assert_eq!(result, Err(StakingError::NotActiveValidator));
+        run_to_next_era(&mut staking);
+
+        // A tenth of the stake goes to the treasury, and free balance is untouched
+        assert_eq!(staking.slash(alice, Perbill::from_percent(10)), Ok(0));
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(alice), 450u64);
+        assert_eq!(staking.get_free_balance(alice), 500u64);
+        assert_eq!(staking.get_free_balance(treasury), 50u64);
+
+        // After chilling, fractions round down and are capped at the whole stake
+        staking.chill(alice).unwrap();
+        staking.validate(alice).unwrap();
+        run_to_next_era(&mut staking);
+        staking.slash(alice, Perbill::from_parts(1_000_000)).unwrap();
+        staking.slash(alice, Perbill::from_percent(150)).unwrap();
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.slashes[1].amount, 0u64);
+        assert_eq!(staking.slashes[2].amount, 449u64);
+        assert_eq!(staking.get_staked_balance(alice), 1u64);
+
+        let record = &staking.slashes[0];
+        assert_eq!(record.validator, alice);
+        assert_eq!(record.fraction, Perbill::from_percent(10));
+        assert_eq!(record.amount, 50u64);
+        assert_eq!(record.beneficiary, Some(treasury));
+        assert_eq!(staking.slashes.len(), 3);
+    }
+
+    #[test]
+    fn test_slashing_spans() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.set_balance(bob, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.stake(bob, 500).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        staking.nominate(bob, vec![alice]).unwrap();
+        run_to_next_era(&mut staking);
+
+        // Overlapping offences in a span only apply the largest slash once
+        staking.slash(alice, Perbill::from_percent(10)).unwrap();
+        staking.slash(alice, Perbill::from_percent(20)).unwrap();
+        staking.slash(alice, Perbill::from_percent(5)).unwrap();
+        run_to_next_era(&mut staking);
+        staking.slash(alice, Perbill::from_percent(20)).unwrap();
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(alice), 400u64);
+        assert_eq!(staking.get_staked_balance(bob), 400u64);
+        let fractions: Vec<Perbill> = staking.slashes.iter().map(|slash| slash.fraction).collect();
+        assert_eq!(fractions, vec![Perbill::from_percent(10), Perbill::from_percent(10)]);
+
+        // Chilling ends the span, so offences after validating again are punished anew
+        staking.chill(alice).unwrap();
+        staking.validate(alice).unwrap();
+        assert_eq!(staking.slashing_spans(&alice).len(), 2);
+        run_to_next_era(&mut staking);
+        staking.slash(alice, Perbill::from_percent(10)).unwrap();
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(alice), 360u64);
+        assert_eq!(staking.slashing_spans(&alice)[1].max_fraction, Perbill::from_percent(10));
+    }
+
+    #[test]
+    fn test_validator_count() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for (who, stake) in [(alice, 300), (bob, 200), (charlie, 100)] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, stake).unwrap();
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        run_to_next_era(&mut staking);
+
+        // The new count takes effect at the next era
+        let result = staking.set_validator_count(Origin::Signed(alice), 2);
+        assert_eq!(result, Err(StakingError::BadOrigin));
+        staking.set_validator_count(Origin::Council, 2).unwrap();
+        assert_eq!(staking.active_validators().len(), 3);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+
+        // The minimum cannot exceed the count, and an election with too few winners keeps
+        // the previous validators
+        let result = staking.set_minimum_validator_count(Origin::Root, 3);
+        assert_eq!(result, Err(StakingError::InvalidValidatorCount));
+        staking.set_minimum_validator_count(Origin::Root, 2).unwrap();
+        let result = staking.set_validator_count(Origin::Root, 1);
+        assert_eq!(result, Err(StakingError::InvalidValidatorCount));
+        staking.chill(bob).unwrap();
+        staking.chill(charlie).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+    }
+
+    #[test]
+    fn test_permissioned_validators() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        let result = staking.set_permissioned(Origin::Signed(alice), true);
+        assert_eq!(result, Err(StakingError::BadOrigin));
+        let result = staking.set_allowlisted(Origin::Signed(alice), alice, true);
+        assert_eq!(result, Err(StakingError::BadOrigin));
+
+        // Only allowlisted accounts can validate, and others are no longer elected
+        staking.set_permissioned(Origin::Root, true).unwrap();
+        staking.set_allowlisted(Origin::Council, bob, true).unwrap();
+        assert_eq!(staking.validate(charlie), Err(StakingError::NotAllowlisted));
+        staking.validate(bob).unwrap();
+        staking.set_keys(bob, vec![2]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[bob]);
+
+        // Leaving permissioned mode opens validation to everyone again
+        staking.set_permissioned(Origin::Council, false).unwrap();
+        staking.validate(charlie).unwrap();
+        staking.set_keys(charlie, vec![3]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators().len(), 3);
+    }
+
+    // Records the era boundaries it is told about
+    struct EraRecorder(Vec<(&'static str, u32)>);
+
+    impl EraHooks<Runtime> for EraRecorder {
+        fn on_era_end(&mut self, _staking: &mut StakingPallet<Runtime>, era_index: u32) {
+            self.0.push(("end", era_index));
+        }
+
+        fn on_era_start(&mut self, staking: &mut StakingPallet<Runtime>, era_index: u32) {
+            self.0.push(("start", era_index));
+            // Top up the treasury at the start of every era
+            let treasury = Runtime::SLASH_DESTINATION.unwrap();
+            let balance = staking.get_free_balance(treasury);
+            staking.set_balance(treasury, balance + 10);
+        }
+    }
+
+    #[test]
+    fn test_era_hooks() {
+        let alice = 1u64;
+        let treasury = Runtime::SLASH_DESTINATION.unwrap();
+        let mut staking = StakingPallet::<Runtime>::new();
+        let mut recorder = EraRecorder(Vec::new());
+        let mut other = EraRecorder(Vec::new());
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        for block in 0..20 {
+            staking.on_finalize_with_hooks(block, &mut recorder);
+        }
+        assert_eq!(recorder.0, vec![("end", 0), ("start", 1), ("end", 1), ("start", 2)]);
+        assert_eq!(staking.get_free_balance(treasury), 20u64);
+
+        // The new era's validators are elected by the time it starts
+        assert_eq!(staking.active_validators(), &[alice]);
+        let mut hooks = (recorder, other);
+        for block in 20..30 {
+            staking.on_finalize_with_hooks(block, &mut hooks);
+        }
+        (recorder, other) = hooks;
+        assert_eq!(recorder.0.len(), 6);
+        assert_eq!(other.0, vec![("end", 2), ("start", 3)]);
+        assert_eq!(staking.get_free_balance(treasury), 40u64);
+    }
+
+    #[test]
+    fn test_bond_extra() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+        }
+        assert_eq!(staking.bond_extra(alice, 100), Err(StakingError::NotBonded));
+        for who in [alice, bob] {
+            staking.stake(who, 100).unwrap();
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        staking.stake(charlie, 100).unwrap();
+        staking.nominate(charlie, vec![bob]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.bond_extra(alice, 2000), Err(StakingError::InsufficientBalance));
+
+        // The extra stake is in the ledger at once and in the exposure from the next era
+        staking.bond_extra(alice, 200).unwrap();
+        staking.bond_extra(charlie, 100).unwrap();
+        assert_eq!(staking.ledger(&alice).unwrap().total, 300u64);
+        assert_eq!(staking.get_free_balance(alice), 700u64);
+        assert_eq!(staking.era_exposure(1, &alice).unwrap().total(), 100);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.era_exposure(2, &alice).unwrap().total(), 300);
+        assert_eq!(staking.era_exposure(2, &bob).unwrap().total(), 300);
+
+        // Era 2 rewards are split by the new stake
+        run_to_next_era(&mut staking);
+        staking.payout_era(2).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 750u64);
+        assert_eq!(staking.get_free_balance(charlie), 833u64);
+    }
+
+    #[test]
+    fn test_slash_insurance() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        staking.nominate(bob, vec![alice]).unwrap();
+        staking.join_insurance(alice).unwrap();
+        assert_eq!(staking.join_insurance(alice), Err(StakingError::AlreadyInsured));
+        assert_eq!(staking.leave_insurance(bob), Err(StakingError::NotInsured));
+
+        // Alice pays 5% of each reward into the pool, Bob is paid in full
+        for era_index in 1..=2 {
+            run_to_next_era(&mut staking);
+            run_to_next_era(&mut staking);
+            staking.payout_era(era_index).unwrap();
+        }
+        assert_eq!(staking.get_free_balance(alice), 996u64);
+        assert_eq!(staking.get_free_balance(bob), 1000u64);
+        assert_eq!(staking.insurance.funds, 4u64);
+
+        // Half of the slash is covered, as far as the pool's funds go
+        staking.report_slash(alice, Perbill::from_percent(10), vec![]).unwrap();
+        for _ in 0..=Runtime::SLASH_DEFER_DURATION {
+            run_to_next_era(&mut staking);
+        }
+        assert_eq!(staking.get_staked_balance(alice), 90u64);
+        assert_eq!(staking.get_free_balance(alice), 1000u64);
+        assert_eq!(staking.get_staked_balance(bob), 90u64);
+        assert_eq!(staking.insurance.funds, 0u64);
+    }
+
+    #[test]
+    fn test_deferred_slashing() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 500).unwrap();
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        run_to_next_era(&mut staking);
+        let alice_slash = staking.slash(alice, Perbill::from_percent(10)).unwrap();
+        let bob_slash = staking.slash(bob, Perbill::from_percent(10)).unwrap();
+        assert_eq!(staking.pending_slashes().len(), 2);
+        assert_eq!(staking.pending_slashes()[&bob_slash].own, 500u64);
+        assert_eq!(staking.pending_slashes()[&alice_slash].apply_era, 3);
+
+        // Only root or the council can cancel a slash before it is applied
+        let result = staking.cancel_slash(Origin::Signed(alice), alice_slash);
+        assert_eq!(result, Err(StakingError::BadOrigin));
+        staking.dispatch(StakingCall::CancelSlash(alice_slash)).unwrap();
+        let result = staking.cancel_slash(Origin::Council, alice_slash);
+        assert_eq!(result, Err(StakingError::SlashNotFound));
+
+        // The remaining slash waits for its era
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(bob), 500u64);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(bob), 450u64);
+        assert_eq!(staking.get_staked_balance(alice), 500u64);
+        assert!(staking.pending_slashes().is_empty());
+        assert_eq!(staking.slashes.len(), 1);
+    }
+
+    #[test]
+    fn test_unbond_after_slash_report() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 500).unwrap();
+        }
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        for who in [bob, charlie] {
+            staking.nominate(who, vec![alice]).unwrap();
+        }
+        staking.set_parameter(ParameterChange::UnbondingPeriod(1));
+        run_to_next_era(&mut staking);
+        staking.slash(alice, Perbill::from_percent(50)).unwrap();
+
+        // Nominators who unbond after the report cannot withdraw before the slash is applied
+        let block = staking.era_start;
+        for who in [bob, charlie] {
+            staking.unbond(who, 450, block).unwrap();
+            let result = staking.withdraw_unbonded(who, block + 1);
+            assert_eq!(result, Err(StakingError::SlashPending));
+        }
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert!(staking.pending_slashes().is_empty());
+
+        // The slash reaches their unbonding chunks in full
+        for who in [bob, charlie] {
+            assert_eq!(staking.get_staked_balance(who), 0u64);
+            assert_eq!(staking.get_unbonding_balance(who), 250u64);
+            assert_eq!(staking.withdraw_unbonded(who, staking.era_start), Ok(250));
+            assert_eq!(staking.get_free_balance(who), 750u64);
+        }
+    }
+
+    #[test]
+    fn test_staking_events() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.unbond(alice, 100, 0).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        staking.chill(alice).unwrap();
+        assert!(matches!(
+            staking.pending_events(),
+            [
+                StakingEvent::Bonded { stash: 1, amount: 500 },
+                StakingEvent::Unbonded { stash: 1, amount: 100 },
+                StakingEvent::ValidatorChilled { validator: 1 },
+            ]
+        ));
+
+        // Depositing moves the events into the system pallet
+        let mut system = SystemPallet::<Runtime>::new();
+        staking.deposit_events(&mut system);
+        assert!(staking.pending_events().is_empty());
+        assert!(matches!(
+            system.events()[0],
+            RuntimeEvent::Staking(StakingEvent::Bonded { stash: 1, amount: 500 })
+        ));
+        assert_eq!(system.events().len(), 3);
+    }
+
+    #[test]
+    fn test_era_payouts() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 300).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        staking.validate(bob).unwrap();
+        staking.set_keys(bob, bob.to_le_bytes().to_vec()).unwrap();
+        assert_eq!(staking.validate(alice), Err(StakingError::AlreadyValidating));
+
+        // The era ends after its last block, electing validators for the next one
+        for block in 0..Runtime::BLOCKS_PER_ERA - 1 {
+            staking.on_finalize(block);
+        }
+        assert_eq!(staking.current_era, 0);
+        assert_eq!(staking.payout_era(0), Err(StakingError::EraNotEnded));
+        staking.on_finalize(Runtime::BLOCKS_PER_ERA - 1);
+        assert_eq!(staking.current_era, 1);
+        assert_eq!(staking.era_start, Runtime::BLOCKS_PER_ERA);
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+
+        // Nobody was elected for the first era
+        assert_eq!(staking.payout_era(0), Ok(0));
+
+        // The reward is split 3:1 by stake, and only once
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(100));
+        assert_eq!(staking.get_free_balance(alice), 775u64);
+        assert_eq!(staking.get_free_balance(bob), 925u64);
+        assert_eq!(staking.payout_era(1), Err(StakingError::AlreadyPaid));
+    }
+
+    #[test]
+    fn test_nominations() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(alice, 200).unwrap();
+        staking.stake(bob, 200).unwrap();
+        staking.stake(charlie, 400).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        staking.validate(bob).unwrap();
+        staking.set_keys(bob, bob.to_le_bytes().to_vec()).unwrap();
+
+        // Nominators back validators only, and split their stake between them
+        let result = staking.nominate(dave, vec![alice]);
+        assert_eq!(result, Err(StakingError::InsufficientNominatorBond));
+        let result = staking.nominate(charlie, vec![alice, dave]);
+        assert_eq!(result, Err(StakingError::TargetNotValidator));
+        assert_eq!(staking.nominate(alice, vec![bob]), Err(StakingError::ValidatorCannotNominate));
+        staking.nominate(charlie, vec![alice, bob]).unwrap();
+        let exposure = staking.exposure_of(&alice);
+        assert_eq!(exposure.own, 200u64);
+        assert_eq!(exposure.others[0].who, charlie);
+        assert_eq!(exposure.others[0].value, 200u64);
+        assert_eq!(exposure.total(), 400);
+
+        // Nominators share in the rewards of the validators they back
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(100));
+        assert_eq!(staking.get_free_balance(alice), 825u64);
+        assert_eq!(staking.get_free_balance(bob), 825u64);
+        assert_eq!(staking.get_free_balance(charlie), 650u64);
+
+        // And in their slashes, on the part of their stake backing the offender
+        staking.slash(alice, Perbill::from_percent(10)).unwrap();
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.get_staked_balance(alice), 180u64);
+        assert_eq!(staking.get_staked_balance(charlie), 380u64);
+        assert_eq!(staking.get_staked_balance(bob), 200u64);
+        let record = staking.slashes.last().unwrap();
+        assert_eq!(record.amount, 20u64);
+        assert_eq!(record.others[0].value, 20u64);
+    }
+
+    #[test]
+    fn test_validator_election() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(alice, 150).unwrap();
+        staking.stake(bob, 300).unwrap();
+        staking.stake(charlie, 200).unwrap();
+        staking.stake(dave, 150).unwrap();
+        for who in [alice, bob, charlie] {
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        staking.set_parameter(ParameterChange::MaxValidators(2));
+
+        // Dave's nomination lifts Alice above Charlie
+        staking.nominate(dave, vec![alice]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+
+        // Only the active set is rewarded and can be slashed
+        let result = staking.slash(charlie, Perbill::from_percent(10));
+        assert_eq!(result, Err(StakingError::NotActiveValidator));
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(100));
+        assert_eq!(staking.get_free_balance(charlie), 800u64);
+        assert_eq!(staking.get_free_balance(bob), 750u64);
+    }
+
+    #[test]
+    fn test_commission() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        staking.nominate(bob, vec![alice]).unwrap();
+
+        // Commission is validated against the governance ceiling
+        staking.set_parameter(ParameterChange::MaxCommission(Perbill::from_percent(50)));
+        let result = staking.set_commission(bob, Perbill::from_percent(10));
+        assert_eq!(result, Err(StakingError::NotValidating));
+        let result = staking.set_commission(alice, Perbill::from_percent(60));
+        assert_eq!(result, Err(StakingError::CommissionTooHigh));
+        staking.set_commission(alice, Perbill::from_percent(20)).unwrap();
+
+        // Alice takes 20 off the top, then the remaining 80 is split evenly
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(100));
+        assert_eq!(staking.get_free_balance(alice), 960u64);
+        assert_eq!(staking.get_free_balance(bob), 940u64);
+
+        // Lowering the ceiling caps commissions already set
+        staking.set_parameter(ParameterChange::MaxCommission(Perbill::from_percent(10)));
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(2), Ok(100));
+        assert_eq!(staking.get_free_balance(alice), 1015u64);
+        assert_eq!(staking.get_free_balance(bob), 985u64);
+    }
+
+    #[test]
+    fn test_minimum_bonds() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 90).unwrap();
+        staking.set_balance(bob, 1000);
+        staking.stake(bob, 40).unwrap();
+
+        // Roles need their minimum bond to be taken up
+        assert_eq!(staking.validate(alice), Err(StakingError::InsufficientValidatorBond));
+        staking.stake(alice, 30).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        let result = staking.nominate(bob, vec![alice]);
+        assert_eq!(result, Err(StakingError::InsufficientNominatorBond));
+        staking.stake(bob, 20).unwrap();
+        staking.nominate(bob, vec![alice]).unwrap();
+
+        // And to be kept when unbonding
+        let result = staking.unbond(alice, 30, 0);
+        assert_eq!(result, Err(StakingError::InsufficientValidatorBond));
+        let result = staking.unbond(bob, 20, 0);
+        assert_eq!(result, Err(StakingError::InsufficientNominatorBond));
+
+        // A slash can still push both below, and then they are kicked out
+        run_to_next_era(&mut staking);
+        staking.slash(alice, Perbill::from_percent(50)).unwrap();
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        staking.events.clear();
+        assert_eq!(staking.kick_below_minimum(), vec![alice, bob]);
+        assert!(matches!(
+            staking.pending_events(),
+            [
+                StakingEvent::ValidatorChilled { validator: 1 },
+                StakingEvent::NominatorChilled { nominator: 2 },
+            ]
+        ));
+        assert!(staking.chilled.contains(&alice));
+        assert!(staking.nominations.is_empty());
+        assert!(staking.kick_below_minimum().is_empty());
+
+        // Alice cannot validate again until she tops up her bond
+        assert_eq!(staking.validate(alice), Err(StakingError::InsufficientValidatorBond));
+    }
+
+    #[test]
+    fn test_reward_destinations() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        let result = staking.set_payee(dave, RewardDestination::Staked);
+        assert_eq!(result, Err(StakingError::NotBonded));
+        staking.set_payee(alice, RewardDestination::Staked).unwrap();
+        staking.set_payee(bob, RewardDestination::Account(dave)).unwrap();
+        staking.set_payee(charlie, RewardDestination::Stash).unwrap();
+
+        // Each validator earns 33 of the reward, paid where it asked
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(99));
+        assert_eq!(staking.get_staked_balance(alice), 133u64);
+        assert_eq!(staking.get_free_balance(alice), 900u64);
+        assert_eq!(staking.get_free_balance(bob), 900u64);
+        assert_eq!(staking.get_free_balance(dave), 33u64);
+        assert_eq!(staking.get_free_balance(charlie), 933u64);
+    }
+
+    #[test]
+    fn test_auto_compound() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        assert_eq!(staking.set_auto_compound(3u64, true), Err(StakingError::NotBonded));
+        staking.set_auto_compound(alice, true).unwrap();
+        assert!(staking.is_auto_compounding(&alice));
+        assert!(!staking.is_auto_compounding(&bob));
+
+        // Alice's reward grows her bond, Bob's is paid as free balance
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(100));
+        assert_eq!(staking.get_staked_balance(alice), 150u64);
+        assert_eq!(staking.get_free_balance(alice), 900u64);
+        assert_eq!(staking.get_free_balance(bob), 950u64);
+
+        // Opting out pays the next rewards to the free balance
+        staking.set_auto_compound(alice, false).unwrap();
+        assert!(!staking.is_auto_compounding(&alice));
+        run_to_next_era(&mut staking);
+        staking.payout_era(2).unwrap();
+        assert_eq!(staking.get_staked_balance(alice), 150u64);
+        assert!(staking.get_free_balance(alice) > 900u64);
+    }
+
+    #[test]
+    fn test_unbonding_chunks() {
+        let alice = 1u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 500).unwrap();
+        staking.set_parameter(ParameterChange::UnbondingPeriod(10));
+
+        // Unbonding in the same block tops up the same chunk
+        staking.unbond(alice, 50, 0).unwrap();
+        staking.unbond(alice, 50, 0).unwrap();
+        staking.unbond(alice, 50, 1).unwrap();
+        staking.unbond(alice, 50, 2).unwrap();
+        let ledger = staking.ledger(&alice).unwrap();
+        assert_eq!(ledger.unlocking.len(), 3);
+        assert_eq!(ledger.unlocking[0].value, 100u64);
+        assert_eq!(ledger.total_unlocking(), 200u64);
+
+        // At the cap, a new chunk needs an old one to be withdrawn first
+        assert_eq!(staking.unbond(alice, 50, 3), Err(StakingError::TooManyUnlockingChunks));
+        assert_eq!(staking.get_staked_balance(alice), 300u64);
+        assert_eq!(staking.withdraw_unbonded(alice, 10), Ok(100));
+        staking.unbond(alice, 50, 3).unwrap();
+        assert_eq!(staking.ledger(&alice).unwrap().unlocking.len(), 3);
+        assert_eq!(staking.get_unbonding_balance(alice), 150u64);
+    }
+
+    #[test]
+    fn test_oversubscribed_nominators() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let dave = 4u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie, dave] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(alice, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        staking.stake(bob, 300).unwrap();
+        staking.stake(charlie, 200).unwrap();
+        staking.stake(dave, 100).unwrap();
+        for who in [bob, charlie, dave] {
+            staking.nominate(who, vec![alice]).unwrap();
+        }
+        staking.set_parameter(ParameterChange::MaxNominatorsPerValidator(2));
+
+        // Only the two largest nominators count toward Alice's exposure
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.exposure_of(&alice).total(), 600);
+        assert_eq!(staking.oversubscribed_targets(&dave), vec![alice]);
+        assert!(staking.oversubscribed_targets(&bob).is_empty());
+
+        // So Dave earns nothing from the era
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(99));
+        assert_eq!(staking.get_free_balance(dave), 900u64);
+        assert_eq!(staking.get_free_balance(bob), 750u64);
+    }
+
+    #[test]
+    fn test_payout_history() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        staking.set_balance(alice, 1000);
+        staking.stake(alice, 100).unwrap();
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, vec![1]).unwrap();
+        staking.set_payee(alice, RewardDestination::Account(bob)).unwrap();
+
+        // Rewards are recorded for the staker that earned them, not the payee
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        run_to_next_era(&mut staking);
+        staking.payout_era(2).unwrap();
+        staking.payout_era(1).unwrap();
+        assert_eq!(staking.payout_history(&alice), vec![(2, 100u64), (1, 100)]);
+        assert!(staking.payout_history(&bob).is_empty());
+
+        // Records older than the history depth are pruned at the start of each era
+        for _ in 1..Runtime::PAYOUT_HISTORY_DEPTH {
+            run_to_next_era(&mut staking);
+        }
+        assert_eq!(staking.payout_history(&alice), vec![(2, 100u64)]);
+        run_to_next_era(&mut staking);
+        assert!(staking.payout_history(&alice).is_empty());
+        assert!(staking.payout_history.is_empty());
+    }
+
+    #[test]
+    fn test_era_points() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+        }
+        staking.stake(alice, 300).unwrap();
+        staking.stake(bob, 100).unwrap();
+        for who in [alice, bob] {
+            staking.validate(who).unwrap();
+            staking.set_keys(who, who.to_le_bytes().to_vec()).unwrap();
+        }
+        assert_eq!(staking.note_author(alice), Err(StakingError::NotActiveValidator));
+        run_to_next_era(&mut staking);
+
+        // Bob authors three blocks to Alice's one and earns three quarters of the reward,
+        // despite having a third of her stake
+        staking.note_author(alice).unwrap();
+        for _ in 0..3 {
+            staking.note_author(bob).unwrap();
+        }
+        assert_eq!(staking.note_author(charlie), Err(StakingError::NotActiveValidator));
+        assert_eq!(staking.era_points(1, &bob), 3 * Runtime::AUTHORING_POINTS);
+        run_to_next_era(&mut staking);
+        staking.payout_era(1).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 725u64);
+        assert_eq!(staking.get_free_balance(bob), 975u64);
+
+        // Without any points, the reward is split by stake
+        run_to_next_era(&mut staking);
+        staking.payout_era(2).unwrap();
+        assert_eq!(staking.get_free_balance(alice), 800u64);
+        assert_eq!(staking.get_free_balance(bob), 1000u64);
+    }
+
+    #[test]
+    fn test_session_keys() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        assert_eq!(staking.set_keys(alice, vec![1, 2]), Err(StakingError::NotBonded));
+        for who in [alice, bob] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+            staking.validate(who).unwrap();
+        }
+        assert_eq!(staking.set_keys(alice, Vec::new()), Err(StakingError::EmptySessionKeys));
+
+        // Only validators with keys are elected
+        staking.set_keys(alice, vec![1, 2]).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[alice]);
+
+        // Calling again rotates the keys
+        staking.set_keys(bob, vec![3]).unwrap();
+        staking.set_keys(alice, vec![4, 5]).unwrap();
+        assert_eq!(staking.session_keys(&alice), Some(&[4u8, 5][..]));
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+    }
+
+    #[test]
+    fn test_chill() {
+        let alice = 1u64;
+        let bob = 2u64;
+        let charlie = 3u64;
+        let mut staking = StakingPallet::<Runtime>::new();
+
+        for who in [alice, bob, charlie] {
+            staking.set_balance(who, 1000);
+            staking.stake(who, 100).unwrap();
+        }
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        staking.validate(bob).unwrap();
+        staking.set_keys(bob, bob.to_le_bytes().to_vec()).unwrap();
+        staking.nominate(charlie, vec![alice, bob]).unwrap();
+        assert_eq!(staking.chill(charlie), Err(StakingError::NotValidating));
+
+        // A validator chilled after the election misses out on that era's reward
+        run_to_next_era(&mut staking);
+        staking.chill(alice).unwrap();
+        assert_eq!(staking.chill(alice), Err(StakingError::AlreadyChilled));
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(1), Ok(49));
+        assert_eq!(staking.get_free_balance(alice), 900u64);
+        assert_eq!(staking.get_free_balance(bob), 933u64);
+
+        // Nor is it elected, so its nominators back Bob alone
+        assert_eq!(staking.active_validators(), &[bob]);
+        assert_eq!(staking.exposure_of(&bob).total(), 200);
+        assert_eq!(staking.get_staked_balance(alice), 100u64);
+
+        // Validating again puts it back in the next election
+        staking.validate(alice).unwrap();
+        staking.set_keys(alice, alice.to_le_bytes().to_vec()).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.active_validators(), &[alice, bob]);
+
+        // Chilling once the era has ended keeps its reward
+        run_to_next_era(&mut staking);
+        staking.chill(bob).unwrap();
+        assert_eq!(staking.payout_era(3), Ok(98));
+        assert_eq!(staking.get_free_balance(bob), 966u64);
+
+        // Validating again before the payout does not earn back a forfeited reward
+        staking.chill(alice).unwrap();
+        staking.validate(alice).unwrap();
+        run_to_next_era(&mut staking);
+        assert_eq!(staking.payout_era(4), Ok(0));
+        assert_eq!(staking.get_free_balance(alice), 933u64);
     }
 }
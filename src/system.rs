@@ -1,6 +1,118 @@
+use num::traits::{CheckedAdd, CheckedMul, CheckedSub, One, Zero};
+use std::fmt::{self, Debug};
 use std::hash::Hash;
 
 pub trait SystemConfig {
     // Define the account identifier type
-    type AccountId: Eq + Hash + Clone;
+    type AccountId: Eq + Ord + Hash + Clone;
+    // Define the block number type with ability to perform checked arithmetic operations
+    type BlockNumber: Zero + One + CheckedAdd + CheckedSub + CheckedMul + From<u32> + Copy + Ord;
+    // The overarching event type that every pallet's events are converted into
+    type RuntimeEvent;
+    // Output of the runtime's hashing function
+    type Hash: Eq + Hash + Clone + Debug;
+    // Hash arbitrary data, e.g. to commit to a vote without revealing it
+    fn hash(data: &[u8]) -> Self::Hash;
+}
+
+// Error returned by a dispatched call, like `sp_runtime::DispatchError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DispatchError {
+    BadOrigin, // The call was made from an origin it does not accept
+    Module {
+        pallet: &'static str,  // Pallet the call was routed to
+        message: &'static str, // Description of the pallet's error
+    },
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispatchError::BadOrigin => f.write_str("Bad origin"),
+            DispatchError::Module { pallet, message } => write!(f, "{} error: {}", pallet, message),
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {}
+
+// Who a call is made on behalf of, like `frame_system::RawOrigin`
+pub enum Origin<T: SystemConfig> {
+    Root, // The chain itself, e.g. genesis configuration or an enacted proposal
+    Signed(T::AccountId),
+    Council, // The council acting collectively through an approved motion
+    Committee, // The weighted committee acting through a decision that reached its threshold
+}
+
+pub struct SystemPallet<T: SystemConfig> {
+    // Track the current block number
+    block_number: T::BlockNumber,
+    // Events deposited by pallets, in the order they happened
+    events: Vec<T::RuntimeEvent>,
+}
+
+impl<T: SystemConfig> SystemPallet<T> {
+    pub fn new() -> Self {
+        Self {
+            block_number: T::BlockNumber::zero(),
+            events: Vec::new(),
+        }
+    }
+
+    // Get the current block number
+    pub fn block_number(&self) -> T::BlockNumber {
+        self.block_number
+    }
+
+    // Move to the next block
+    pub fn inc_block_number(&mut self) {
+        if let Some(next_block) = self.block_number.checked_add(&T::BlockNumber::one()) {
+            self.block_number = next_block;
+        }
+    }
+
+    // Record an event
+    pub fn deposit_event(&mut self, event: T::RuntimeEvent) {
+        self.events.push(event);
+    }
+
+    // Get all events deposited so far
+    pub fn events(&self) -> &[T::RuntimeEvent] {
+        &self.events
+    }
+
+    // Clear the event log
+    pub fn reset_events(&mut self) {
+        self.events.clear();
+    }
+
+    // Jump directly to a given block (useful for tests and genesis setup)
+    pub fn set_block_number(&mut self, block_number: T::BlockNumber) {
+        self.block_number = block_number;
+    }
+}
+
+impl<T: SystemConfig> Default for SystemPallet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Runtime;
+
+    #[test]
+    fn test_block_number_should_work() {
+        let mut system = SystemPallet::<Runtime>::new();
+        assert_eq!(system.block_number(), 0u64);
+
+        system.inc_block_number();
+        system.inc_block_number();
+        assert_eq!(system.block_number(), 2u64);
+
+        system.set_block_number(100);
+        assert_eq!(system.block_number(), 100u64);
+    }
 }